@@ -0,0 +1,24 @@
+//! `cargo bench --bench scan`: times the CPU-bound scan stages over the
+//! recorded fixtures in `GITHUB_FIXTURES_DIR` (`BENCH_ITERATIONS` runs each,
+//! default 100). The `bench` subcommand runs the same stages as a budget gate.
+
+use sui_contibutors::bench::{self, Corpus};
+
+const DEFAULT_ITERATIONS: u32 = 100;
+
+fn main() {
+    let iterations = std::env::var("BENCH_ITERATIONS").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_ITERATIONS);
+    let dir = bench::fixtures_dir(None);
+    let corpus = match Corpus::load(&dir) {
+        Ok(corpus) => corpus,
+        Err(e) => {
+            eprintln!("Cannot load fixtures from {}: {e}", dir.display());
+            std::process::exit(1);
+        }
+    };
+
+    println!("Benchmarks over {} ({iterations} iterations)", dir.display());
+    for sample in bench::measure_stages(&corpus, iterations) {
+        println!("  {sample}");
+    }
+}
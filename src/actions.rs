@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use crate::{
+    policy::Verdict,
+    scan::{self, ScanLimits, ScanMode, ScanOptions},
+    storage,
+};
+
+// ------------------- Verify -------------------
+
+/// Scans `username` (comma-separated aliases allowed), evaluates `policy`
+/// and prints the outcome as GitHub Actions workflow commands. Returns the
+/// exit code: 0 when the verdict passes, 1 when it fails or the check could
+/// not run.
+pub async fn run(username: &str, policy: Option<&str>, mode: ScanMode) -> i32 {
+    match verify(username, policy, mode).await {
+        Ok(verdict) => {
+            report(username, &verdict);
+            if verdict.is_sui_developer { 0 } else { 1 }
+        }
+        Err(e) => {
+            println!("::error title=Sui developer check::{}", escape_data(&format!("Could not verify {username}: {e}")));
+            1
+        }
+    }
+}
+
+async fn verify(username: &str, policy: Option<&str>, mode: ScanMode) -> Result<Verdict, Box<dyn std::error::Error + Send + Sync>> {
+    let locale = crate::i18n::Locale { lang: "en".to_string() };
+    let usernames = scan::parse_aliases(username).map_err(|m| locale.render(&m))?;
+    let runtime = crate::state::runtime();
+    let policy = runtime.policies.select(policy).map_err(|m| locale.render(&m))?;
+    let token = crate::fixtures::github_token().ok_or("GITHUB_TOKEN is not set")?;
+
+    let client = crate::github::build_client()?;
+    let options = ScanOptions::new(mode, ScanLimits::ceiling());
+    let result = scan::get_user_move_repos(&client, &token, &usernames, options).await?;
+    Ok(policy.evaluate(&result, storage::now_secs()))
+}
+
+/// One annotation per criterion, then the overall verdict, and the
+/// `is_sui_developer` step output when `GITHUB_OUTPUT` is set.
+fn report(username: &str, verdict: &Verdict) {
+    for criterion in &verdict.criteria {
+        let values = criterion
+            .values
+            .iter()
+            .map(|(name, value)| match value {
+                Some(value) => format!("{name}={value}"),
+                None => format!("{name}=unmeasured"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let level = if criterion.passed { "notice" } else { "error" };
+        let mark = if criterion.passed { "passed" } else { "failed" };
+        let message = format!("{} {mark} ({values})", criterion.rule);
+        println!("::{level} title={}::{}", escape_property(&verdict.policy), escape_data(&message));
+    }
+
+    let (level, outcome) = if verdict.is_sui_developer {
+        ("notice", "is a verified Sui developer")
+    } else {
+        ("error", "is not a verified Sui developer")
+    };
+    let message = format!("{username} {outcome} under policy {}", verdict.policy);
+    println!("::{level} title=Sui developer check::{}", escape_data(&message));
+
+    if let Ok(path) = std::env::var("GITHUB_OUTPUT") {
+        let written = std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)
+            .and_then(|mut f| writeln!(f, "is_sui_developer={}", verdict.is_sui_developer));
+        if let Err(e) = written {
+            println!("::warning::{}", escape_data(&format!("Could not write GITHUB_OUTPUT: {e}")));
+        }
+    }
+}
+
+/// Workflow command message escaping.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Workflow command property escaping (`title=`).
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
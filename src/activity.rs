@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use crate::policy::days_since_epoch;
+
+// ------------------- Activity -------------------
+
+/// Weeks looked back over for `active_weeks_last_year`.
+const WEEKS_PER_YEAR: u64 = 52;
+
+/// How sustained a user's Move work is, from the days with counted commits
+/// in their Move repositories. Grant programs weigh steady activity over a
+/// single burst.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Activity {
+    /// Most consecutive days with at least one commit.
+    pub longest_streak_days: u32,
+    /// Of the last 52 weeks (counted back from the scan), those with a commit.
+    pub active_weeks_last_year: u32,
+    /// `active_weeks_last_year` over the weeks since the first of them, from
+    /// 0 to 1: committing every week since starting scores 1, one weekend a
+    /// year ago about 0.02.
+    pub consistency_index: f64,
+}
+
+/// Distinct UTC days (`YYYY-MM-DD`) with one of `commits` (commits API
+/// objects), sorted.
+pub fn commit_days(commits: &[serde_json::Value]) -> Vec<String> {
+    let mut days: Vec<String> = commits
+        .iter()
+        .filter_map(|c| c["commit"]["author"]["date"].as_str()?.get(..10).map(String::from))
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// `days` with the days of `more` added, sorted and distinct.
+pub fn merge_days(mut days: Vec<String>, more: &[String]) -> Vec<String> {
+    days.extend_from_slice(more);
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// Metrics over every repository's commit days as of `now_secs`; `None`
+/// without any commit.
+pub fn activity<'a>(days: impl Iterator<Item = &'a String>, now_secs: u64) -> Option<Activity> {
+    let mut days: Vec<u64> = days.filter_map(|d| days_since_epoch(d)).collect();
+    days.sort_unstable();
+    days.dedup();
+    if days.is_empty() {
+        return None;
+    }
+
+    let mut longest_streak_days = 1;
+    let mut streak = 1;
+    for pair in days.windows(2) {
+        streak = if pair[1] == pair[0] + 1 { streak + 1 } else { 1 };
+        longest_streak_days = longest_streak_days.max(streak);
+    }
+
+    // Week 0 is the seven days up to today.
+    let today = now_secs / 86_400;
+    let mut weeks: Vec<u64> = days
+        .iter()
+        .filter(|day| **day <= today)
+        .map(|day| (today - day) / 7)
+        .filter(|week| *week < WEEKS_PER_YEAR)
+        .collect();
+    weeks.dedup();
+    let active_weeks = weeks.len() as u32;
+    let consistency_index = match weeks.iter().max() {
+        Some(first) => (active_weeks as f64 / (first + 1) as f64 * 100.0).round() / 100.0,
+        None => 0.0,
+    };
+
+    Some(Activity { longest_streak_days, active_weeks_last_year: active_weeks, consistency_index })
+}
@@ -0,0 +1,242 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode, header::AUTHORIZATION},
+};
+use serde::Deserialize;
+
+use crate::{chain, github, state::{self, AppState}, storage::{DeletedUserData, Storage, TemplateRecord}, templates};
+
+// ------------------- Auth -------------------
+
+/// Admin endpoints require `Authorization: Bearer <ADMIN_TOKEN>`; they are
+/// disabled entirely when `ADMIN_TOKEN` is not configured.
+pub fn require_admin(headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let expected = std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty()).ok_or((
+        StatusCode::NOT_FOUND,
+        "admin endpoints are disabled".to_string(),
+    ))?;
+
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(()),
+        _ => Err((StatusCode::UNAUTHORIZED, "invalid admin token".to_string())),
+    }
+}
+
+/// Reviewer endpoints accept the admin token or one of `REVIEWER_TOKENS`
+/// (comma-separated `name:token` pairs) and return who is reviewing:
+/// the reviewer's name, or `admin`. Disabled when neither is configured.
+pub fn require_reviewer(headers: &HeaderMap) -> Result<String, (StatusCode, String)> {
+    let admin = std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+    let reviewers = std::env::var("REVIEWER_TOKENS").unwrap_or_default();
+    let reviewers: Vec<(&str, &str)> = reviewers
+        .split(',')
+        .filter_map(|pair| pair.trim().split_once(':'))
+        .filter(|(name, token)| !name.is_empty() && !token.is_empty())
+        .collect();
+    if admin.is_none() && reviewers.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "reviewer endpoints are disabled".to_string()));
+    }
+
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if admin.is_some_and(|admin| constant_time_eq(provided.as_bytes(), admin.as_bytes())) {
+        return Ok("admin".to_string());
+    }
+    reviewers
+        .iter()
+        .find(|(_, token)| constant_time_eq(provided.as_bytes(), token.as_bytes()))
+        .map(|(name, _)| name.to_string())
+        .ok_or((StatusCode::UNAUTHORIZED, "invalid reviewer token".to_string()))
+}
+
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub fn internal(e: Box<dyn std::error::Error + Send + Sync>) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+// ------------------- Template Registry -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct AddTemplateRequest {
+    url: String,
+}
+
+pub async fn list_templates(
+    headers: HeaderMap,
+    State(storage): State<Storage>,
+) -> Result<Json<Vec<TemplateRecord>>, (StatusCode, String)> {
+    require_admin(&headers)?;
+    storage.list_templates().map(Json).map_err(internal)
+}
+
+/// Registers a template repository and fingerprints it in the background.
+pub async fn add_template(
+    headers: HeaderMap,
+    State(AppState { client, github_token: token, storage, .. }): State<AppState>,
+    Json(body): Json<AddTemplateRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
+    require_admin(&headers)?;
+
+    let repo = github::parse_repo_url(&body.url)
+        .ok_or((StatusCode::BAD_REQUEST, format!("not a GitHub repository URL: {}", body.url)))?;
+    let id = storage.add_template(&repo).map_err(internal)?;
+
+    let job_storage = storage.clone();
+    let job_repo = repo.clone();
+    state::spawn(async move {
+        if let Err(e) = templates::fingerprint_template(&client, &token, &job_storage, id, &job_repo).await {
+            tracing::warn!("Failed to fingerprint template {job_repo}: {e}");
+        }
+    });
+
+    Ok((StatusCode::ACCEPTED, Json(serde_json::json!({ "id": id, "repo": repo }))))
+}
+
+pub async fn remove_template(
+    headers: HeaderMap,
+    Path(id): Path<i64>,
+    State(storage): State<Storage>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    require_admin(&headers)?;
+
+    match storage.remove_template(id).map_err(internal)? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err((StatusCode::NOT_FOUND, format!("template {id} not found"))),
+    }
+}
+
+// ------------------- Scan Cache -------------------
+
+/// Drops every cached scan, then warms `PRELOAD_USERS` again.
+pub async fn flush_cache(
+    headers: HeaderMap,
+    State(AppState { client, github_token: token, storage, .. }): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&headers)?;
+
+    let removed = storage.flush_scans().map_err(internal)?;
+    crate::spawn_preload(client, token, storage);
+    Ok(Json(serde_json::json!({ "scans_removed": removed })))
+}
+
+// ------------------- User Data -------------------
+
+/// `DELETE /users/{username}/data`: erases everything stored about a person
+/// on request, except an opt-out, which keeps them out of public listings
+/// if they are scanned again. Reports already archived on Walrus or IPFS
+/// are content addressed and cannot be recalled.
+pub async fn delete_user_data(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+) -> Result<Json<DeletedUserData>, (StatusCode, String)> {
+    require_admin(&headers)?;
+
+    let deleted = storage.delete_user_data(username.trim()).map_err(internal)?;
+    tracing::info!("Deleted stored data of {username}: {deleted:?}");
+    Ok(Json(deleted))
+}
+
+// ------------------- Wallet Bindings -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct SetWalletsRequest {
+    addresses: Vec<String>,
+}
+
+pub async fn get_wallets(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&headers)?;
+    let addresses = storage.wallets(std::slice::from_ref(&username)).map_err(internal)?;
+    Ok(Json(serde_json::json!({ "username": username, "addresses": addresses })))
+}
+
+/// Replaces the wallet addresses bound to a GitHub user; an empty list unbinds.
+pub async fn set_wallets(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+    Json(body): Json<SetWalletsRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&headers)?;
+
+    if let Some(invalid) = body.addresses.iter().find(|a| !chain::is_valid_address(a)) {
+        return Err((StatusCode::BAD_REQUEST, format!("not a Sui address: {invalid}")));
+    }
+
+    let mut addresses: Vec<String> = Vec::new();
+    for address in body.addresses.iter().map(|a| a.to_ascii_lowercase()) {
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+
+    storage.set_wallets(&username, &addresses).map_err(internal)?;
+    Ok(Json(serde_json::json!({ "username": username, "addresses": addresses })))
+}
+
+// ------------------- Labels -------------------
+
+/// Longest label accepted, e.g. `cohort:lagos-2025`.
+const MAX_LABEL_LEN: usize = 64;
+
+#[derive(Debug, Deserialize)]
+pub struct SetLabelsRequest {
+    labels: Vec<String>,
+}
+
+/// Whether `label` is non-empty, at most `MAX_LABEL_LEN` long and made of
+/// letters, digits and `:`, `-`, `_`, `.`, `/`.
+pub fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= MAX_LABEL_LEN
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '-' | '_' | '.' | '/'))
+}
+
+pub async fn get_labels(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&headers)?;
+    let labels = storage.labels(&username).map_err(internal)?;
+    Ok(Json(serde_json::json!({ "username": username, "labels": labels })))
+}
+
+/// Replaces the labels of a tracked user; an empty list removes them all.
+pub async fn set_labels(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+    Json(body): Json<SetLabelsRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    require_admin(&headers)?;
+
+    let mut labels: Vec<String> = Vec::new();
+    for label in body.labels.iter().map(|l| l.trim()) {
+        if !is_valid_label(label) {
+            return Err((StatusCode::BAD_REQUEST, format!("invalid label: {label:?}")));
+        }
+        if !labels.iter().any(|l| l.eq_ignore_ascii_case(label)) {
+            labels.push(label.to_string());
+        }
+    }
+
+    storage.set_labels(&username, &labels).map_err(internal)?;
+    Ok(Json(serde_json::json!({ "username": username, "labels": labels })))
+}
@@ -0,0 +1,153 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    admin, github,
+    scan::UserMoveFilesResponse,
+    storage::{self, Storage},
+};
+
+// ------------------- Structs -------------------
+
+/// What a reviewer's annotation does to the results it is merged into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    /// A note for other reviewers; changes nothing.
+    Note,
+    /// The repository is boilerplate: left out of `original_repositories`.
+    Boilerplate,
+    /// The repository is original work, even if it matched a template.
+    Original,
+    /// A reviewer confirmed the account belongs to the person (`identity_confirmed`).
+    IdentityConfirmed,
+    #[serde(other)]
+    Unknown,
+}
+
+impl AnnotationKind {
+    /// Whether the annotation applies to one repository rather than the user.
+    fn needs_repo(self) -> bool {
+        matches!(self, AnnotationKind::Boilerplate | AnnotationKind::Original)
+    }
+}
+
+/// A reviewer's note or override on a user or one of their repositories,
+/// merged into every later response for the user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: i64,
+    pub kind: AnnotationKind,
+    /// `owner/repo` for repository annotations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub reviewer: String,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddAnnotationRequest {
+    kind: AnnotationKind,
+    /// Repository URL or `owner/repo`; required for `boilerplate` and `original`.
+    repo: Option<String>,
+    note: Option<String>,
+}
+
+/// Longest note accepted, in characters.
+const MAX_NOTE_CHARS: usize = 2000;
+
+// ------------------- Merging -------------------
+
+/// Attaches the stored annotations of `result.username` to the result and
+/// its repositories and applies their overrides. Later annotations of a
+/// repository win over earlier ones.
+pub fn apply(storage: &Storage, result: &mut UserMoveFilesResponse) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let annotations = storage.annotations(&result.username)?;
+
+    for repo in &mut result.repositories {
+        repo.annotations = annotations
+            .iter()
+            .filter(|a| a.repo.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(&repo.repo_name)))
+            .cloned()
+            .collect();
+    }
+    result.identity_confirmed = annotations.iter().any(|a| a.kind == AnnotationKind::IdentityConfirmed);
+    result.annotations = annotations.into_iter().filter(|a| a.repo.is_none()).collect();
+    Ok(())
+}
+
+/// The reviewer override of a repository's originality, if any: `Some(false)`
+/// for boilerplate, `Some(true)` for confirmed original work.
+pub fn originality(annotations: &[Annotation]) -> Option<bool> {
+    annotations.iter().rev().find_map(|a| match a.kind {
+        AnnotationKind::Boilerplate => Some(false),
+        AnnotationKind::Original => Some(true),
+        _ => None,
+    })
+}
+
+// ------------------- Handlers -------------------
+
+pub async fn list_annotations(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+) -> Result<Json<Vec<Annotation>>, (StatusCode, String)> {
+    admin::require_reviewer(&headers)?;
+    storage.annotations(username.trim()).map(Json).map_err(admin::internal)
+}
+
+/// Records a note or override by the authenticated reviewer.
+pub async fn add_annotation(
+    headers: HeaderMap,
+    Path(username): Path<String>,
+    State(storage): State<Storage>,
+    Json(body): Json<AddAnnotationRequest>,
+) -> Result<(StatusCode, Json<Annotation>), (StatusCode, String)> {
+    let reviewer = admin::require_reviewer(&headers)?;
+
+    if body.kind == AnnotationKind::Unknown {
+        return Err((StatusCode::BAD_REQUEST, "kind must be note, boilerplate, original or identity_confirmed".to_string()));
+    }
+    let repo = match body.repo.as_deref() {
+        Some(url) => Some(github::parse_repo_url(url).ok_or((StatusCode::BAD_REQUEST, format!("not a GitHub repository URL: {url}")))?),
+        None => None,
+    };
+    if body.kind.needs_repo() && repo.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "boilerplate and original annotations need a repo".to_string()));
+    }
+    if body.kind == AnnotationKind::IdentityConfirmed && repo.is_some() {
+        return Err((StatusCode::BAD_REQUEST, "identity_confirmed applies to the user, not a repo".to_string()));
+    }
+    let note = body.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if note.as_ref().is_some_and(|n| n.chars().count() > MAX_NOTE_CHARS) {
+        return Err((StatusCode::BAD_REQUEST, format!("note is longer than {MAX_NOTE_CHARS} characters")));
+    }
+    if body.kind == AnnotationKind::Note && note.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "a note annotation needs a note".to_string()));
+    }
+
+    let mut annotation = Annotation { id: 0, kind: body.kind, repo, note, reviewer, created_at: storage::now_secs() };
+    annotation.id = storage.add_annotation(username.trim(), &annotation).map_err(admin::internal)?;
+    tracing::info!("{} annotated {username}: {:?}", annotation.reviewer, annotation.kind);
+    Ok((StatusCode::CREATED, Json(annotation)))
+}
+
+pub async fn remove_annotation(
+    headers: HeaderMap,
+    Path((username, id)): Path<(String, i64)>,
+    State(storage): State<Storage>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    admin::require_reviewer(&headers)?;
+
+    match storage.remove_annotation(username.trim(), id).map_err(admin::internal)? {
+        true => Ok(StatusCode::NO_CONTENT),
+        false => Err((StatusCode::NOT_FOUND, format!("annotation {id} not found"))),
+    }
+}
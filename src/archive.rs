@@ -0,0 +1,128 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::Instrument;
+
+use crate::scan::UserMoveFilesResponse;
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveBackend {
+    Walrus,
+    Ipfs,
+}
+
+/// Where the canonical JSON report of a scan was archived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveReceipt {
+    pub backend: ArchiveBackend,
+    /// Walrus blob ID or IPFS CID.
+    pub content_id: String,
+    /// Sui object holding the blob (Walrus only, absent if it was already certified).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sui_object_id: Option<String>,
+    pub archived_at: u64,
+}
+
+const DEFAULT_WALRUS_PUBLISHER: &str = "https://publisher.walrus-testnet.walrus.space";
+const DEFAULT_WALRUS_EPOCHS: u32 = 5;
+const DEFAULT_IPFS_API: &str = "http://127.0.0.1:5001";
+
+/// Whether this deployment opted in to archiving with `ARCHIVE_ENABLED`.
+/// Off by default: the default backend publishes reports on the public
+/// Walrus testnet.
+pub fn enabled() -> bool {
+    std::env::var("ARCHIVE_ENABLED").is_ok_and(|v| matches!(v.trim(), "true" | "1" | "yes"))
+}
+
+/// `ARCHIVE_BACKEND` selects `walrus` (default) or `ipfs`.
+pub fn backend() -> Result<ArchiveBackend, Box<dyn std::error::Error + Send + Sync>> {
+    match std::env::var("ARCHIVE_BACKEND").as_deref() {
+        Err(_) | Ok("walrus") => Ok(ArchiveBackend::Walrus),
+        Ok("ipfs") => Ok(ArchiveBackend::Ipfs),
+        Ok(other) => Err(format!("unknown ARCHIVE_BACKEND {other}").into()),
+    }
+}
+
+// ------------------- Core Logic -------------------
+
+/// Serializes the report canonically (sorted keys, no whitespace) so the same
+/// result always produces the same content ID. Any previous receipt and the
+/// timing diagnostics are left out.
+pub fn canonical_json(result: &UserMoveFilesResponse) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut value = serde_json::to_value(result)?;
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("archive");
+        fields.remove("diagnostics");
+    }
+    Ok(serde_json::to_vec(&value)?)
+}
+
+/// Uploads the canonical report to the configured backend.
+#[tracing::instrument(name = "archive", skip_all, fields(username = %result.username))]
+pub async fn archive_report(
+    client: &Client,
+    result: &UserMoveFilesResponse,
+) -> Result<ArchiveReceipt, Box<dyn std::error::Error + Send + Sync>> {
+    let body = canonical_json(result)?;
+
+    match backend()? {
+        ArchiveBackend::Walrus => store_on_walrus(client, body).await,
+        ArchiveBackend::Ipfs => add_to_ipfs(client, body).await,
+    }
+}
+
+async fn store_on_walrus(client: &Client, body: Vec<u8>) -> Result<ArchiveReceipt, Box<dyn std::error::Error + Send + Sync>> {
+    let publisher = std::env::var("WALRUS_PUBLISHER_URL").unwrap_or_else(|_| DEFAULT_WALRUS_PUBLISHER.to_string());
+    let epochs = std::env::var("WALRUS_EPOCHS").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_WALRUS_EPOCHS);
+
+    let resp = client
+        .put(format!("{}/v1/blobs?epochs={}", publisher.trim_end_matches('/'), epochs))
+        .body(body)
+        .send()
+        .instrument(tracing::info_span!("walrus.store"))
+        .await?;
+
+    if !resp.status().is_success() {
+        return Err(format!("Walrus publisher returned {}", resp.status()).into());
+    }
+
+    let reply: serde_json::Value = resp.json().await?;
+    let (content_id, sui_object_id) = if let Some(created) = reply.get("newlyCreated") {
+        (created["blobObject"]["blobId"].as_str(), created["blobObject"]["id"].as_str().map(|s| s.to_string()))
+    } else {
+        (reply["alreadyCertified"]["blobId"].as_str(), None)
+    };
+
+    Ok(ArchiveReceipt {
+        backend: ArchiveBackend::Walrus,
+        content_id: content_id.ok_or("Walrus response did not include a blob ID")?.to_string(),
+        sui_object_id,
+        archived_at: crate::storage::now_secs(),
+    })
+}
+
+async fn add_to_ipfs(client: &Client, body: Vec<u8>) -> Result<ArchiveReceipt, Box<dyn std::error::Error + Send + Sync>> {
+    let api = std::env::var("IPFS_API_URL").unwrap_or_else(|_| DEFAULT_IPFS_API.to_string());
+    let part = reqwest::multipart::Part::bytes(body).file_name("report.json").mime_str("application/json")?;
+
+    let resp = client
+        .post(format!("{}/api/v0/add?cid-version=1&pin=true", api.trim_end_matches('/')))
+        .multipart(reqwest::multipart::Form::new().part("file", part))
+        .send()
+        .instrument(tracing::info_span!("ipfs.add"))
+        .await?;
+
+    if !resp.status().is_success() {
+        return Err(format!("IPFS API returned {}", resp.status()).into());
+    }
+
+    let reply: serde_json::Value = resp.json().await?;
+    Ok(ArchiveReceipt {
+        backend: ArchiveBackend::Ipfs,
+        content_id: reply["Hash"].as_str().ok_or("IPFS response did not include a CID")?.to_string(),
+        sui_object_id: None,
+        archived_at: crate::storage::now_secs(),
+    })
+}
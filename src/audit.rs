@@ -0,0 +1,166 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::{
+    admin, annotations, i18n,
+    scan::RepositoryWithCommits,
+    state::Runtime,
+    storage::{self, Storage, StoredScan},
+};
+
+// ------------------- Audit Samples -------------------
+
+const DEFAULT_SAMPLE_SIZE: usize = 10;
+const MAX_SAMPLE_SIZE: usize = 100;
+
+/// File links listed per repository.
+const MAX_FILES: usize = 10;
+
+/// A reproducible random subset of a cohort's verified participants, for
+/// judges to check by hand. The same seed draws the same sample as long as
+/// the verified participants are unchanged.
+#[derive(Debug, Serialize)]
+pub struct AuditSample {
+    pub cohort: String,
+    pub policy: String,
+    pub seed: String,
+    /// Participants whose latest stored scan passes `policy`, sampled from.
+    pub verified: usize,
+    pub sample: Vec<AuditEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub username: String,
+    pub profile_url: String,
+    pub scanned_at: u64,
+    /// A valid certificate, see `GET /certificates/<id>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate_id: Option<String>,
+    pub repositories: Vec<AuditRepository>,
+}
+
+/// Direct links to what a repository was counted on.
+#[derive(Debug, Serialize)]
+pub struct AuditRepository {
+    pub repo: String,
+    pub repo_url: String,
+    pub commit_count: u32,
+    /// The user's commits in the repository on GitHub.
+    pub commits_url: String,
+    /// The newest counted commit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_commit_url: Option<String>,
+    /// Move sources and manifests the detection rested on.
+    pub files: Vec<String>,
+}
+
+impl AuditRepository {
+    fn new(username: &str, repo: &RepositoryWithCommits) -> Self {
+        let base = format!("https://github.com/{}", repo.repo_name);
+        let mut paths: Vec<&str> = repo.evidence.iter().map(|e| e.path.as_str()).collect();
+        if paths.is_empty() {
+            paths = repo.detections.iter().flat_map(|d| d.evidence.iter().map(String::as_str)).collect();
+        }
+        paths.dedup();
+        AuditRepository {
+            repo: repo.repo_name.clone(),
+            repo_url: repo.repo_url.clone(),
+            commit_count: repo.commit_count,
+            commits_url: format!("{base}/commits?author={}", urlencoding::encode(username)),
+            latest_commit_url: repo.commit_cursor.as_ref().map(|c| format!("{base}/commit/{}", c.sha)),
+            files: paths.into_iter().take(MAX_FILES).map(|path| format!("{base}/blob/HEAD/{path}")).collect(),
+        }
+    }
+}
+
+/// Orders participants by SHA-256 of the seed and their lowercased name, so
+/// the draw is uniform yet reproducible from the seed alone.
+fn draw_rank(seed: &str, username: &str) -> Vec<u8> {
+    let input = format!("{seed}:{}", username.to_lowercase());
+    ring::digest::digest(&ring::digest::SHA256, input.as_bytes()).as_ref().to_vec()
+}
+
+fn fresh_seed() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let mut bytes = [0u8; 8];
+    ring::rand::SystemRandom::new().fill(&mut bytes).map_err(|_| "could not generate a sample seed")?;
+    Ok(hex::encode(bytes))
+}
+
+// ------------------- Handler -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct AuditSampleQuery {
+    n: Option<usize>,
+    /// Seed of an earlier sample to draw it again; a new one otherwise.
+    seed: Option<String>,
+    policy: Option<String>,
+}
+
+/// `GET /cohorts/{id}/audit-sample?n=<count>&seed=<seed>&policy=<name>`:
+/// `n` random participants labelled `cohort:<id>` whose latest stored scan
+/// passes the policy, with links to the files and commits they were
+/// verified on (ADMIN_TOKEN or REVIEWER_TOKENS).
+pub async fn audit_sample(
+    locale: i18n::Locale,
+    headers: HeaderMap,
+    Path(cohort): Path<String>,
+    Query(params): Query<AuditSampleQuery>,
+    State(storage): State<Storage>,
+    State(runtime): State<Arc<Runtime>>,
+) -> Result<Json<AuditSample>, (StatusCode, String)> {
+    let reviewer = admin::require_reviewer(&headers)?;
+    let policy = runtime.policies.select(params.policy.as_deref()).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let n = params.n.unwrap_or(DEFAULT_SAMPLE_SIZE).clamp(1, MAX_SAMPLE_SIZE);
+    let seed = match params.seed.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(seed) => seed.to_string(),
+        None => fresh_seed().map_err(admin::internal)?,
+    };
+
+    let label = format!("cohort:{}", cohort.trim());
+    let participants = storage.labelled_users(&label).map_err(admin::internal)?;
+    if participants.is_empty() {
+        return Err((StatusCode::NOT_FOUND, format!("no users are labelled {label}")));
+    }
+    let now = storage::now_secs();
+    let mut verified: Vec<StoredScan> = Vec::new();
+    for username in &participants {
+        let Some(mut scan) = storage.latest_scan(username).map_err(admin::internal)? else {
+            continue;
+        };
+        // Reviewer overrides count, as in every served verdict.
+        annotations::apply(&storage, &mut scan.result).map_err(admin::internal)?;
+        if policy.evaluate(&scan.result, now).is_sui_developer {
+            verified.push(scan);
+        }
+    }
+    let pool = verified.len();
+    verified.sort_by_cached_key(|scan| draw_rank(&seed, &scan.username));
+    verified.truncate(n);
+
+    let mut sample = Vec::new();
+    for scan in verified {
+        let certificate_id = storage
+            .certificates(&scan.username)
+            .map_err(admin::internal)?
+            .into_iter()
+            .find(|c| c.is_valid(now))
+            .map(|c| c.id);
+        sample.push(AuditEntry {
+            profile_url: format!("https://github.com/{}", scan.username),
+            scanned_at: scan.scanned_at,
+            certificate_id,
+            repositories: scan.result.repositories.iter().map(|r| AuditRepository::new(&scan.username, r)).collect(),
+            username: scan.username,
+        });
+    }
+
+    tracing::info!("Audit sample of {label} drawn for {reviewer}: seed {seed}, {} of {pool} verified", sample.len());
+    Ok(Json(AuditSample { cohort: cohort.trim().to_string(), policy: policy.name.clone(), seed, verified: pool, sample }))
+}
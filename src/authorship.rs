@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+// ------------------- Confidence -------------------
+
+/// How certain we are that a repository's counted commits belong to the
+/// queried person, from the author objects returned by the commits API.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthorshipConfidence {
+    /// Weighted mean over all commits, 0.0–1.0.
+    pub score: f64,
+    /// The commit is linked to one of the queried GitHub accounts.
+    pub login_matched: u32,
+    /// Not linked, but the author email names the account (e.g. its noreply address).
+    pub email_matched: u32,
+    /// Only the author name matches the login.
+    pub name_matched: u32,
+    pub unmatched: u32,
+    /// Created through the GitHub web UI (committer `web-flow`); counted in
+    /// the groups above but weighted lower.
+    pub web_flagged: u32,
+}
+
+const LOGIN_WEIGHT: f64 = 1.0;
+const EMAIL_WEIGHT: f64 = 0.75;
+const NAME_WEIGHT: f64 = 0.4;
+const UNMATCHED_WEIGHT: f64 = 0.1;
+const WEB_FLOW_FACTOR: f64 = 0.8;
+
+/// Scores `commits` (commits API objects) against `usernames`.
+pub fn confidence(commits: &[serde_json::Value], usernames: &[String]) -> AuthorshipConfidence {
+    let is_user = |s: &str| usernames.iter().any(|u| u.eq_ignore_ascii_case(s));
+    let mut result = AuthorshipConfidence::default();
+    let mut total = 0.0;
+
+    for commit in commits {
+        let login = commit["author"]["login"].as_str().unwrap_or_default();
+        let email = commit["commit"]["author"]["email"].as_str().unwrap_or_default();
+        let name = commit["commit"]["author"]["name"].as_str().unwrap_or_default();
+
+        let mut weight = if is_user(login) {
+            result.login_matched += 1;
+            LOGIN_WEIGHT
+        } else if email_names_user(email, &is_user) {
+            result.email_matched += 1;
+            EMAIL_WEIGHT
+        } else if is_user(name.trim()) {
+            result.name_matched += 1;
+            NAME_WEIGHT
+        } else {
+            result.unmatched += 1;
+            UNMATCHED_WEIGHT
+        };
+
+        if commit["committer"]["login"] == "web-flow" {
+            result.web_flagged += 1;
+            weight *= WEB_FLOW_FACTOR;
+        }
+        total += weight;
+    }
+
+    if !commits.is_empty() {
+        result.score = (total / commits.len() as f64 * 100.0).round() / 100.0;
+    }
+    result
+}
+
+impl AuthorshipConfidence {
+    fn commits(&self) -> u32 {
+        self.login_matched + self.email_matched + self.name_matched + self.unmatched
+    }
+
+    /// Merges the confidence of two disjoint sets of commits.
+    pub fn combine(&self, other: &AuthorshipConfidence) -> AuthorshipConfidence {
+        let total = self.commits() + other.commits();
+        let score = if total == 0 {
+            0.0
+        } else {
+            let weighted = self.score * self.commits() as f64 + other.score * other.commits() as f64;
+            (weighted / total as f64 * 100.0).round() / 100.0
+        };
+
+        AuthorshipConfidence {
+            score,
+            login_matched: self.login_matched + other.login_matched,
+            email_matched: self.email_matched + other.email_matched,
+            name_matched: self.name_matched + other.name_matched,
+            unmatched: self.unmatched + other.unmatched,
+            web_flagged: self.web_flagged + other.web_flagged,
+        }
+    }
+}
+
+/// Whether a bare git identity, with no GitHub account linked, belongs to one
+/// of `usernames` by its email or, failing that, its name.
+pub fn identity_matches(name: &str, email: &str, usernames: &[String]) -> bool {
+    let is_user = |s: &str| usernames.iter().any(|u| u.eq_ignore_ascii_case(s));
+    email_names_user(email, &is_user) || is_user(name.trim())
+}
+
+/// `login@...`, `<id>+login@users.noreply.github.com`.
+fn email_names_user(email: &str, is_user: &impl Fn(&str) -> bool) -> bool {
+    let local = email.split('@').next().unwrap_or_default();
+    let local = local.split_once('+').map_or(local, |(_, login)| login);
+    !local.is_empty() && is_user(local)
+}
+
+// ------------------- Bot Exclusion -------------------
+
+/// Patterns matched (case-insensitively, `*` as wildcard) against author and
+/// committer logins and names, in addition to `[bot]` accounts.
+const DEFAULT_BOT_PATTERNS: &str = "dependabot*,renovate*,github-actions*,mergify*,*-bot";
+
+fn bot_patterns() -> &'static [String] {
+    static PATTERNS: OnceLock<Vec<String>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        std::env::var("BOT_PATTERNS")
+            .unwrap_or_else(|_| DEFAULT_BOT_PATTERNS.to_string())
+            .split(',')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect()
+    })
+}
+
+/// Whether a commit was authored or committed by automation.
+pub fn is_bot_commit(commit: &serde_json::Value) -> bool {
+    let identities = [
+        &commit["author"]["login"],
+        &commit["committer"]["login"],
+        &commit["commit"]["author"]["name"],
+        &commit["commit"]["committer"]["name"],
+    ];
+
+    commit["author"]["type"] == "Bot"
+        || commit["committer"]["type"] == "Bot"
+        || identities
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(str::to_lowercase)
+            .any(|id| id.ends_with("[bot]") || bot_patterns().iter().any(|p| glob_match(p, &id)))
+}
+
+/// Removes bot commits in place and returns how many were dropped.
+pub fn exclude_bots(commits: &mut Vec<serde_json::Value>) -> u32 {
+    let before = commits.len();
+    commits.retain(|c| !is_bot_commit(c));
+    (before - commits.len()) as u32
+}
+
+pub fn is_zero(n: &u32) -> bool {
+    *n == 0
+}
+
+/// Matches `text` against `pattern`, where `*` matches any run of characters.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
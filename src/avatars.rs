@@ -0,0 +1,161 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        StatusCode,
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+    },
+    response::{IntoResponse, Response},
+};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::{
+    admin, cache, github, i18n, readonly,
+    state::AppState,
+    storage::{self, Storage},
+};
+
+// ------------------- Avatars -------------------
+
+/// Pixel sizes served; other sizes are rejected so the cache stays bounded.
+const SIZES: [u32; 5] = [40, 80, 120, 240, 460];
+const DEFAULT_SIZE: u32 = 80;
+
+/// Age after which a cached avatar is fetched again (`AVATAR_TTL`).
+const DEFAULT_TTL_SECS: u64 = 7 * 86_400;
+
+/// Largest image stored.
+const MAX_IMAGE_BYTES: usize = 1_000_000;
+
+/// How long clients may reuse a served avatar.
+const CLIENT_MAX_AGE_SECS: u64 = 86_400;
+
+/// A cached GitHub avatar at one size.
+#[derive(Debug, Clone)]
+pub struct Avatar {
+    /// The profile's `avatar_url` the image was fetched from; a profile
+    /// refresh reporting another URL drops the cached sizes.
+    pub avatar_url: String,
+    pub content_type: String,
+    pub image: Vec<u8>,
+    pub fetched_at: u64,
+}
+
+fn ttl_secs() -> u64 {
+    std::env::var("AVATAR_TTL").ok().and_then(|v| cache::parse_duration_secs(&v)).unwrap_or(DEFAULT_TTL_SECS)
+}
+
+/// The user's current avatar URL through REST; `None` for unknown users.
+async fn avatar_url(client: &Client, token: &str, username: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("https://api.github.com/users/{username}");
+    let resp = github::send(github::EndpointClass::Repos, github::get(client, token, &url)).await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(format!("GitHub user lookup failed with {}", resp.status()).into());
+    }
+    let user: serde_json::Value = resp.json().await?;
+    Ok(user["avatar_url"].as_str().map(String::from))
+}
+
+/// Downloads `avatar_url` at `size` pixels from GitHub's avatar CDN.
+async fn download(client: &Client, avatar_url: &str, size: u32) -> Result<Avatar, Box<dyn std::error::Error + Send + Sync>> {
+    let separator = if avatar_url.contains('?') { '&' } else { '?' };
+    let resp = client.get(format!("{avatar_url}{separator}s={size}")).send().await?;
+    if !resp.status().is_success() {
+        return Err(format!("avatar download failed with {}", resp.status()).into());
+    }
+    let content_type = resp.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+    if !content_type.starts_with("image/") {
+        return Err(format!("avatar is not an image ({content_type})").into());
+    }
+    if resp.content_length().is_some_and(|len| len > MAX_IMAGE_BYTES as u64) {
+        return Err(format!("avatar is larger than {MAX_IMAGE_BYTES} bytes").into());
+    }
+    let image = resp.bytes().await?;
+    if image.len() > MAX_IMAGE_BYTES {
+        return Err(format!("avatar is larger than {MAX_IMAGE_BYTES} bytes").into());
+    }
+    Ok(Avatar { avatar_url: avatar_url.to_string(), content_type, image: image.to_vec(), fetched_at: storage::now_secs() })
+}
+
+/// Forgets `username`'s cached avatars when their profile now reports a
+/// different `avatar_url`. Called whenever a profile is fetched.
+pub fn note_profile(storage: &Storage, username: &str, avatar_url: Option<&str>) {
+    match storage.invalidate_avatars(username, avatar_url.unwrap_or_default()) {
+        Ok(0) => {}
+        Ok(dropped) => tracing::info!("Dropped {dropped} cached avatars of {username} after a profile change"),
+        Err(e) => tracing::warn!("Failed to invalidate avatars of {username}: {e}"),
+    }
+}
+
+// ------------------- Handler -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct AvatarQuery {
+    size: Option<u32>,
+}
+
+/// `GET /avatar/{username}?size=<40|80|120|240|460>`: the GitHub avatar of
+/// a user with a stored scan, served from the local cache so frontends need
+/// not hotlink GitHub. Other users get a 404, so the endpoint cannot be used
+/// to fetch and store arbitrary accounts' images.
+pub async fn avatar(
+    locale: i18n::Locale,
+    Path(username): Path<String>,
+    Query(params): Query<AvatarQuery>,
+    State(AppState { client, github_token: token, storage, .. }): State<AppState>,
+) -> Result<Response, (StatusCode, String)> {
+    let size = params.size.unwrap_or(DEFAULT_SIZE);
+    if !SIZES.contains(&size) {
+        let sizes: Vec<String> = SIZES.iter().map(u32::to_string).collect();
+        return Err((StatusCode::BAD_REQUEST, format!("size must be one of {}", sizes.join(", "))));
+    }
+    let username = username.trim();
+    if !github::is_valid_login(username) {
+        return Err((StatusCode::BAD_REQUEST, format!("`{username}` is not a GitHub username")));
+    }
+    if storage.latest_scan(username).map_err(admin::internal)?.is_none() {
+        let message = i18n::Message::new("avatar_not_scanned").arg("username", username);
+        return Err((StatusCode::NOT_FOUND, locale.render(&message)));
+    }
+
+    let fresh = async {
+        let Some(url) = avatar_url(&client, &token, username).await? else {
+            return Ok(None);
+        };
+        download(&client, &url, size).await.map(Some)
+    };
+    let cached = storage.avatar(username, size).map_err(admin::internal)?;
+    let avatar = match cached {
+        // Serve-only deployments keep whatever they have.
+        Some(avatar) if readonly::enabled() => avatar,
+        None if readonly::enabled() => {
+            return Err((StatusCode::NOT_FOUND, format!("no cached {size}px avatar of {username} on this read-only deployment")));
+        }
+        Some(avatar) if storage::now_secs().saturating_sub(avatar.fetched_at) < ttl_secs() => avatar,
+        stale => match fresh.await {
+            Ok(Some(avatar)) => {
+                note_profile(&storage, username, Some(&avatar.avatar_url));
+                storage.save_avatar(username, size, &avatar).map_err(admin::internal)?;
+                avatar
+            }
+            Ok(None) => {
+                let message = i18n::Message::new("user_not_found").arg("username", username);
+                return Err((StatusCode::NOT_FOUND, locale.render(&message)));
+            }
+            // A stale copy beats none while GitHub misbehaves.
+            Err(e) => match stale {
+                Some(avatar) => {
+                    tracing::warn!("Serving a stale avatar of {username}: {e}");
+                    avatar
+                }
+                None => return Err(crate::upstream_error(username, e)),
+            },
+        },
+    };
+
+    let headers = [(CONTENT_TYPE, avatar.content_type), (CACHE_CONTROL, format!("public, max-age={CLIENT_MAX_AGE_SECS}"))];
+    Ok((headers, avatar.image).into_response())
+}
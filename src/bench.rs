@@ -0,0 +1,161 @@
+use std::{
+    collections::BTreeMap,
+    hint::black_box,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    authorship, blobs, fixtures,
+    scan::{self, TreeEntry},
+};
+
+// ------------------- Benchmarks -------------------
+
+/// Tree entries kept per fixture, as with the default scan ceiling.
+const MAX_TREE_ENTRIES: usize = 100_000;
+
+/// The CPU-bound stages of a scan, fed from recorded fixtures so runs are
+/// repeatable and need neither network nor token.
+pub struct Corpus {
+    trees: Vec<serde_json::Value>,
+    sources: Vec<String>,
+    commits: Vec<Vec<serde_json::Value>>,
+}
+
+impl Corpus {
+    /// Sorts fixtures by the GitHub endpoint they recorded.
+    pub fn load(dir: &std::path::Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut corpus = Corpus { trees: Vec::new(), sources: Vec::new(), commits: Vec::new() };
+        for (url, body) in fixtures::recorded(dir)? {
+            if url.contains("/git/trees/") {
+                corpus.trees.extend(serde_json::from_str(&body).ok());
+            } else if url.contains("/git/blobs/") {
+                corpus.sources.push(body);
+            } else if url.contains("/commits?") {
+                corpus.commits.extend(serde_json::from_str(&body).ok());
+            }
+        }
+        Ok(corpus)
+    }
+}
+
+/// Timing of one stage over all iterations.
+pub struct Sample {
+    pub stage: &'static str,
+    pub inputs: usize,
+    pub median: Duration,
+    pub p95: Duration,
+}
+
+impl std::fmt::Display for Sample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<16} {:>6} inputs  median {:>9.3} ms  p95 {:>9.3} ms",
+            self.stage,
+            self.inputs,
+            self.median.as_secs_f64() * 1000.0,
+            self.p95.as_secs_f64() * 1000.0,
+        )
+    }
+}
+
+/// The fixtures directory: `dir`, or `GITHUB_FIXTURES_DIR`.
+pub fn fixtures_dir(dir: Option<&str>) -> PathBuf {
+    dir.map(PathBuf::from).unwrap_or_else(fixtures::dir)
+}
+
+/// Times every stage `iterations` times against `corpus`.
+pub fn measure_stages(corpus: &Corpus, iterations: u32) -> [Sample; 3] {
+    [
+        measure("tree_filtering", corpus.trees.len(), iterations, || tree_filtering(&corpus.trees)),
+        measure("move_parsing", corpus.sources.len(), iterations, || move_parsing(&corpus.sources)),
+        measure("aggregation", corpus.commits.len(), iterations, || aggregation(&corpus.commits)),
+    ]
+}
+
+fn measure(stage: &'static str, inputs: usize, iterations: u32, mut body: impl FnMut()) -> Sample {
+    let mut timings: Vec<Duration> = (0..iterations.max(1))
+        .map(|_| {
+            let started = Instant::now();
+            body();
+            started.elapsed()
+        })
+        .collect();
+    timings.sort_unstable();
+    let at = |q: f64| timings[((timings.len() - 1) as f64 * q).round() as usize];
+    Sample { stage, inputs, median: at(0.5), p95: at(0.95) }
+}
+
+/// Tree parsing and the `.move` / `Move.toml` filtering of the detection step.
+fn tree_filtering(trees: &[serde_json::Value]) {
+    for tree in trees {
+        let entries = scan::parse_tree(tree, MAX_TREE_ENTRIES);
+        let manifests: Vec<&TreeEntry> = entries.iter().filter(|e| scan::is_manifest(&e.path)).collect();
+        let move_files: Vec<&TreeEntry> = entries.iter().filter(|e| e.path.ends_with(".move")).collect();
+        black_box((manifests, move_files));
+    }
+}
+
+/// Line counting, module extraction and fingerprinting of Move sources.
+fn move_parsing(sources: &[String]) {
+    for source in sources {
+        black_box(blobs::analyze_source(source));
+    }
+}
+
+/// Bot exclusion and authorship scoring of each repository's commits,
+/// attributed to the author of its first commit.
+fn aggregation(commits: &[Vec<serde_json::Value>]) {
+    for commits in commits {
+        let mut commits = commits.clone();
+        authorship::exclude_bots(&mut commits);
+        let author = commits.first().and_then(|c| c["author"]["login"].as_str()).unwrap_or_default();
+        black_box(authorship::confidence(&commits, &[author.to_string()]));
+    }
+}
+
+/// The CI budget gate: runs every stage `iterations` times against the
+/// fixtures in `dir` (default `GITHUB_FIXTURES_DIR`) and checks each median
+/// against `budget`, a JSON object of stage -> maximum median in
+/// milliseconds. Returns 1 when a stage is over budget or had no fixtures to
+/// time, so an emptied corpus cannot pass, or when the fixtures or budget
+/// cannot be read; otherwise 0. Plain timing runs are `cargo bench --bench scan`.
+pub fn run(dir: Option<&str>, iterations: u32, budget: &str) -> i32 {
+    let dir = fixtures_dir(dir);
+    let loaded = Corpus::load(&dir).and_then(|corpus| {
+        let budget: BTreeMap<String, f64> = serde_json::from_str(&std::fs::read_to_string(budget)?)?;
+        Ok((corpus, budget))
+    });
+    let (corpus, budget) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Cannot run benchmarks: {e}");
+            return 1;
+        }
+    };
+
+    let samples = measure_stages(&corpus, iterations);
+
+    println!("Benchmarks over {} ({iterations} iterations)", dir.display());
+    let mut failed = false;
+    for sample in &samples {
+        let median_ms = sample.median.as_secs_f64() * 1000.0;
+        let verdict = match budget.get(sample.stage) {
+            Some(_) if sample.inputs == 0 => {
+                failed = true;
+                "FAILED: no inputs to time".to_string()
+            }
+            Some(limit) if median_ms > *limit => {
+                failed = true;
+                format!("OVER budget {limit:.3} ms")
+            }
+            Some(limit) => format!("within {limit:.3} ms"),
+            None => "no budget".to_string(),
+        };
+        println!("  {sample}  {verdict}");
+    }
+
+    if failed { 1 } else { 0 }
+}
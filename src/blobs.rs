@@ -0,0 +1,95 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{edition, pacing, scan, similarity, storage::Storage};
+
+// ------------------- Blob Analysis -------------------
+
+/// What is derived from one Move source file. Keyed by git blob SHA, so it
+/// holds for every copy of the file in any repository or revision.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobAnalysis {
+    /// Lines that are neither blank nor only a comment.
+    pub lines_of_code: u32,
+    /// `address::name` of every `module` declaration.
+    pub modules: Vec<String>,
+    /// Winnowing fingerprints, see `similarity`.
+    pub fingerprints: Vec<i64>,
+    /// Move 2024 syntax the file uses, see `edition`.
+    #[serde(default)]
+    pub move_2024_features: Vec<edition::Feature>,
+}
+
+/// Bumped whenever the analysis changes, so stored results are recomputed.
+pub const ANALYSIS_VERSION: u32 = 2;
+
+/// Analyses the blob `sha` of `repo`, reading a stored result when there is
+/// one and downloading and parsing the file only on a miss. `None` when the
+/// blob cannot be fetched or is not UTF-8.
+pub async fn analyze_move_blob(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    repo: &str,
+    sha: &str,
+) -> Result<Option<BlobAnalysis>, Box<dyn std::error::Error + Send + Sync>> {
+    match storage.blob_analysis(sha, ANALYSIS_VERSION) {
+        Ok(Some(analysis)) => return Ok(Some(analysis)),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Blob analysis cache read failed for {sha}: {e}"),
+    }
+
+    let Some(source) = scan::fetch_blob(client, token, repo, sha).await? else {
+        return Ok(None);
+    };
+    pacing::pause().await;
+
+    let analysis = analyze_source(&source);
+    if let Err(e) = storage.save_blob_analysis(sha, ANALYSIS_VERSION, &analysis) {
+        tracing::warn!("Blob analysis cache write failed for {sha}: {e}");
+    }
+    Ok(Some(analysis))
+}
+
+pub fn analyze_source(source: &str) -> BlobAnalysis {
+    let mut in_block_comment = false;
+    let mut lines_of_code = 0;
+    for line in source.lines() {
+        let mut line = line.trim();
+        if in_block_comment {
+            match line.find("*/") {
+                Some(end) => {
+                    in_block_comment = false;
+                    line = line[end + 2..].trim();
+                }
+                None => continue,
+            }
+        }
+        if let Some(rest) = line.strip_prefix("/*") {
+            in_block_comment = !rest.contains("*/");
+            continue;
+        }
+        if !line.is_empty() && !line.starts_with("//") {
+            lines_of_code += 1;
+        }
+    }
+
+    BlobAnalysis {
+        lines_of_code,
+        modules: module_names(source),
+        fingerprints: similarity::fingerprints(source),
+        move_2024_features: edition::features(source),
+    }
+}
+
+/// `module <address>::<name>` declarations, with or without a body block.
+fn module_names(source: &str) -> Vec<String> {
+    source
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("module ")?;
+            let name: String = rest.chars().take_while(|c| c.is_alphanumeric() || matches!(c, '_' | ':')).collect();
+            name.contains("::").then_some(name)
+        })
+        .collect()
+}
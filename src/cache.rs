@@ -0,0 +1,232 @@
+use std::{
+    collections::HashSet,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{
+    scan::{ScanLimits, ScanMode, ScanOptions, Snapshot, UserMoveFilesResponse},
+    storage::{self, Storage},
+};
+
+// ------------------- Scan Cache -------------------
+
+/// Age (seconds) up to which a stored scan with Move code is served as fresh.
+const DEFAULT_TTL_SECS: u64 = 6 * 60 * 60;
+
+/// Age up to which a scan that found no Move code is served. Shorter than
+/// the positive TTL so newly started Move work shows up sooner.
+const DEFAULT_NEGATIVE_TTL_SECS: u64 = 60 * 60;
+
+/// How long past the TTL a stored scan may still be served while it is
+/// refreshed in the background, unless the client sends `max_stale`.
+const DEFAULT_MAX_STALE_SECS: u64 = 24 * 60 * 60;
+
+/// How long a user confirmed to have no Move code is skipped by batch scans.
+const DEFAULT_NON_DEVELOPER_SKIP_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// How often scans past `SCAN_RETENTION_SECS` are deleted.
+const RETENTION_SWEEP_SECS: u64 = 60 * 60;
+
+fn env_secs(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+pub fn ttl_secs() -> u64 {
+    env_secs("SCAN_CACHE_TTL_SECS", DEFAULT_TTL_SECS)
+}
+
+pub fn negative_ttl_secs() -> u64 {
+    env_secs("SCAN_CACHE_NEGATIVE_TTL_SECS", DEFAULT_NEGATIVE_TTL_SECS)
+}
+
+/// TTL applying to `result`: negative results use the shorter negative TTL.
+fn ttl_for(result: &UserMoveFilesResponse) -> u64 {
+    if result.has_move_files { ttl_secs() } else { negative_ttl_secs() }
+}
+
+/// Sets `negative_cached_until` on a result without Move code scanned at `scanned_at`.
+pub fn mark_negative(result: &mut UserMoveFilesResponse, scanned_at: u64) {
+    if !result.has_move_files {
+        result.negative_cached_until = Some(scanned_at + negative_ttl_secs());
+    }
+}
+
+pub fn non_developer_skip_secs() -> u64 {
+    env_secs("NON_DEVELOPER_SKIP_SECS", DEFAULT_NON_DEVELOPER_SKIP_SECS)
+}
+
+pub fn default_max_stale_secs() -> u64 {
+    env_secs("SCAN_CACHE_MAX_STALE_SECS", DEFAULT_MAX_STALE_SECS)
+}
+
+/// Returns the latest stored scan of the same accounts, mode and limits if it
+/// is within `ttl + max_stale`. `stale` is set on results past the TTL.
+/// Negative results answer any mode, since every scan mode checks the same trees.
+pub fn lookup(
+    storage: &Storage,
+    usernames: &[String],
+    mode: ScanMode,
+    limits: ScanLimits,
+    max_stale_secs: u64,
+) -> Result<Option<UserMoveFilesResponse>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(stored) = storage.latest_scan(&usernames[0])? else {
+        return Ok(None);
+    };
+
+    let result = stored.result;
+    let same_scan = (result.mode == mode || !result.has_move_files)
+        && result.limits == limits
+        && result.aliases.len() == usernames.len() - 1
+        && result.aliases.iter().zip(&usernames[1..]).all(|(a, b)| a.eq_ignore_ascii_case(b));
+    if !same_scan {
+        return Ok(None);
+    }
+
+    let age = storage::now_secs().saturating_sub(stored.scanned_at);
+    let ttl = ttl_for(&result);
+    if age > ttl.saturating_add(max_stale_secs) {
+        return Ok(None);
+    }
+
+    let mut result = UserMoveFilesResponse { cached_at: Some(stored.scanned_at), stale: age > ttl, ..result };
+    mark_negative(&mut result, stored.scanned_at);
+    Ok(Some(result))
+}
+
+/// Like [`lookup`], but the TTL does not apply: the stored result is
+/// returned when it is at most `max_age_secs` old (`min_freshness`).
+pub fn lookup_fresh(
+    storage: &Storage,
+    usernames: &[String],
+    mode: ScanMode,
+    limits: ScanLimits,
+    max_age_secs: u64,
+) -> Result<Option<UserMoveFilesResponse>, Box<dyn std::error::Error + Send + Sync>> {
+    let now = storage::now_secs();
+    Ok(lookup(storage, usernames, mode, limits, u64::MAX)?
+        .filter(|result| result.cached_at.is_some_and(|at| now.saturating_sub(at) <= max_age_secs)))
+}
+
+/// Parses a `min_freshness` duration: whole seconds, or a number followed by
+/// `s`, `m`, `h` or `d` (`90`, `15m`, `2h`, `1d`).
+pub fn parse_duration_secs(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => value.split_at(at),
+        None => (value, "s"),
+    };
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(scale)
+}
+
+/// The stored scan a re-scan with `options` can build on: same accounts,
+/// mode and limits, a plain non-quick scan, with its repository cursors.
+pub fn snapshot(
+    storage: &Storage,
+    usernames: &[String],
+    options: ScanOptions,
+) -> Result<Option<Snapshot>, Box<dyn std::error::Error + Send + Sync>> {
+    if !options.is_plain() || options.mode == ScanMode::Quick {
+        return Ok(None);
+    }
+    let Some(stored) = storage.latest_scan(&usernames[0])? else {
+        return Ok(None);
+    };
+
+    let result = stored.result;
+    let same_scan = result.mode == options.mode
+        && result.limits == options.limits
+        && result.aliases.len() == usernames.len() - 1
+        && result.aliases.iter().zip(&usernames[1..]).all(|(a, b)| a.eq_ignore_ascii_case(b));
+    if !same_scan {
+        return Ok(None);
+    }
+
+    Ok(Some(Snapshot { cursors: storage.repo_cursors(&usernames[0])?, result }))
+}
+
+// ------------------- Background Refresh -------------------
+
+fn refreshing() -> &'static Mutex<HashSet<String>> {
+    static REFRESHING: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+    REFRESHING.get_or_init(Default::default)
+}
+
+/// Held while a background refresh of one user runs; at most one refresh per
+/// user is in flight.
+pub struct RefreshGuard(String);
+
+/// Claims the refresh of `username`, or returns `None` if one is already running.
+pub fn begin_refresh(username: &str) -> Option<RefreshGuard> {
+    let key = username.to_lowercase();
+    let mut set = refreshing().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    set.insert(key.clone()).then_some(RefreshGuard(key))
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        refreshing().lock().unwrap_or_else(|poisoned| poisoned.into_inner()).remove(&self.0);
+    }
+}
+
+// ------------------- Retention -------------------
+
+/// Age past which stored scans, non-developer marks, avatars and
+/// certificates are deleted (`SCAN_RETENTION_SECS`), with the fingerprints
+/// and cursors of users left without a scan; unset keeps them indefinitely.
+pub fn retention_secs() -> Option<u64> {
+    std::env::var("SCAN_RETENTION_SECS").ok().and_then(|v| v.parse().ok()).filter(|secs| *secs > 0)
+}
+
+/// Spawns the hourly sweep enforcing [`retention_secs`], if configured.
+pub fn spawn_retention_job(storage: Storage) {
+    let Some(retention) = retention_secs() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(RETENTION_SWEEP_SECS));
+        loop {
+            interval.tick().await;
+            match storage.prune_before(storage::now_secs().saturating_sub(retention)) {
+                Ok(0) => {}
+                Ok(removed) => tracing::info!("Deleted {removed} scans older than {retention}s"),
+                Err(e) => tracing::warn!("Retention sweep failed: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_secs_reads_each_unit() {
+        assert_eq!(parse_duration_secs("90"), Some(90));
+        assert_eq!(parse_duration_secs("45s"), Some(45));
+        assert_eq!(parse_duration_secs("15m"), Some(15 * 60));
+        assert_eq!(parse_duration_secs("2h"), Some(2 * 60 * 60));
+        assert_eq!(parse_duration_secs(" 1d "), Some(24 * 60 * 60));
+        assert_eq!(parse_duration_secs("0"), Some(0));
+    }
+
+    #[test]
+    fn parse_duration_secs_rejects_malformed_values() {
+        for value in ["", "m", "1w", "1.5h", "-1", "15 m", "1h30m", "1H", "abc"] {
+            assert_eq!(parse_duration_secs(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn parse_duration_secs_rejects_overflow() {
+        assert_eq!(parse_duration_secs(&format!("{}d", u64::MAX / 86_400 + 1)), None);
+        assert_eq!(parse_duration_secs("99999999999999999999"), None);
+    }
+}
@@ -0,0 +1,139 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n, notify,
+    scan::UserMoveFilesResponse,
+    storage::{self, Storage},
+};
+
+// ------------------- Certificates -------------------
+
+/// How long a certificate stays valid after it is issued.
+const DEFAULT_TTL_SECS: u64 = 90 * 24 * 60 * 60;
+
+fn ttl_secs() -> u64 {
+    std::env::var("CERTIFICATE_TTL_SECS").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_TTL_SECS)
+}
+
+/// A stored record that `username` passed `policy`, so third parties can
+/// cite its ID instead of re-running the scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Certificate {
+    pub id: String,
+    pub username: String,
+    pub policy: String,
+    /// Commit-weighted authorship confidence (0.0–1.0) at issue time; `None`
+    /// for quick scans, which do not attribute commits.
+    pub score: Option<f64>,
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+impl Certificate {
+    pub fn is_valid(&self, now_secs: u64) -> bool {
+        now_secs < self.expires_at
+    }
+}
+
+/// Commit-weighted mean of the repositories' authorship confidence.
+pub fn score(result: &UserMoveFilesResponse) -> Option<f64> {
+    let (weighted, commits) = result
+        .repositories
+        .iter()
+        .filter_map(|r| r.confidence.as_ref().map(|c| (c.score, r.commit_count)))
+        .fold((0.0, 0u32), |(weighted, commits), (score, count)| (weighted + score * count as f64, commits + count));
+    (commits > 0).then(|| (weighted / commits as f64 * 100.0).round() / 100.0)
+}
+
+/// Sets `certificate_id` on a result whose verdict passed, reusing the
+/// user's still-valid certificate for the same policy or issuing a new one.
+pub fn certify(storage: &Storage, result: &mut UserMoveFilesResponse) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let Some(verdict) = result.verdict.as_ref().filter(|v| v.is_sui_developer) else {
+        return Ok(());
+    };
+
+    let now = storage::now_secs();
+    let existing = storage
+        .certificates(&result.username)?
+        .into_iter()
+        .find(|c| c.policy == verdict.policy && c.is_valid(now));
+    let certificate = match existing {
+        Some(certificate) => certificate,
+        None => {
+            let certificate = Certificate {
+                id: uuid::Uuid::new_v4().to_string(),
+                username: result.username.clone(),
+                policy: verdict.policy.clone(),
+                score: score(result),
+                issued_at: now,
+                expires_at: now + ttl_secs(),
+            };
+            storage.save_certificate(&certificate)?;
+            tracing::info!("Issued certificate {} to {} under {}", certificate.id, certificate.username, certificate.policy);
+            let summary = format!("{} verified as a Sui developer under the {} policy", certificate.username, certificate.policy);
+            notify::publish(
+                notify::Event::new(notify::EventKind::DeveloperVerified, summary, serde_json::to_value(&certificate)?)
+                    .username(&certificate.username)
+                    .policy(&certificate.policy),
+            );
+            certificate
+        }
+    };
+
+    result.certificate_id = Some(certificate.id);
+    Ok(())
+}
+
+// ------------------- Handlers -------------------
+
+#[derive(Debug, Serialize)]
+pub struct CertificateView {
+    #[serde(flatten)]
+    certificate: Certificate,
+    /// False once `expires_at` has passed.
+    valid: bool,
+}
+
+impl From<Certificate> for CertificateView {
+    fn from(certificate: Certificate) -> Self {
+        let valid = certificate.is_valid(storage::now_secs());
+        CertificateView { certificate, valid }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CertificatesQuery {
+    username: String,
+}
+
+fn internal(e: Box<dyn std::error::Error + Send + Sync>) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+pub async fn get_certificate(
+    locale: i18n::Locale,
+    Path(id): Path<String>,
+    State(storage): State<Storage>,
+) -> Result<Json<CertificateView>, (StatusCode, String)> {
+    match storage.certificate(&id).map_err(internal)? {
+        Some(certificate) => Ok(Json(certificate.into())),
+        None => {
+            let message = i18n::Message::new("certificate_not_found").arg("id", &id);
+            Err((StatusCode::NOT_FOUND, locale.render(&message)))
+        }
+    }
+}
+
+/// Every certificate issued to a user, newest first, expired ones included.
+pub async fn list_certificates(
+    Query(params): Query<CertificatesQuery>,
+    State(storage): State<Storage>,
+) -> Result<Json<Vec<CertificateView>>, (StatusCode, String)> {
+    let certificates = storage.certificates(params.username.trim()).map_err(internal)?;
+    Ok(Json(certificates.into_iter().map(CertificateView::from).collect()))
+}
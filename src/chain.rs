@@ -0,0 +1,335 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+    sync::{Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::Instrument;
+
+// ------------------- Structs -------------------
+
+/// On-chain evidence for the wallet addresses bound to a scanned user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainActivity {
+    /// `SUI_NETWORK` at the time of the scan.
+    #[serde(default)]
+    pub network: Network,
+    /// Fullnode preferred when the activity was read.
+    pub rpc_url: String,
+    pub transaction_count: u64,
+    pub packages_published: u32,
+    /// Unix milliseconds of the earliest and latest transaction across all addresses.
+    pub first_activity_ms: Option<u64>,
+    pub last_activity_ms: Option<u64>,
+    pub addresses: Vec<AddressActivity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressActivity {
+    pub address: String,
+    /// Transactions sent by the address.
+    pub transaction_count: u64,
+    /// True when `CHAIN_MAX_PAGES` was reached, so the counts are lower bounds.
+    pub transaction_count_capped: bool,
+    pub packages_published: u32,
+    pub first_activity_ms: Option<u64>,
+    pub last_activity_ms: Option<u64>,
+}
+
+/// Transactions per `suix_queryTransactionBlocks` page (the fullnode maximum).
+const PAGE_SIZE: u32 = 50;
+
+const DEFAULT_MAX_PAGES: u32 = 10;
+
+/// Accepts `0x`-prefixed hex addresses of up to 32 bytes.
+pub fn is_valid_address(address: &str) -> bool {
+    address
+        .strip_prefix("0x")
+        .is_some_and(|hex| !hex.is_empty() && hex.len() <= 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+// ------------------- Fullnodes -------------------
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+    /// Stored results from before the network was recorded, or from a newer release.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl Network {
+    pub fn name(self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+            Network::Unknown => "unknown",
+        }
+    }
+
+    fn default_url(self) -> &'static str {
+        match self {
+            Network::Mainnet => "https://fullnode.mainnet.sui.io:443",
+            Network::Testnet | Network::Unknown => "https://fullnode.testnet.sui.io:443",
+            Network::Devnet => "https://fullnode.devnet.sui.io:443",
+        }
+    }
+}
+
+/// The network chain queries go to: `SUI_NETWORK` (default `testnet`).
+pub fn network() -> Result<Network, String> {
+    match std::env::var("SUI_NETWORK").unwrap_or_default().trim().to_lowercase().as_str() {
+        "mainnet" => Ok(Network::Mainnet),
+        "" | "testnet" => Ok(Network::Testnet),
+        "devnet" => Ok(Network::Devnet),
+        other => Err(format!("SUI_NETWORK must be mainnet, testnet or devnet, got {other}")),
+    }
+}
+
+/// A fullnode an endpoint list can fail over from.
+struct Endpoint {
+    url: String,
+    down_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_up(&self) -> bool {
+        lock(&self.down_until).is_none_or(|until| Instant::now() >= until)
+    }
+
+    fn mark(&self, up: bool) {
+        *lock(&self.down_until) = (!up).then(|| Instant::now() + cooldown());
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// How long a failing fullnode is skipped before it is tried again.
+const DEFAULT_COOLDOWN_SECS: u64 = 60;
+
+/// Interval of the background fullnode health checks.
+const DEFAULT_HEALTH_INTERVAL_SECS: u64 = 30;
+
+fn env_secs(name: &str, default: u64) -> Duration {
+    Duration::from_secs(std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default))
+}
+
+fn cooldown() -> Duration {
+    env_secs("SUI_RPC_COOLDOWN_SECS", DEFAULT_COOLDOWN_SECS)
+}
+
+/// Fullnodes of the active network, in preference order: the comma-separated
+/// `SUI_RPC_URLS_<NETWORK>` (e.g. `SUI_RPC_URLS_MAINNET`), else `SUI_RPC_URL`,
+/// else the public Mysten Labs fullnode. Loaded once per process.
+fn endpoints() -> &'static [Endpoint] {
+    static ENDPOINTS: OnceLock<Vec<Endpoint>> = OnceLock::new();
+    ENDPOINTS.get_or_init(|| {
+        let network = network().unwrap_or_else(|e| {
+            tracing::warn!("{e}; using testnet");
+            Network::Testnet
+        });
+
+        let configured = std::env::var(format!("SUI_RPC_URLS_{}", network.name().to_uppercase()))
+            .or_else(|_| std::env::var("SUI_RPC_URL"))
+            .unwrap_or_default();
+        let mut urls: Vec<String> =
+            configured.split(',').map(str::trim).filter(|u| !u.is_empty()).map(String::from).collect();
+        if urls.is_empty() {
+            urls.push(network.default_url().to_string());
+        }
+
+        urls.into_iter().map(|url| Endpoint { url, down_until: Mutex::new(None) }).collect()
+    })
+}
+
+/// The fullnode currently preferred: the first healthy endpoint.
+pub fn rpc_url() -> String {
+    let endpoints = endpoints();
+    endpoints.iter().find(|e| e.is_up()).unwrap_or(&endpoints[0]).url.clone()
+}
+
+/// Pings every configured fullnode every `SUI_RPC_HEALTH_INTERVAL_SECS` so
+/// failed ones rejoin the rotation as soon as they recover.
+pub fn spawn_health_checks(client: Client) {
+    if endpoints().len() < 2 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(env_secs("SUI_RPC_HEALTH_INTERVAL_SECS", DEFAULT_HEALTH_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            for endpoint in endpoints() {
+                let healthy = matches!(
+                    send(&client, &endpoint.url, "sui_getLatestCheckpointSequenceNumber", &json!([])).await,
+                    Ok(Ok(_))
+                );
+                if healthy != endpoint.is_up() {
+                    tracing::info!("Sui fullnode {} is {}", endpoint.url, if healthy { "back up" } else { "down" });
+                }
+                endpoint.mark(healthy);
+            }
+        }
+    });
+}
+
+// ------------------- Core Logic -------------------
+
+/// Sends one JSON-RPC request and returns `result`. Healthy fullnodes are
+/// tried in order, then the ones cooling down; a fullnode that cannot be
+/// reached or answers with an HTTP error is skipped for `SUI_RPC_COOLDOWN_SECS`.
+/// JSON-RPC errors are the method's answer and are returned without failover.
+pub async fn rpc_request(
+    client: &Client,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+    let endpoints = endpoints();
+    let (up, down): (Vec<&Endpoint>, Vec<&Endpoint>) = endpoints.iter().partition(|e| e.is_up());
+
+    let mut last_error = String::new();
+    for endpoint in up.into_iter().chain(down) {
+        match send(client, &endpoint.url, method, &params).await {
+            Ok(reply) => {
+                endpoint.mark(true);
+                return reply.map_err(Into::into);
+            }
+            Err(e) => {
+                tracing::warn!("Sui fullnode {} failed {method}: {e}", endpoint.url);
+                endpoint.mark(false);
+                last_error = e;
+            }
+        }
+    }
+
+    Err(format!("Sui RPC {method} failed on every fullnode: {last_error}").into())
+}
+
+/// The outer error is a transport failure worth failing over; the inner one
+/// a JSON-RPC error returned by a working fullnode.
+async fn send(
+    client: &Client,
+    url: &str,
+    method: &str,
+    params: &serde_json::Value,
+) -> Result<Result<serde_json::Value, String>, String> {
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let resp = client
+        .post(url)
+        .json(&body)
+        .send()
+        .instrument(tracing::info_span!("sui.rpc", method, url))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status()));
+    }
+
+    let mut reply: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+    if let Some(error) = reply.get("error") {
+        return Ok(Err(format!("Sui RPC {method} error: {}", error["message"].as_str().unwrap_or("unknown"))));
+    }
+    Ok(Ok(reply["result"].take()))
+}
+
+/// Summarises the on-chain activity of `addresses`.
+#[tracing::instrument(name = "chain_activity", skip(client))]
+pub async fn chain_activity(
+    client: &Client,
+    addresses: &[String],
+) -> Result<ChainActivity, Box<dyn std::error::Error + Send + Sync>> {
+    let mut activity = Vec::new();
+    for address in addresses {
+        activity.push(address_activity(client, address).await?);
+    }
+
+    Ok(ChainActivity {
+        network: network().unwrap_or(Network::Unknown),
+        rpc_url: rpc_url(),
+        transaction_count: activity.iter().map(|a| a.transaction_count).sum(),
+        packages_published: activity.iter().map(|a| a.packages_published).sum(),
+        first_activity_ms: activity.iter().filter_map(|a| a.first_activity_ms).min(),
+        last_activity_ms: activity.iter().filter_map(|a| a.last_activity_ms).max(),
+        addresses: activity,
+    })
+}
+
+async fn address_activity(
+    client: &Client,
+    address: &str,
+) -> Result<AddressActivity, Box<dyn std::error::Error + Send + Sync>> {
+    let max_pages = std::env::var("CHAIN_MAX_PAGES").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_MAX_PAGES);
+    let query = json!({
+        "filter": { "FromAddress": address },
+        "options": { "showObjectChanges": true }
+    });
+
+    let mut transaction_count = 0u64;
+    let mut packages_published = 0u32;
+    let mut last_activity_ms = None;
+    let mut oldest_seen_ms = None;
+    let mut cursor = serde_json::Value::Null;
+    let mut capped = false;
+
+    // Newest first, so the first transaction seen is the latest activity.
+    for page in 1..=max_pages {
+        let result = rpc_request(client, "suix_queryTransactionBlocks", json!([query, cursor, PAGE_SIZE, true])).await?;
+        let data = result["data"].as_array().cloned().unwrap_or_default();
+
+        for tx in &data {
+            transaction_count += 1;
+            let timestamp = timestamp_ms(tx);
+            last_activity_ms = last_activity_ms.or(timestamp);
+            oldest_seen_ms = timestamp.or(oldest_seen_ms);
+            packages_published += tx["objectChanges"]
+                .as_array()
+                .map(|changes| changes.iter().filter(|c| c["type"] == "published").count() as u32)
+                .unwrap_or(0);
+        }
+
+        if !result["hasNextPage"].as_bool().unwrap_or(false) {
+            break;
+        }
+        if page == max_pages {
+            capped = true;
+            break;
+        }
+        cursor = result["nextCursor"].clone();
+    }
+
+    // The oldest page was never reached; ask for it directly.
+    let first_activity_ms = if capped {
+        let result = rpc_request(
+            client,
+            "suix_queryTransactionBlocks",
+            json!([{ "filter": { "FromAddress": address } }, null, 1, false]),
+        )
+        .await?;
+        result["data"].get(0).and_then(timestamp_ms)
+    } else {
+        oldest_seen_ms
+    };
+
+    Ok(AddressActivity {
+        address: address.to_string(),
+        transaction_count,
+        transaction_count_capped: capped,
+        packages_published,
+        first_activity_ms,
+        last_activity_ms,
+    })
+}
+
+/// Fullnodes encode `timestampMs` as a decimal string.
+fn timestamp_ms(tx: &serde_json::Value) -> Option<u64> {
+    tx["timestampMs"].as_str().and_then(|t| t.parse().ok())
+}
@@ -0,0 +1,191 @@
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+};
+use base64::Engine;
+use reqwest::Client;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    authorship, github, i18n, queue,
+    scan::{self, ScanLimits},
+    storage,
+};
+
+// ------------------- Signing Key -------------------
+
+/// The attestation key: `ATTESTATION_SIGNING_KEY`, a hex-encoded 32-byte
+/// Ed25519 seed. `None` when unset.
+pub fn signing_key() -> Result<Option<Ed25519KeyPair>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(seed) = std::env::var("ATTESTATION_SIGNING_KEY").ok().filter(|k| !k.trim().is_empty()) else {
+        return Ok(None);
+    };
+    let seed = hex::decode(seed.trim()).map_err(|e| format!("ATTESTATION_SIGNING_KEY is not hex: {e}"))?;
+    let key = Ed25519KeyPair::from_seed_unchecked(&seed)
+        .map_err(|_| "ATTESTATION_SIGNING_KEY must be a 32-byte Ed25519 seed")?;
+    Ok(Some(key))
+}
+
+fn configured_key() -> Result<Ed25519KeyPair, (StatusCode, String)> {
+    signing_key()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "ATTESTATION_SIGNING_KEY is not set".to_string()))
+}
+
+// ------------------- Claims -------------------
+
+/// Relative difference from the observed count a claim may have and still hold.
+const DEFAULT_TOLERANCE: f64 = 0.1;
+
+#[derive(Debug, Deserialize)]
+pub struct ClaimRequest {
+    /// A GitHub user, or comma-separated aliases of one person.
+    username: String,
+    /// Repository URL or `owner/repo`.
+    repo: String,
+    claimed_commits: u32,
+    /// Fraction of the claimed count the observed count may differ by (default 0.1).
+    tolerance: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimVerdict {
+    Attest,
+    Deny,
+}
+
+/// The signed statement: what was claimed, what was counted and whether the
+/// claim holds. Signed as its compact JSON serialization.
+#[derive(Debug, Serialize)]
+pub struct Attestation {
+    pub username: String,
+    pub repo: String,
+    pub claimed_commits: u32,
+    pub tolerance: f64,
+    /// Non-bot commits by the user on the default branch, as scans count them.
+    pub observed_commits: u32,
+    /// The count stopped at the commit page limit, so the user may have more.
+    pub observed_is_lower_bound: bool,
+    pub verdict: ClaimVerdict,
+    pub checked_at: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignedAttestation {
+    pub attestation: Attestation,
+    /// Ed25519 signature over the JSON of `attestation`, base64url.
+    pub signature: String,
+    /// Hex public key that verifies `signature`, as `GET /verify-claim/key` reports it.
+    pub public_key: String,
+}
+
+/// Whether `claimed` matches `observed` within `tolerance` of the claim. A
+/// lower-bound count cannot refute a claim above it.
+fn holds(claimed: u32, observed: u32, lower_bound: bool, tolerance: f64) -> bool {
+    let slack = (claimed as f64 * tolerance).ceil() as u32;
+    let too_low = claimed.saturating_add(slack) < observed;
+    let too_high = claimed > observed.saturating_add(slack);
+    !too_low && (lower_bound || !too_high)
+}
+
+fn sign(key: &Ed25519KeyPair, attestation: Attestation) -> Result<SignedAttestation, serde_json::Error> {
+    let signature = key.sign(&serde_json::to_vec(&attestation)?);
+    Ok(SignedAttestation {
+        attestation,
+        signature: base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(signature.as_ref()),
+        public_key: hex::encode(key.public_key().as_ref()),
+    })
+}
+
+// ------------------- Handlers -------------------
+
+/// `POST /verify-claim`: counts the user's commits in the repository and
+/// returns a signed attestation or denial of the claimed number.
+pub async fn verify_claim(
+    locale: i18n::Locale,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+    Json(body): Json<ClaimRequest>,
+) -> Result<Json<SignedAttestation>, (StatusCode, String)> {
+    let key = configured_key()?;
+    let usernames = scan::parse_aliases(&body.username).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let Some(repo) = github::parse_repo_url(&body.repo) else {
+        let message = i18n::Message::new("invalid_repo_url").arg("url", &body.repo);
+        return Err((StatusCode::BAD_REQUEST, locale.render(&message)));
+    };
+    let tolerance = body.tolerance.unwrap_or(DEFAULT_TOLERANCE);
+    if !(0.0..=1.0).contains(&tolerance) {
+        return Err((StatusCode::BAD_REQUEST, "tolerance must be between 0 and 1".to_string()));
+    }
+
+    let max_pages = ScanLimits::ceiling().max_commit_pages;
+    let count = async {
+        let mut commits = scan::fetch_commits(&client, &token, &repo, &usernames, max_pages).await?;
+        let lower_bound = commits.len() as u32 >= max_pages * 100;
+        authorship::exclude_bots(&mut commits);
+        Ok((commits.len() as u32, lower_bound))
+    };
+    let (observed_commits, observed_is_lower_bound) =
+        queue::run(queue::Lane::Interactive, count).await.map_err(|e| crate::upstream_error(&body.username, e))?;
+
+    let verdict = match holds(body.claimed_commits, observed_commits, observed_is_lower_bound, tolerance) {
+        true => ClaimVerdict::Attest,
+        false => ClaimVerdict::Deny,
+    };
+    tracing::info!("Claim of {} commits by {} in {repo}: {verdict:?} ({observed_commits} counted)", body.claimed_commits, usernames[0]);
+    let attestation = Attestation {
+        username: usernames[0].clone(),
+        repo,
+        claimed_commits: body.claimed_commits,
+        tolerance,
+        observed_commits,
+        observed_is_lower_bound,
+        verdict,
+        checked_at: storage::now_secs(),
+    };
+    sign(&key, attestation).map(Json).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// `GET /verify-claim/key`: the public key attestations are signed with.
+pub async fn signing_public_key() -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let key = configured_key()?;
+    Ok(Json(serde_json::json!({ "algorithm": "Ed25519", "public_key": hex::encode(key.public_key().as_ref()) })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_claims_hold() {
+        assert!(holds(40, 40, false, 0.0));
+        assert!(!holds(40, 41, false, 0.0));
+        assert!(!holds(40, 39, false, 0.0));
+    }
+
+    #[test]
+    fn tolerance_is_relative_to_the_claim_and_rounded_up() {
+        // 10% of 41 rounds up to 5 commits either way.
+        assert!(holds(41, 46, false, 0.1));
+        assert!(holds(41, 36, false, 0.1));
+        assert!(!holds(41, 47, false, 0.1));
+        assert!(!holds(41, 35, false, 0.1));
+    }
+
+    #[test]
+    fn lower_bounds_only_refute_claims_below_them() {
+        assert!(holds(20_000, 10_000, true, 0.0));
+        assert!(holds(9_600, 10_000, true, 0.05));
+        assert!(!holds(9_000, 10_000, true, 0.05));
+    }
+
+    #[test]
+    fn zero_claims() {
+        assert!(holds(0, 0, false, 0.5));
+        assert!(!holds(0, 1, false, 0.5));
+        assert!(holds(0, 0, true, 0.0));
+    }
+}
@@ -0,0 +1,77 @@
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use crate::scan::{OwnedRepository, TreeEntry};
+
+// ------------------- Ruleset -------------------
+
+/// Category name -> keywords. A keyword matches any word of a repository's
+/// topics, description or Move module names that starts with it, so `nft`
+/// also matches `nfts`.
+pub type Ruleset = BTreeMap<String, Vec<String>>;
+
+const DEFAULT_RULES: &[(&str, &[&str])] = &[
+    ("defi", &["defi", "dex", "swap", "amm", "lend", "borrow", "vault", "stak", "yield", "liquidity", "pool", "perp", "stablecoin", "farm"]),
+    ("nft", &["nft", "collectible", "kiosk", "mint", "marketplace", "royalt"]),
+    ("gaming", &["game", "gaming", "arena", "battle", "quest", "player", "hero", "dungeon", "card"]),
+    ("infra", &["sdk", "indexer", "bridge", "wallet", "oracle", "multisig", "framework", "infra", "tooling", "cli", "rpc", "util"]),
+    ("tutorial", &["tutorial", "example", "hello", "demo", "course", "workshop", "learn", "sample", "bootcamp", "intro", "exercise"]),
+];
+
+/// The active ruleset: the JSON file at `CATEGORY_RULES_PATH` (an object of
+/// category -> keyword list) when set and readable, otherwise the built-in
+/// rules. Loaded once per process.
+pub fn ruleset() -> &'static Ruleset {
+    static RULES: OnceLock<Ruleset> = OnceLock::new();
+    RULES.get_or_init(|| {
+        if let Ok(path) = std::env::var("CATEGORY_RULES_PATH") {
+            match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|raw| {
+                serde_json::from_str::<Ruleset>(&raw).map_err(|e| e.to_string())
+            }) {
+                Ok(rules) => return normalise(rules),
+                Err(e) => tracing::warn!("Ignoring CATEGORY_RULES_PATH={path}: {e}"),
+            }
+        }
+
+        DEFAULT_RULES
+            .iter()
+            .map(|(category, keywords)| (category.to_string(), keywords.iter().map(|k| k.to_string()).collect()))
+            .collect()
+    })
+}
+
+fn normalise(rules: Ruleset) -> Ruleset {
+    rules
+        .into_iter()
+        .map(|(category, keywords)| (category.to_lowercase(), keywords.into_iter().map(|k| k.to_lowercase()).collect()))
+        .collect()
+}
+
+// ------------------- Classification -------------------
+
+/// Categories of `repo` under `rules`, using its topics, description and the
+/// stems of its `.move` files as module names.
+pub fn classify(rules: &Ruleset, repo: &OwnedRepository, move_files: &[TreeEntry]) -> Vec<String> {
+    let module_names = move_files.iter().filter_map(|f| {
+        let file = f.path.rsplit('/').next()?;
+        file.strip_suffix(".move")
+    });
+
+    let text = repo
+        .topics
+        .iter()
+        .map(String::as_str)
+        .chain(repo.description.as_deref())
+        .chain(repo.name.rsplit('/').next())
+        .chain(module_names)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+
+    let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+
+    rules
+        .iter()
+        .filter(|(_, keywords)| keywords.iter().any(|k| words.iter().any(|w| w.starts_with(k.as_str()))))
+        .map(|(category, _)| category.clone())
+        .collect()
+}
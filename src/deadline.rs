@@ -0,0 +1,166 @@
+use axum::{
+    Json,
+    extract::{MatchedPath, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{cache, scan::StageDiagnostics};
+
+// ------------------- Budgets -------------------
+
+/// Handling time allowed on routes without a budget of their own.
+const DEFAULT_BUDGET_SECS: u64 = 30;
+
+/// Built-in budgets of the scanning routes, which wait on GitHub.
+const ROUTE_BUDGETS: &[(&str, u64)] = &[
+    ("/check-sui-developer", 120),
+    ("/check-sui-developers", 600),
+    ("/check-repos", 300),
+    ("/org-external-contributors", 300),
+    ("/ecosystem-graph", 120),
+    ("/resolve-email", 60),
+    ("/verify-claim", 120),
+];
+
+/// Maximum handling time per route: the built-in budgets, overridden by
+/// `ROUTE_DEADLINES`, comma-separated `<route>=<duration>` pairs using the
+/// routes as registered (`/profile/{username}`), with `*` for every other
+/// route. A duration of `0` disables the deadline.
+struct Budgets {
+    routes: HashMap<String, u64>,
+    default_secs: u64,
+}
+
+fn budgets() -> &'static Budgets {
+    static BUDGETS: OnceLock<Budgets> = OnceLock::new();
+    BUDGETS.get_or_init(|| {
+        let mut budgets = Budgets {
+            routes: ROUTE_BUDGETS.iter().map(|(route, secs)| (route.to_string(), *secs)).collect(),
+            default_secs: DEFAULT_BUDGET_SECS,
+        };
+        match parse(&std::env::var("ROUTE_DEADLINES").unwrap_or_default()) {
+            Ok(overrides) => {
+                for (route, secs) in overrides {
+                    match route.as_str() {
+                        "*" => budgets.default_secs = secs,
+                        _ => {
+                            budgets.routes.insert(route, secs);
+                        }
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Ignoring ROUTE_DEADLINES: {e}"),
+        }
+        budgets
+    })
+}
+
+/// Parses `ROUTE_DEADLINES`.
+pub fn parse(spec: &str) -> Result<Vec<(String, u64)>, String> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (route, duration) = pair.split_once('=').ok_or(format!("`{pair}` is not <route>=<duration>"))?;
+            let route = route.trim();
+            if route != "*" && !route.starts_with('/') {
+                return Err(format!("`{route}` is not a route"));
+            }
+            let secs = cache::parse_duration_secs(duration).ok_or(format!("`{duration}` is not a duration"))?;
+            Ok((route.to_string(), secs))
+        })
+        .collect()
+}
+
+impl Budgets {
+    fn of(&self, route: &str) -> Option<Duration> {
+        let secs = self.routes.get(route).copied().unwrap_or(self.default_secs);
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+// ------------------- Progress -------------------
+
+/// What the request got done before its deadline, reported with the 504.
+#[derive(Default)]
+struct Progress {
+    github_requests: AtomicU32,
+    stages: Mutex<Vec<StageDiagnostics>>,
+}
+
+tokio::task_local! {
+    static PROGRESS: Arc<Progress>;
+}
+
+/// Counts a GitHub call against the request being served, if it has a deadline.
+pub fn note_request() {
+    let _ = PROGRESS.try_with(|progress| progress.github_requests.fetch_add(1, Ordering::Relaxed));
+}
+
+/// Records a finished scan stage for the request being served, if it has a deadline.
+pub fn note_stage(stage: &str, elapsed_ms: u64, github_requests: u32) {
+    let _ = PROGRESS.try_with(|progress| {
+        let mut stages = progress.stages.lock().unwrap_or_else(|p| p.into_inner());
+        match stages.iter_mut().find(|s| s.stage == stage) {
+            Some(existing) => {
+                existing.elapsed_ms += elapsed_ms;
+                existing.github_requests += github_requests;
+            }
+            None => stages.push(StageDiagnostics { stage: stage.to_string(), elapsed_ms, github_requests }),
+        }
+    });
+}
+
+// ------------------- Middleware -------------------
+
+#[derive(Debug, Serialize)]
+struct DeadlineExceeded {
+    error: &'static str,
+    route: String,
+    deadline_secs: u64,
+    elapsed_ms: u64,
+    /// GitHub calls made before the request was stopped.
+    github_requests: u32,
+    /// Scan stages that finished in time, in order.
+    stages: Vec<StageDiagnostics>,
+}
+
+/// Stops a request that has not produced a response within its route's
+/// budget and answers 504 with the progress made so far. Dropping the
+/// handler cancels its scan, freeing the worker slot. Streamed bodies are
+/// not limited once the response has started.
+pub async fn enforce(request: Request, next: Next) -> Response {
+    let route = request.extensions().get::<MatchedPath>().map(|p| p.as_str().to_string()).unwrap_or_default();
+    let Some(budget) = budgets().of(&route) else {
+        return next.run(request).await;
+    };
+
+    let started = Instant::now();
+    let progress = Arc::new(Progress::default());
+    match tokio::time::timeout(budget, PROGRESS.scope(progress.clone(), next.run(request))).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!("{route} exceeded its {}s deadline", budget.as_secs());
+            let body = DeadlineExceeded {
+                error: "deadline exceeded",
+                deadline_secs: budget.as_secs(),
+                elapsed_ms: started.elapsed().as_millis() as u64,
+                github_requests: progress.github_requests.load(Ordering::Relaxed),
+                stages: std::mem::take(&mut *progress.stages.lock().unwrap_or_else(|p| p.into_inner())),
+                route,
+            };
+            (StatusCode::GATEWAY_TIMEOUT, Json(body)).into_response()
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{future::Future, pin::Pin};
+
+use crate::{
+    chain,
+    ecosystems::{self, MoveEcosystem},
+    mirror::Mirror,
+    pacing,
+    scan::{self, OwnedRepository, TreeEntry},
+};
+
+// ------------------- Detector Trait -------------------
+
+/// Everything a detector may look at for one repository. `entries` is the
+/// recursive tree at the default branch.
+pub struct RepoContext<'a> {
+    pub client: &'a Client,
+    pub token: &'a str,
+    pub repo: &'a OwnedRepository,
+    pub entries: &'a [TreeEntry],
+}
+
+/// A positive detection: which stage fired and the paths that triggered it
+/// (at most `MAX_EVIDENCE`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Detection {
+    pub detector: DetectorKind,
+    /// Name of a detector added with [`Pipeline::with`] (`detector: custom`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub evidence: Vec<String>,
+    /// Sui SDKs found by the `sdk` detector.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frameworks: Vec<Framework>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectorKind {
+    MoveFiles,
+    MoveToml,
+    Sdk,
+    Onchain,
+    #[serde(other)]
+    Custom,
+}
+
+/// Off-chain Move SDKs recognised in dependency manifests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Framework {
+    /// `@mysten/sui` (formerly `@mysten/sui.js`).
+    TypescriptSdk,
+    /// `@mysten/dapp-kit`.
+    DappKit,
+    RustSdk,
+    /// `pysui`.
+    Python,
+    GoSdk,
+    /// `@aptos-labs/ts-sdk` and the Rust and Python `aptos-sdk`, recognised
+    /// when `MOVE_ECOSYSTEMS` includes `aptos`.
+    AptosSdk,
+    #[serde(other)]
+    Unknown,
+}
+
+impl Framework {
+    pub fn ecosystem(self) -> MoveEcosystem {
+        match self {
+            Framework::AptosSdk => MoveEcosystem::Aptos,
+            _ => MoveEcosystem::Sui,
+        }
+    }
+}
+
+const MAX_EVIDENCE: usize = 10;
+
+pub type DetectResult = Result<Option<Detection>, Box<dyn std::error::Error + Send + Sync>>;
+pub type DetectFuture<'a> = Pin<Box<dyn Future<Output = DetectResult> + Send + 'a>>;
+
+/// One stage of the scan pipeline. A repository is reported when any enabled
+/// detector returns a detection for it. Implement this and add it with
+/// [`Pipeline::with`] to extend detection without touching the scan itself.
+pub trait Detector: Send + Sync {
+    /// Name used in `SCAN_DETECTORS`.
+    fn name(&self) -> &'static str;
+
+    /// Reported in `detections`; detectors outside this module are `Custom`
+    /// and also report their name.
+    fn kind(&self) -> DetectorKind {
+        DetectorKind::Custom
+    }
+
+    fn detect<'a>(&'a self, ctx: &'a RepoContext<'a>) -> DetectFuture<'a>;
+}
+
+// ------------------- Pipeline -------------------
+
+/// Detectors enabled when `SCAN_DETECTORS` is unset.
+const DEFAULT_DETECTORS: &str = "move_files";
+
+pub struct Pipeline {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl Pipeline {
+    /// The pipeline of [`DEFAULT_DETECTORS`].
+    pub fn builtin() -> Self {
+        Self::from_names(DEFAULT_DETECTORS).expect("default detectors are built in")
+    }
+
+    /// Builds the pipeline from `SCAN_DETECTORS`, a comma-separated list of
+    /// built-in detector names (`move_files`, `move_toml`, `sdk`, `onchain`).
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_names(&std::env::var("SCAN_DETECTORS").unwrap_or_else(|_| DEFAULT_DETECTORS.to_string()))
+    }
+
+    pub fn from_names(names: &str) -> Result<Self, String> {
+        let mut pipeline = Pipeline { detectors: Vec::new() };
+        for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            pipeline = pipeline.with(builtin(name).ok_or_else(|| format!("unknown detector {name}"))?);
+        }
+
+        if pipeline.detectors.is_empty() {
+            return Err("no detectors enabled".to_string());
+        }
+        Ok(pipeline)
+    }
+
+    /// Appends a detector, built-in or custom.
+    pub fn with(mut self, detector: Box<dyn Detector>) -> Self {
+        self.detectors.push(detector);
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.detectors.iter().map(|d| d.name()).collect()
+    }
+
+    /// Runs every detector against one repository.
+    pub async fn run(&self, ctx: &RepoContext<'_>) -> Result<Vec<Detection>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut detections = Vec::new();
+        for detector in &self.detectors {
+            if let Some(detection) = detector.detect(ctx).await? {
+                detections.push(detection);
+            }
+        }
+        Ok(detections)
+    }
+}
+
+fn builtin(name: &str) -> Option<Box<dyn Detector>> {
+    match name {
+        "move_files" => Some(Box::new(MoveFileDetector)),
+        "move_toml" => Some(Box::new(MoveTomlDetector)),
+        "sdk" => Some(Box::new(SdkDetector)),
+        "onchain" => Some(Box::new(OnChainDetector)),
+        _ => None,
+    }
+}
+
+// ------------------- Built-in Detectors -------------------
+
+/// Any `.move` source file.
+pub struct MoveFileDetector;
+
+impl Detector for MoveFileDetector {
+    fn name(&self) -> &'static str {
+        "move_files"
+    }
+
+    fn kind(&self) -> DetectorKind {
+        DetectorKind::MoveFiles
+    }
+
+    fn detect<'a>(&'a self, ctx: &'a RepoContext<'a>) -> DetectFuture<'a> {
+        let evidence = paths(ctx.entries, |p| p.ends_with(".move"));
+        Box::pin(async move { Ok(found(self, evidence)) })
+    }
+}
+
+/// A `Move.toml` package manifest.
+pub struct MoveTomlDetector;
+
+impl Detector for MoveTomlDetector {
+    fn name(&self) -> &'static str {
+        "move_toml"
+    }
+
+    fn kind(&self) -> DetectorKind {
+        DetectorKind::MoveToml
+    }
+
+    fn detect<'a>(&'a self, ctx: &'a RepoContext<'a>) -> DetectFuture<'a> {
+        let evidence = paths(ctx.entries, scan::is_manifest);
+        Box::pin(async move { Ok(found(self, evidence)) })
+    }
+}
+
+/// Dependency manifests that pull in a Move SDK, with the marker per framework.
+const SDK_MARKERS: &[(&str, &[(&str, Framework)])] = &[
+    (
+        "package.json",
+        &[
+            ("\"@mysten/sui\"", Framework::TypescriptSdk),
+            ("\"@mysten/sui.js\"", Framework::TypescriptSdk),
+            ("\"@mysten/dapp-kit\"", Framework::DappKit),
+            ("\"@aptos-labs/ts-sdk\"", Framework::AptosSdk),
+        ],
+    ),
+    (
+        "Cargo.toml",
+        &[("sui-sdk", Framework::RustSdk), ("sui_sdk", Framework::RustSdk), ("aptos-sdk", Framework::AptosSdk)],
+    ),
+    ("pyproject.toml", &[("pysui", Framework::Python), ("aptos-sdk", Framework::AptosSdk)]),
+    ("requirements.txt", &[("pysui", Framework::Python), ("aptos-sdk", Framework::AptosSdk)]),
+    ("go.mod", &[("sui-go-sdk", Framework::GoSdk)]),
+];
+
+/// Manifests fetched per repository by [`SdkDetector`].
+const MAX_SDK_MANIFESTS: usize = 5;
+
+/// Off-chain code using a Sui SDK (TypeScript, Rust, Python or Go), or an
+/// SDK of another served ecosystem, found by reading the first few
+/// dependency manifests.
+pub struct SdkDetector;
+
+impl Detector for SdkDetector {
+    fn name(&self) -> &'static str {
+        "sdk"
+    }
+
+    fn kind(&self) -> DetectorKind {
+        DetectorKind::Sdk
+    }
+
+    fn detect<'a>(&'a self, ctx: &'a RepoContext<'a>) -> DetectFuture<'a> {
+        Box::pin(async move {
+            let manifests = ctx
+                .entries
+                .iter()
+                .filter(|e| !e.path.contains("node_modules/"))
+                .filter_map(|e| {
+                    let file = e.path.rsplit('/').next().unwrap_or(&e.path);
+                    SDK_MARKERS.iter().find(|(name, _)| *name == file).map(|(_, markers)| (e, *markers))
+                })
+                .take(MAX_SDK_MANIFESTS);
+
+            let mut evidence = Vec::new();
+            let mut frameworks = Vec::new();
+            for (entry, markers) in manifests {
+                let Some(content) = scan::fetch_blob(ctx.client, ctx.token, &ctx.repo.name, &entry.sha).await? else {
+                    continue;
+                };
+                let matched: Vec<Framework> = markers
+                    .iter()
+                    .filter(|(m, f)| content.contains(m) && ecosystems::enabled().contains(&f.ecosystem()))
+                    .map(|(_, f)| *f)
+                    .collect();
+                if !matched.is_empty() {
+                    evidence.push(entry.path.clone());
+                    frameworks.extend(matched);
+                }
+                pacing::pause().await;
+            }
+
+            frameworks.sort_unstable();
+            frameworks.dedup();
+            Ok(found(self, evidence).map(|d| Detection { frameworks, ..d }))
+        })
+    }
+}
+
+/// Packages whose manifest records a `published-at` address that exists on
+/// the configured network (`SUI_NETWORK`).
+pub struct OnChainDetector;
+
+impl Detector for OnChainDetector {
+    fn name(&self) -> &'static str {
+        "onchain"
+    }
+
+    fn kind(&self) -> DetectorKind {
+        DetectorKind::Onchain
+    }
+
+    fn detect<'a>(&'a self, ctx: &'a RepoContext<'a>) -> DetectFuture<'a> {
+        Box::pin(async move {
+            let mut evidence = Vec::new();
+            for entry in ctx.entries.iter().filter(|e| scan::is_manifest(&e.path)).take(MAX_SDK_MANIFESTS) {
+                let Some(manifest) = scan::fetch_blob(ctx.client, ctx.token, &ctx.repo.name, &entry.sha).await? else {
+                    continue;
+                };
+                pacing::pause().await;
+
+                let Some(address) = published_at(&manifest) else {
+                    continue;
+                };
+                let object = chain::rpc_request(ctx.client, "sui_getObject", json!([address, { "showType": true }])).await?;
+                if object["data"]["type"] == "package" {
+                    evidence.push(format!("{}: {address}", entry.path));
+                }
+            }
+
+            Ok(found(self, evidence))
+        })
+    }
+}
+
+/// Reads `published-at = "0x..."` from a `Move.toml` `[package]` section.
+fn published_at(manifest: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "published-at")
+            .then(|| value.trim().trim_matches('"').to_string())
+            .filter(|a| chain::is_valid_address(a))
+    })
+}
+
+fn paths(entries: &[TreeEntry], matches: impl Fn(&str) -> bool) -> Vec<String> {
+    entries.iter().filter(|e| matches(&e.path)).map(|e| e.path.clone()).collect()
+}
+
+/// Builds the detection for `detector` when there is any evidence. Custom
+/// detectors should use this too, so their name is reported.
+pub fn found(detector: &dyn Detector, mut evidence: Vec<String>) -> Option<Detection> {
+    evidence.truncate(MAX_EVIDENCE);
+    let kind = detector.kind();
+    (!evidence.is_empty()).then(|| Detection {
+        detector: kind,
+        name: (kind == DetectorKind::Custom).then(|| detector.name().to_string()),
+        evidence,
+        frameworks: Vec::new(),
+    })
+}
+
+// ------------------- Evidence -------------------
+
+/// Why a repository counts as a Sui Move repository, stored with the scan so
+/// a disputed verification can be reviewed without re-scanning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evidence {
+    pub kind: EvidenceKind,
+    /// File the evidence was found in, or the matched path itself.
+    pub path: String,
+    /// The line of `path` that matched, for `dependency` and `lock_address`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    /// A `.move` source file or `Move.toml` manifest in the tree.
+    Path,
+    /// An entry of a `Move.toml` `[dependencies]` section.
+    Dependency,
+    /// A published package address recorded in a `Move.lock`.
+    LockAddress,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Collects the evidence for a detected repository: up to `MAX_EVIDENCE`
+/// matched paths, and unless `paths_only` (quick scans), the dependency
+/// lines of the first manifests and the addresses in the first lockfiles.
+/// Files are read from `mirror` when the repository was cloned.
+pub async fn evidence(
+    ctx: &RepoContext<'_>,
+    mirror: Option<&Mirror>,
+    paths_only: bool,
+) -> Result<Vec<Evidence>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut evidence: Vec<Evidence> = ctx
+        .entries
+        .iter()
+        .filter(|e| e.path.ends_with(".move") || scan::is_manifest(&e.path))
+        .take(MAX_EVIDENCE)
+        .map(|e| Evidence { kind: EvidenceKind::Path, path: e.path.clone(), line: None })
+        .collect();
+    if paths_only {
+        return Ok(evidence);
+    }
+
+    let manifests = ctx.entries.iter().filter(|e| scan::is_manifest(&e.path)).take(MAX_SDK_MANIFESTS);
+    let lockfiles = ctx.entries.iter().filter(|e| is_lockfile(&e.path)).take(MAX_SDK_MANIFESTS);
+    let files = manifests.map(|e| (e, EvidenceKind::Dependency)).chain(lockfiles.map(|e| (e, EvidenceKind::LockAddress)));
+    for (entry, kind) in files {
+        let content = match mirror {
+            Some(mirror) => mirror.read(&entry.path).await?,
+            None => {
+                let content = scan::fetch_blob(ctx.client, ctx.token, &ctx.repo.name, &entry.sha).await?;
+                pacing::pause().await;
+                content
+            }
+        };
+        let Some(content) = content else {
+            continue;
+        };
+
+        let lines = match kind {
+            EvidenceKind::Dependency => dependency_lines(&content),
+            _ => address_lines(&content),
+        };
+        evidence.extend(
+            lines
+                .into_iter()
+                .take(MAX_EVIDENCE)
+                .map(|line| Evidence { kind, path: entry.path.clone(), line: Some(line.to_string()) }),
+        );
+    }
+    Ok(evidence)
+}
+
+fn is_lockfile(path: &str) -> bool {
+    path == "Move.lock" || path.ends_with("/Move.lock")
+}
+
+/// Non-empty, non-comment lines of a manifest's `[dependencies]` sections.
+fn dependency_lines(manifest: &str) -> Vec<&str> {
+    let mut in_dependencies = false;
+    manifest
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            if line.starts_with('[') {
+                in_dependencies = matches!(*line, "[dependencies]" | "[dev-dependencies]");
+                return false;
+            }
+            in_dependencies && !line.is_empty() && !line.starts_with('#')
+        })
+        .collect()
+}
+
+/// Lockfile lines whose value is a Sui address (`original-published-id`,
+/// `latest-published-id`, ...).
+fn address_lines(lockfile: &str) -> Vec<&str> {
+    lockfile
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            line.split_once('=')
+                .is_some_and(|(_, value)| chain::is_valid_address(value.trim().trim_matches('"')))
+        })
+        .collect()
+}
+
+// ------------------- Tooling Evidence -------------------
+
+/// Client configs read per repository for their environment aliases.
+const MAX_CLIENT_CONFIGS: usize = 2;
+
+/// A Sui CLI, localnet or suibase artifact in a detected repository:
+/// corroboration that its Move code was actually built and deployed
+/// locally, however little of it there is.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolingEvidence {
+    pub kind: ToolingKind,
+    pub path: String,
+    /// Environment aliases of a `client_config`, e.g. `localnet, testnet`.
+    /// Key material and addresses are never read or reported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolingKind {
+    /// A `sui client` config (`client.yaml`).
+    ClientConfig,
+    /// A `sui.keystore` or other `.keystore` file; only its path is used.
+    Keystore,
+    /// A genesis blob or config.
+    Genesis,
+    /// Localnet network, fullnode or validator configs.
+    Localnet,
+    /// `suibase` workdir files.
+    Suibase,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Which tooling artifact `path` is, if any.
+fn tooling_kind(path: &str) -> Option<ToolingKind> {
+    let path = path.to_lowercase();
+    let mut segments = path.split('/').rev();
+    let file = segments.next().unwrap_or_default();
+    let dirs: Vec<&str> = segments.collect();
+
+    if file == "suibase.yaml" || dirs.iter().any(|d| matches!(*d, "suibase" | ".suibase")) {
+        Some(ToolingKind::Suibase)
+    } else if matches!(file, "client.yaml" | "client.yml") {
+        Some(ToolingKind::ClientConfig)
+    } else if file.ends_with(".keystore") {
+        Some(ToolingKind::Keystore)
+    } else if file == "genesis.blob" || (file.starts_with("genesis.") && (file.ends_with(".yaml") || file.ends_with(".yml"))) {
+        Some(ToolingKind::Genesis)
+    } else if matches!(file, "network.yaml" | "fullnode.yaml" | "validator.yaml")
+        || (dirs.contains(&"localnet") && (file.ends_with(".yaml") || file.ends_with(".yml")))
+    {
+        Some(ToolingKind::Localnet)
+    } else {
+        None
+    }
+}
+
+/// Up to `MAX_EVIDENCE` tooling artifacts in the tree of a detected
+/// repository. Unless `paths_only` (quick scans), the first client configs
+/// are read for their environment aliases.
+pub async fn tooling_evidence(
+    ctx: &RepoContext<'_>,
+    mirror: Option<&Mirror>,
+    paths_only: bool,
+) -> Result<Vec<ToolingEvidence>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut tooling = Vec::new();
+    let mut configs_read = 0;
+    for entry in ctx.entries {
+        let Some(kind) = tooling_kind(&entry.path) else {
+            continue;
+        };
+        let mut detail = None;
+        if kind == ToolingKind::ClientConfig && !paths_only && configs_read < MAX_CLIENT_CONFIGS {
+            configs_read += 1;
+            let content = match mirror {
+                Some(mirror) => mirror.read(&entry.path).await?,
+                None => {
+                    let content = scan::fetch_blob(ctx.client, ctx.token, &ctx.repo.name, &entry.sha).await?;
+                    pacing::pause().await;
+                    content
+                }
+            };
+            detail = content.as_deref().map(env_aliases).filter(|aliases| !aliases.is_empty()).map(|a| a.join(", "));
+        }
+        tooling.push(ToolingEvidence { kind, path: entry.path.clone(), detail });
+        if tooling.len() == MAX_EVIDENCE {
+            break;
+        }
+    }
+    Ok(tooling)
+}
+
+/// The `alias:` values of a client config's `envs` list.
+fn env_aliases(config: &str) -> Vec<String> {
+    config
+        .lines()
+        .filter_map(|line| line.trim().trim_start_matches("- ").strip_prefix("alias:"))
+        .map(|alias| alias.trim().trim_matches(['"', '\'']).to_string())
+        .filter(|alias| !alias.is_empty())
+        .collect()
+}
@@ -0,0 +1,93 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use crate::{authorship, pacing, scan};
+
+// ------------------- Rules -------------------
+
+/// Documentation repository (`owner/repo`) -> path prefixes counted as
+/// documentation. An empty list counts the whole repository.
+pub type DocRules = BTreeMap<String, Vec<String>>;
+
+const DEFAULT_RULES: &[(&str, &[&str])] = &[
+    ("MystenLabs/sui", &["docs/"]),
+    ("MystenLabs/move-book", &[]),
+    ("sui-foundation/sui-move-intro-course", &[]),
+];
+
+/// The active rules: the JSON file at `DOC_RULES_PATH` (an object of repo ->
+/// path prefixes) when set and readable, otherwise the built-in rules.
+pub fn rules() -> &'static DocRules {
+    static RULES: OnceLock<DocRules> = OnceLock::new();
+    RULES.get_or_init(|| {
+        if let Ok(path) = std::env::var("DOC_RULES_PATH") {
+            match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|raw| {
+                serde_json::from_str::<DocRules>(&raw).map_err(|e| e.to_string())
+            }) {
+                Ok(rules) => return rules,
+                Err(e) => tracing::warn!("Ignoring DOC_RULES_PATH={path}: {e}"),
+            }
+        }
+
+        DEFAULT_RULES
+            .iter()
+            .map(|(repo, paths)| (repo.to_string(), paths.iter().map(|p| p.to_string()).collect()))
+            .collect()
+    })
+}
+
+// ------------------- Structs -------------------
+
+/// Commits to documentation and tutorial content, kept apart from code commits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentationContributions {
+    pub total_commits: u32,
+    pub repositories: Vec<DocContribution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocContribution {
+    pub repo: String,
+    /// Path prefix the commits touch; absent when the whole repository counts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub commits: u32,
+}
+
+// ------------------- Core Logic -------------------
+
+/// Counts the commits of any of `usernames` under each documentation rule.
+/// Returns `None` when there are none.
+#[tracing::instrument(name = "documentation", skip(client, token))]
+pub async fn fetch_documentation_contributions(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    max_pages: u32,
+) -> Result<Option<DocumentationContributions>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut repositories = Vec::new();
+
+    for (repo, paths) in rules() {
+        let targets: Vec<Option<&str>> =
+            if paths.is_empty() { vec![None] } else { paths.iter().map(|p| Some(p.as_str())).collect() };
+
+        for path in targets {
+            let mut commits = scan::fetch_commits_in(client, token, repo, path, usernames, max_pages).await?;
+            authorship::exclude_bots(&mut commits);
+            if !commits.is_empty() {
+                repositories.push(DocContribution {
+                    repo: repo.clone(),
+                    path: path.map(|p| p.to_string()),
+                    commits: commits.len() as u32,
+                });
+            }
+            pacing::pause().await;
+        }
+    }
+
+    Ok((!repositories.is_empty()).then(|| DocumentationContributions {
+        total_commits: repositories.iter().map(|r| r.commits).sum(),
+        repositories,
+    }))
+}
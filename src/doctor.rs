@@ -0,0 +1,228 @@
+use reqwest::Client;
+use std::time::Instant;
+
+use crate::scan::{ScanLimits, ScanMode, ScanOptions};
+
+// ------------------- Doctor -------------------
+
+/// Default user for the fixture scan; known to own public `.move` repositories.
+const DEFAULT_FIXTURE_USER: &str = "dotandev";
+
+struct Check {
+    name: &'static str,
+    result: Result<String, String>,
+}
+
+/// Runs every self-test, prints a checklist and returns the process exit code
+/// (0 when all checks pass, 1 otherwise).
+pub async fn run() -> i32 {
+    let mut checks = vec![check_config(), check_storage()];
+
+    let token = std::env::var("GITHUB_TOKEN").ok().filter(|t| !t.is_empty());
+    let client = match crate::github::build_client() {
+        Ok(client) => client,
+        Err(e) => {
+            println!("Could not build the HTTP client: {e}");
+            return 1;
+        }
+    };
+
+    match &token {
+        // Serve-only deployments hold no token and never reach GitHub.
+        _ if crate::readonly::requested_by_env() => {
+            for name in ["GitHub token", "Rate limits", "Fixture scan"] {
+                checks.push(Check { name, result: Ok("skipped: SERVE_ONLY".into()) });
+            }
+        }
+        Some(token) => {
+            checks.push(check_token_scopes(&client, token).await);
+            checks.push(check_rate_limits(&client, token).await);
+            checks.push(check_fixture_scan(&client, token).await);
+        }
+        None => {
+            for name in ["GitHub token", "Rate limits", "Fixture scan"] {
+                checks.push(Check { name, result: Err("skipped: GITHUB_TOKEN not set".into()) });
+            }
+        }
+    }
+
+    println!("sui-contributors doctor\n");
+    for check in &checks {
+        match &check.result {
+            Ok(detail) => println!("  ✅ {:<14} {}", check.name, detail),
+            Err(detail) => println!("  ❌ {:<14} {}", check.name, detail),
+        }
+    }
+
+    let failed = checks.iter().filter(|c| c.result.is_err()).count();
+    if failed == 0 {
+        println!("\nAll {} checks passed.", checks.len());
+        0
+    } else {
+        println!("\n{failed} of {} checks failed.", checks.len());
+        1
+    }
+}
+
+fn check_config() -> Check {
+    let mut problems = Vec::new();
+
+    if std::env::var("GITHUB_TOKEN").map(|t| t.is_empty()).unwrap_or(true) && !crate::readonly::requested_by_env() {
+        problems.push("GITHUB_TOKEN is not set".to_string());
+    }
+    if let Ok(port) = std::env::var("PORT")
+        && port.parse::<u16>().is_err()
+    {
+        problems.push(format!("PORT={port} is not a valid port"));
+    }
+    if let Ok(ratio) = std::env::var("OTEL_TRACES_SAMPLER_RATIO")
+        && !ratio.parse::<f64>().is_ok_and(|r| (0.0..=1.0).contains(&r))
+    {
+        problems.push(format!("OTEL_TRACES_SAMPLER_RATIO={ratio} is not between 0 and 1"));
+    }
+    if let Ok(dsn) = std::env::var("SENTRY_DSN")
+        && !dsn.is_empty()
+        && dsn.parse::<sentry::types::Dsn>().is_err()
+    {
+        problems.push("SENTRY_DSN is not a valid DSN".to_string());
+    }
+    if let Err(e) = crate::detect::Pipeline::from_env() {
+        problems.push(format!("SCAN_DETECTORS: {e}"));
+    }
+    if let Err(e) = crate::chain::network() {
+        problems.push(e);
+    }
+    if let Err(e) = crate::ecosystems::config() {
+        problems.push(e);
+    }
+    if let Err(e) = crate::archive::backend() {
+        problems.push(e.to_string());
+    }
+    if let Err(e) = crate::repo_rules::RepoRules::from_env() {
+        problems.push(e);
+    }
+    if let Ok(spec) = std::env::var("ROUTE_DEADLINES")
+        && let Err(e) = crate::deadline::parse(&spec)
+    {
+        problems.push(format!("ROUTE_DEADLINES: {e}"));
+    }
+    if let Err(e) = crate::claims::signing_key() {
+        problems.push(e.to_string());
+    }
+    if let Ok(path) = std::env::var("NOTIFICATIONS_PATH")
+        && let Err(e) = crate::notify::load(&path)
+    {
+        problems.push(format!("NOTIFICATIONS_PATH: {e}"));
+    }
+    if let Err(e) = crate::pacing::load(std::env::var("PACING_PROFILES_PATH").ok().as_deref(), std::env::var("PACING_PROFILE").ok().as_deref()) {
+        problems.push(e);
+    }
+    if let Err(e) = crate::sheets::config() {
+        problems.push(e.to_string());
+    }
+    if let Err(e) = crate::installations::config() {
+        problems.push(e.to_string());
+    }
+    if let Err(e) = crate::policy::PolicySet::from_env() {
+        problems.push(e);
+    }
+
+    Check {
+        name: "Config",
+        result: if problems.is_empty() { Ok("environment looks valid".into()) } else { Err(problems.join("; ")) },
+    }
+}
+
+fn check_storage() -> Check {
+    let result = crate::storage::open_from_env()
+        .and_then(|storage| storage.ping())
+        .map(|_| "database reachable".to_string())
+        .map_err(|e| e.to_string());
+
+    Check { name: "Storage", result }
+}
+
+async fn check_token_scopes(client: &Client, token: &str) -> Check {
+    let result = async {
+        let resp = crate::github::get(client, token, "https://api.github.com/user")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if !resp.status().is_success() {
+            return Err(format!("GitHub rejected the token ({})", resp.status()));
+        }
+
+        // Classic tokens advertise their scopes; fine-grained tokens send no header.
+        let scopes = resp
+            .headers()
+            .get("x-oauth-scopes")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+
+        let user: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+        let login = user["login"].as_str().unwrap_or("unknown");
+
+        Ok(match scopes {
+            Some(s) if s.is_empty() => format!("authenticated as {login}, no scopes (public data only)"),
+            Some(s) => format!("authenticated as {login}, scopes: {s}"),
+            None => {
+                let degraded: Vec<&str> = crate::github::capabilities(token).degraded.iter().map(|d| d.capability).collect();
+                match degraded.as_slice() {
+                    [] => format!("authenticated as {login}, fine-grained token"),
+                    degraded => format!("authenticated as {login}, fine-grained token (REST fallbacks for {})", degraded.join(", ")),
+                }
+            }
+        })
+    }
+    .await;
+
+    Check { name: "GitHub token", result }
+}
+
+async fn check_rate_limits(client: &Client, token: &str) -> Check {
+    let result = async {
+        let resp = crate::github::get(client, token, "https://api.github.com/rate_limit")
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let limits: serde_json::Value = resp.json().await.map_err(|e| e.to_string())?;
+        let core = &limits["resources"]["core"];
+        let graphql = &limits["resources"]["graphql"];
+
+        let summary = format!(
+            "core {}/{}, graphql {}/{}",
+            core["remaining"], core["limit"], graphql["remaining"], graphql["limit"]
+        );
+
+        if core["remaining"].as_u64() == Some(0) || graphql["remaining"].as_u64() == Some(0) {
+            Err(format!("quota exhausted: {summary}"))
+        } else {
+            Ok(summary)
+        }
+    }
+    .await;
+
+    Check { name: "Rate limits", result }
+}
+
+async fn check_fixture_scan(client: &Client, token: &str) -> Check {
+    let user = std::env::var("DOCTOR_FIXTURE_USER").unwrap_or_else(|_| DEFAULT_FIXTURE_USER.to_string());
+    let started = Instant::now();
+
+    let options = ScanOptions::new(ScanMode::Full, ScanLimits::ceiling());
+
+    let result = match crate::scan::get_user_move_repos(client, token, std::slice::from_ref(&user), options).await {
+        Ok(resp) if resp.has_move_files => Ok(format!(
+            "{user}: {} repos, {} commits in {:.1}s",
+            resp.total_repositories,
+            resp.total_commits,
+            started.elapsed().as_secs_f64()
+        )),
+        Ok(_) => Err(format!("{user}: scan completed but found no .move files")),
+        Err(e) => Err(format!("{user}: {e}")),
+    };
+
+    Check { name: "Fixture scan", result }
+}
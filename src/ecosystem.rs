@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+use crate::storage::StoredScan;
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum GraphNode {
+    Developer { id: String, label: String, scanned_at: u64 },
+    Repository { id: String, label: String, url: String },
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub commits: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EcosystemGraph {
+    pub min_commits: u32,
+    pub developers: usize,
+    pub repositories: usize,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+// ------------------- Graph Building -------------------
+
+/// Builds a bipartite developer ↔ repository graph from the latest stored scan
+/// of each user, keeping only contributions with at least `min_commits`.
+/// Developers and repositories left without edges are omitted.
+pub fn build_graph(scans: &[StoredScan], min_commits: u32) -> EcosystemGraph {
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut seen_repos = std::collections::HashSet::new();
+    let mut developers = 0;
+
+    for scan in scans {
+        let contributions: Vec<_> = scan
+            .result
+            .repositories
+            .iter()
+            .filter(|r| r.commit_count >= min_commits.max(1))
+            .collect();
+
+        if contributions.is_empty() {
+            continue;
+        }
+
+        let dev_id = format!("dev:{}", scan.username.to_lowercase());
+        nodes.push(GraphNode::Developer {
+            id: dev_id.clone(),
+            label: scan.username.clone(),
+            scanned_at: scan.scanned_at,
+        });
+        developers += 1;
+
+        for repo in contributions {
+            let repo_id = format!("repo:{}", repo.repo_name.to_lowercase());
+            if seen_repos.insert(repo_id.clone()) {
+                nodes.push(GraphNode::Repository {
+                    id: repo_id.clone(),
+                    label: repo.repo_name.clone(),
+                    url: repo.repo_url.clone(),
+                });
+            }
+            edges.push(GraphEdge { source: dev_id.clone(), target: repo_id, commits: repo.commit_count });
+        }
+    }
+
+    EcosystemGraph {
+        min_commits,
+        developers,
+        repositories: seen_repos.len(),
+        nodes,
+        edges,
+    }
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{
+    detect::{DetectorKind, EvidenceKind},
+    i18n, policy,
+    scan::{self, RepositoryWithCommits, ScanMode, UserMoveFilesResponse},
+};
+
+// ------------------- Move Ecosystems -------------------
+
+/// A chain built on Move. One deployment can classify developers for
+/// several of them (`MOVE_ECOSYSTEMS`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MoveEcosystem {
+    Sui,
+    Aptos,
+    Movement,
+}
+
+impl MoveEcosystem {
+    const ALL: [MoveEcosystem; 3] = [MoveEcosystem::Sui, MoveEcosystem::Aptos, MoveEcosystem::Movement];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MoveEcosystem::Sui => "sui",
+            MoveEcosystem::Aptos => "aptos",
+            MoveEcosystem::Movement => "movement",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Ecosystems served when `MOVE_ECOSYSTEMS` is unset.
+const DEFAULT_ECOSYSTEMS: &str = "sui";
+
+/// The ecosystems listed in `MOVE_ECOSYSTEMS` (comma-separated), in order.
+pub fn config() -> Result<Vec<MoveEcosystem>, String> {
+    let names = std::env::var("MOVE_ECOSYSTEMS").unwrap_or_else(|_| DEFAULT_ECOSYSTEMS.to_string());
+    let mut ecosystems = Vec::new();
+    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let ecosystem = MoveEcosystem::parse(name).ok_or_else(|| format!("MOVE_ECOSYSTEMS: unknown ecosystem {name}"))?;
+        if !ecosystems.contains(&ecosystem) {
+            ecosystems.push(ecosystem);
+        }
+    }
+    if ecosystems.is_empty() {
+        return Err("MOVE_ECOSYSTEMS lists no ecosystem".to_string());
+    }
+    Ok(ecosystems)
+}
+
+/// The ecosystems this deployment serves, read once; only Sui when
+/// `MOVE_ECOSYSTEMS` is invalid.
+pub fn enabled() -> &'static [MoveEcosystem] {
+    static ENABLED: OnceLock<Vec<MoveEcosystem>> = OnceLock::new();
+    ENABLED.get_or_init(|| {
+        config().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring {e}");
+            vec![MoveEcosystem::Sui]
+        })
+    })
+}
+
+/// The ecosystem a request asked for with `ecosystem=<name>`, if any.
+/// Quick scans keep no dependency evidence to tell ecosystems apart.
+pub fn select(name: Option<&str>, mode: ScanMode) -> Result<Option<MoveEcosystem>, i18n::Message> {
+    let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) else {
+        return Ok(None);
+    };
+    let ecosystem = MoveEcosystem::parse(name).filter(|e| enabled().contains(e)).ok_or_else(|| {
+        i18n::Message::new("unknown_ecosystem")
+            .arg("name", name)
+            .arg("available", enabled().iter().map(|e| e.as_str()).collect::<Vec<_>>().join(", "))
+    })?;
+    if mode == ScanMode::Quick {
+        return Err(i18n::Message::new("ecosystem_quick"));
+    }
+    Ok(Some(ecosystem))
+}
+
+// ------------------- Classification -------------------
+
+/// `Move.toml` dependency markers, checked in order against the lowercased
+/// line: Movement ships its own fork of the Aptos framework, so its URL wins.
+const DEPENDENCY_MARKERS: &[(&str, MoveEcosystem)] = &[
+    ("movementlabsxyz", MoveEcosystem::Movement),
+    ("aptos-labs/aptos-core", MoveEcosystem::Aptos),
+    ("aptosframework", MoveEcosystem::Aptos),
+    ("aptosstdlib", MoveEcosystem::Aptos),
+    ("aptostoken", MoveEcosystem::Aptos),
+    ("mystenlabs/sui", MoveEcosystem::Sui),
+    ("sui-framework", MoveEcosystem::Sui),
+    ("deepbook", MoveEcosystem::Sui),
+];
+
+fn dependency_ecosystem(line: &str) -> Option<MoveEcosystem> {
+    let line = line.to_lowercase();
+    if let Some((_, ecosystem)) = DEPENDENCY_MARKERS.iter().find(|(marker, _)| line.contains(marker)) {
+        return Some(*ecosystem);
+    }
+    let key = line.split('=').next().unwrap_or_default().trim();
+    matches!(key, "sui" | "suisystem").then_some(MoveEcosystem::Sui)
+}
+
+/// The ecosystems a repository's evidence points to: its `Move.toml`
+/// dependencies and SDK frameworks, plus what only Sui has (published
+/// addresses in `Move.lock`, Sui CLI artifacts, on-chain packages and the
+/// `2024` edition). Empty for repositories with nothing to go on, such as
+/// quick scans, which keep paths only.
+pub fn of_repository(repo: &RepositoryWithCommits) -> Vec<MoveEcosystem> {
+    let mut ecosystems: Vec<MoveEcosystem> = repo
+        .evidence
+        .iter()
+        .filter_map(|e| match e.kind {
+            EvidenceKind::Dependency => e.line.as_deref().and_then(dependency_ecosystem),
+            EvidenceKind::LockAddress => Some(MoveEcosystem::Sui),
+            _ => None,
+        })
+        .collect();
+    ecosystems.extend(repo.detections.iter().flat_map(|d| d.frameworks.iter().map(|f| f.ecosystem())));
+    if repo.detections.iter().any(|d| d.detector == DetectorKind::Onchain) || !repo.tooling_evidence.is_empty() {
+        ecosystems.push(MoveEcosystem::Sui);
+    }
+    if repo.move_edition.as_ref().is_some_and(|e| e.declared.iter().any(|d| d.starts_with("2024"))) {
+        ecosystems.push(MoveEcosystem::Sui);
+    }
+    ecosystems.sort_unstable();
+    ecosystems.dedup();
+    ecosystems
+}
+
+/// One ecosystem's share of a scan and whether it alone passes the policy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EcosystemVerdict {
+    pub ecosystem: MoveEcosystem,
+    pub repositories: usize,
+    pub commits: u32,
+    pub is_developer: bool,
+}
+
+/// When the deployment serves more than one ecosystem, tags every
+/// repository of `result` with its ecosystems and evaluates `policy` per
+/// ecosystem.
+pub fn classify(result: &mut UserMoveFilesResponse, policy: &policy::Policy, now_secs: u64) {
+    if enabled().len() < 2 {
+        return;
+    }
+    for repo in &mut result.repositories {
+        repo.ecosystems = of_repository(repo);
+    }
+    result.ecosystems = enabled()
+        .iter()
+        .map(|&ecosystem| {
+            let mut scoped = result.clone();
+            restrict(&mut scoped, ecosystem);
+            EcosystemVerdict {
+                ecosystem,
+                repositories: scoped.total_repositories,
+                commits: scoped.total_commits,
+                is_developer: policy.evaluate(&scoped, now_secs).is_sui_developer,
+            }
+        })
+        .collect();
+}
+
+/// Narrows `result` to `ecosystem` (`ecosystem=<name>`): its repositories
+/// and the totals over them. Outside Sui, the account-level Sui signals
+/// (organizations, SIPs, docs and wallet activity) are dropped as well.
+pub fn restrict(result: &mut UserMoveFilesResponse, ecosystem: MoveEcosystem) {
+    result.repositories.retain(|r| of_repository(r).contains(&ecosystem));
+    scan::retotal(result);
+    if ecosystem != MoveEcosystem::Sui {
+        result.sui_organizations.clear();
+        result.governance = None;
+        result.documentation_contributions = None;
+        result.chain_activity = None;
+    }
+    result.ecosystem = Some(ecosystem);
+}
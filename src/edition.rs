@@ -0,0 +1,172 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    blobs, pacing,
+    scan::{self, RepositoryWithCommits, UserMoveFilesResponse},
+    storage::Storage,
+};
+
+// ------------------- Move Edition -------------------
+
+/// Manifests read per repository for their `edition`.
+const MAX_MANIFESTS: usize = 5;
+
+/// Move sources analysed per repository for Move 2024 features.
+const MAX_SOURCE_FILES: usize = 20;
+
+/// The Move language edition a repository targets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveEdition {
+    /// `2024` when a manifest declares a 2024 edition or the sources use
+    /// Move 2024 features, else `legacy`.
+    pub edition: Edition,
+    /// `edition` values of the repository's `Move.toml` manifests (`2024.beta`, `legacy`, ...).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub declared: Vec<String>,
+    /// Move 2024 features used in the analysed sources.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<Feature>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Edition {
+    Legacy,
+    #[serde(rename = "2024")]
+    Move2024,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Syntax only the 2024 edition accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// `receiver.function(...)` calls.
+    MethodSyntax,
+    /// `enum` declarations.
+    Enum,
+    /// `match` expressions.
+    Match,
+    /// `public struct` visibility.
+    PublicStruct,
+    /// Explicitly mutable `let mut` bindings.
+    LetMut,
+    /// `use fun` method aliases.
+    UseFun,
+    /// `macro fun` declarations.
+    Macro,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Move 2024 features used in `source`, in declaration order of [`Feature`].
+/// Comment lines are skipped.
+pub fn features(source: &str) -> Vec<Feature> {
+    let mut features = Vec::new();
+    for line in source.lines().map(str::trim).filter(|l| !l.starts_with("//") && !l.starts_with('*')) {
+        let declaration = line.strip_prefix("public ").unwrap_or(line);
+        let found = [
+            (Feature::MethodSyntax, has_method_call(line)),
+            (Feature::Enum, declaration.starts_with("enum ")),
+            (Feature::Match, line.contains("match (")),
+            (Feature::PublicStruct, line.starts_with("public struct ")),
+            (Feature::LetMut, line.contains("let mut ")),
+            (Feature::UseFun, declaration.starts_with("use fun ")),
+            (Feature::Macro, declaration.starts_with("macro fun ")),
+        ];
+        features.extend(found.into_iter().filter(|(_, found)| *found).map(|(feature, _)| feature));
+    }
+    features.sort_unstable();
+    features.dedup();
+    features
+}
+
+/// Whether `line` calls a function on a receiver: an identifier or closing
+/// bracket, a dot, then an identifier followed by `(`. Legacy Move only uses
+/// dots for field access.
+fn has_method_call(line: &str) -> bool {
+    let line = line.split("//").next().unwrap_or(line);
+    let bytes = line.as_bytes();
+    bytes.iter().enumerate().any(|(i, &b)| {
+        if b != b'.' || i == 0 || !(bytes[i - 1].is_ascii_alphanumeric() || matches!(bytes[i - 1], b'_' | b')' | b']')) {
+            return false;
+        }
+        let name = line[i + 1..].bytes().take_while(|c| c.is_ascii_alphanumeric() || *c == b'_').count();
+        name > 0 && !bytes[i + 1].is_ascii_digit() && line[i + 1 + name..].starts_with(['(', '<'])
+    })
+}
+
+/// The value of `field` (`edition`, `name`) in a `Move.toml` `[package]` section.
+fn package_field(manifest: &str, field: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if in_package
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == field
+        {
+            let value = value.split('#').next().unwrap_or(value).trim().trim_matches('"');
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+/// Reads the manifests and up to `MAX_SOURCE_FILES` sources of `repo`,
+/// returning the edition and the manifests' package names. Sources go
+/// through the blob analysis cache, so files analysed before cost no request.
+async fn detect(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    repo: &RepositoryWithCommits,
+) -> Result<(MoveEdition, Vec<String>), Box<dyn std::error::Error + Send + Sync>> {
+    let mut declared = Vec::new();
+    let mut package_names = Vec::new();
+    for manifest in repo.manifests.iter().take(MAX_MANIFESTS) {
+        let content = scan::fetch_blob(client, token, &repo.repo_name, &manifest.sha).await?;
+        pacing::pause().await;
+        declared.extend(content.as_deref().and_then(|m| package_field(m, "edition")));
+        package_names.extend(content.as_deref().and_then(|m| package_field(m, "name")));
+    }
+    declared.sort();
+    declared.dedup();
+    package_names.sort();
+    package_names.dedup();
+
+    let mut features = Vec::new();
+    for file in repo.move_files.iter().take(MAX_SOURCE_FILES) {
+        if let Some(analysis) = blobs::analyze_move_blob(client, token, storage, &repo.repo_name, &file.sha).await? {
+            features.extend(analysis.move_2024_features);
+        }
+    }
+    features.sort_unstable();
+    features.dedup();
+
+    let edition = if declared.iter().any(|e| e.starts_with("2024")) || !features.is_empty() {
+        Edition::Move2024
+    } else {
+        Edition::Legacy
+    };
+    Ok((MoveEdition { edition, declared, features }, package_names))
+}
+
+/// Sets `move_edition` and `package_names` on every repository of `result`
+/// that has no edition yet; repositories carried over from an earlier scan
+/// keep theirs.
+pub async fn annotate(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    result: &mut UserMoveFilesResponse,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    for repo in result.repositories.iter_mut().filter(|r| r.move_edition.is_none()) {
+        let (edition, package_names) = detect(client, token, storage, repo).await?;
+        repo.move_edition = Some(edition);
+        repo.package_names = package_names;
+    }
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use axum::http;
+use reqwest::{RequestBuilder, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+// ------------------- Fixtures -------------------
+
+/// Where fixtures are written and read when `GITHUB_FIXTURES_DIR` is unset.
+const DEFAULT_DIR: &str = "fixtures";
+
+/// How GitHub requests are served, from `GITHUB_FIXTURES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Requests go to GitHub as usual.
+    Off,
+    /// Requests go to GitHub and every response is saved as a fixture.
+    Record,
+    /// Responses come only from saved fixtures; nothing reaches the network.
+    Replay,
+}
+
+/// The process-wide mode: `GITHUB_FIXTURES=record` or `replay`, else off.
+pub fn mode() -> Mode {
+    static MODE: OnceLock<Mode> = OnceLock::new();
+    *MODE.get_or_init(|| match std::env::var("GITHUB_FIXTURES").as_deref().map(str::trim) {
+        Ok("record") => Mode::Record,
+        Ok("replay") => Mode::Replay,
+        Ok("" | "off") | Err(_) => Mode::Off,
+        Ok(other) => {
+            tracing::warn!("Ignoring GITHUB_FIXTURES={other}: expected record or replay");
+            Mode::Off
+        }
+    })
+}
+
+/// Stands in for `GITHUB_TOKEN` when replaying; fixtures do not depend on it.
+pub const REPLAY_TOKEN: &str = "replay";
+
+/// `GITHUB_TOKEN`, or [`REPLAY_TOKEN`] when it is unset in replay mode.
+pub fn github_token() -> Option<String> {
+    std::env::var("GITHUB_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty())
+        .or_else(|| (mode() == Mode::Replay).then(|| REPLAY_TOKEN.to_string()))
+}
+
+pub fn dir() -> PathBuf {
+    std::env::var("GITHUB_FIXTURES_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string()).into()
+}
+
+/// One recorded GitHub response. `method`, `url` and `request_body` are kept
+/// for reading the fixture; lookups go by the file name.
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<String>,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    /// The body when it is UTF-8, else `body_hex`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_hex: Option<String>,
+}
+
+/// URL and UTF-8 body of every fixture in `dir`, for benchmarks.
+pub fn recorded(dir: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut recorded = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| format!("cannot read fixtures in {}: {e}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let fixture: Fixture = serde_json::from_slice(&std::fs::read(&path)?)
+            .map_err(|e| format!("invalid fixture {}: {e}", path.display()))?;
+        if let Some(body) = fixture.body {
+            recorded.push((fixture.url, body));
+        }
+    }
+    recorded.sort();
+    Ok(recorded)
+}
+
+/// Fixture file for a request: a hash of its method, URL and body, so the
+/// token it ran with does not matter and GraphQL queries stay distinct.
+fn path_for(request: &reqwest::Request) -> PathBuf {
+    let mut key = format!("{} {}\n", request.method(), request.url()).into_bytes();
+    key.extend_from_slice(request.body().and_then(|b| b.as_bytes()).unwrap_or_default());
+    let digest = ring::digest::digest(&ring::digest::SHA256, &key);
+    dir().join(format!("{}.json", hex::encode(&digest.as_ref()[..16])))
+}
+
+/// Sends `request` and saves its response before handing it back.
+pub async fn record(request: RequestBuilder) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let path = path_for(&request);
+    let method = request.method().to_string();
+    let request_body = request.body().and_then(|b| b.as_bytes()).map(|b| String::from_utf8_lossy(b).into_owned());
+
+    let resp = client.execute(request).await?;
+    let status = resp.status().as_u16();
+    let url = resp.url().clone();
+    let headers: BTreeMap<String, String> = resp
+        .headers()
+        .iter()
+        .filter(|(name, _)| *name != http::header::SET_COOKIE)
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    let bytes = resp.bytes().await?;
+
+    let (body, body_hex) = match std::str::from_utf8(&bytes) {
+        Ok(text) => (Some(text.to_string()), None),
+        Err(_) => (None, Some(hex::encode(&bytes))),
+    };
+    let fixture = Fixture { method, url: url.to_string(), request_body, status, headers, body, body_hex };
+    tokio::fs::create_dir_all(dir()).await?;
+    tokio::fs::write(&path, serde_json::to_vec_pretty(&fixture)?).await?;
+    tracing::debug!("Recorded {} {} to {}", fixture.method, fixture.url, path.display());
+
+    to_response(&fixture, url, bytes.to_vec())
+}
+
+/// Answers `request` from its fixture; a request with none fails instead of
+/// reaching GitHub.
+pub async fn replay(request: RequestBuilder) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    let request = request.build()?;
+    let path = path_for(&request);
+    let raw = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("no fixture for {} {} ({}): {e}", request.method(), request.url(), path.display()))?;
+    let fixture: Fixture = serde_json::from_slice(&raw)?;
+
+    let body = match (&fixture.body, &fixture.body_hex) {
+        (Some(text), _) => text.clone().into_bytes(),
+        (None, Some(encoded)) => hex::decode(encoded)?,
+        (None, None) => Vec::new(),
+    };
+    to_response(&fixture, request.url().clone(), body)
+}
+
+fn to_response(fixture: &Fixture, url: reqwest::Url, body: Vec<u8>) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    let mut builder = http::Response::builder().status(fixture.status).url(url);
+    for (name, value) in &fixture.headers {
+        builder = builder.header(name, value);
+    }
+    Ok(Response::from(builder.body(body)?))
+}
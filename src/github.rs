@@ -0,0 +1,552 @@
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{StatusCode, request::Parts},
+};
+use reqwest::{
+    Client, RequestBuilder, Response,
+    header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT},
+};
+use serde::Serialize;
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{fixtures, metrics, pacing, queue, readonly, reporting, state::AppState};
+
+// ------------------- Client -------------------
+
+/// Product name sent in `User-Agent` unless `HTTP_USER_AGENT` brands it.
+const DEFAULT_USER_AGENT: &str = "Sui-Move-Users-Fetcher";
+
+/// `User-Agent` for every outgoing request: `<HTTP_USER_AGENT>/<crate version>`.
+pub fn user_agent() -> String {
+    let product = std::env::var("HTTP_USER_AGENT").ok().filter(|ua| !ua.trim().is_empty());
+    format!("{}/{}", product.as_deref().map_or(DEFAULT_USER_AGENT, str::trim), env!("CARGO_PKG_VERSION"))
+}
+
+/// The shared HTTP client. Identification headers are set here once: the
+/// `User-Agent` and, when `HTTP_CLIENT_ID` is set, `X-Client-Id`, so GitHub
+/// and other upstreams can tell deployments apart.
+pub fn build_client() -> Result<Client, Box<dyn std::error::Error + Send + Sync>> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, HeaderValue::from_str(&user_agent())?);
+    if let Ok(id) = std::env::var("HTTP_CLIENT_ID")
+        && !id.trim().is_empty()
+    {
+        headers.insert(HeaderName::from_static("x-client-id"), HeaderValue::from_str(id.trim())?);
+    }
+
+    Ok(Client::builder().default_headers(headers).build()?)
+}
+
+/// A GitHub REST `GET` authenticated with `token`.
+pub fn get(client: &Client, token: &str, url: &str) -> RequestBuilder {
+    client.get(url).bearer_auth(token)
+}
+
+// ------------------- Request Tokens -------------------
+
+/// Header through which callers may run their request on their own quota.
+pub const CLIENT_TOKEN_HEADER: &str = "X-GitHub-Token";
+
+/// Whether `X-GitHub-Token` is honoured; `ALLOW_CLIENT_GITHUB_TOKENS=false`
+/// turns it off.
+pub fn client_tokens_allowed() -> bool {
+    std::env::var("ALLOW_CLIENT_GITHUB_TOKENS").map_or(true, |v| !matches!(v.trim(), "false" | "0" | "no"))
+}
+
+/// Classic tokens are 40 hex characters; newer ones carry a `gh?_` or
+/// `github_pat_` prefix. Anything else is rejected before reaching GitHub.
+pub fn is_plausible_token(token: &str) -> bool {
+    let prefixed = ["ghp_", "gho_", "ghu_", "ghs_", "github_pat_"].iter().any(|p| token.starts_with(p));
+    let classic = token.len() == 40 && token.chars().all(|c| c.is_ascii_hexdigit());
+    (prefixed || classic) && token.len() <= 255 && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The token a request's GitHub calls run with: the caller's
+/// `X-GitHub-Token` when allowed and present, else the server token. Its
+/// `Debug` output is redacted so it never reaches logs.
+#[derive(Clone)]
+pub struct RequestToken(pub String);
+
+impl std::fmt::Debug for RequestToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RequestToken(<redacted>)")
+    }
+}
+
+impl<S> FromRequestParts<S> for RequestToken
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(value) = parts.headers.get(CLIENT_TOKEN_HEADER) {
+            if !client_tokens_allowed() {
+                return Err((StatusCode::FORBIDDEN, format!("{CLIENT_TOKEN_HEADER} is disabled on this server")));
+            }
+            let token = value.to_str().map(str::trim).ok().filter(|t| is_plausible_token(t));
+            return token
+                .map(|t| RequestToken(t.to_string()))
+                .ok_or((StatusCode::BAD_REQUEST, format!("{CLIENT_TOKEN_HEADER} is not a GitHub token")));
+        }
+
+        Ok(RequestToken(AppState::from_ref(state).github_token))
+    }
+}
+
+// ------------------- Token Capabilities -------------------
+
+/// What kind of credential a token is, from its prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    /// `ghp_` or 40 hex characters.
+    Classic,
+    /// `github_pat_`, scoped to selected resources.
+    FineGrained,
+    /// `gho_` / `ghu_` user-to-server tokens.
+    Oauth,
+    /// `ghs_` installation tokens.
+    App,
+    Unknown,
+}
+
+impl TokenKind {
+    pub fn of(token: &str) -> Self {
+        if token.starts_with("github_pat_") {
+            TokenKind::FineGrained
+        } else if token.starts_with("ghp_") || (token.len() == 40 && token.chars().all(|c| c.is_ascii_hexdigit())) {
+            TokenKind::Classic
+        } else if token.starts_with("gho_") || token.starts_with("ghu_") {
+            TokenKind::Oauth
+        } else if token.starts_with("ghs_") {
+            TokenKind::App
+        } else {
+            TokenKind::Unknown
+        }
+    }
+
+    /// Whether scans with this token take the REST fallbacks in
+    /// [`FINE_GRAINED_DEGRADED`] instead of the affected GraphQL queries.
+    pub fn rest_only(self) -> bool {
+        self == TokenKind::FineGrained
+    }
+}
+
+/// A GraphQL query fine-grained tokens cannot rely on, and what is used instead.
+#[derive(Debug, Serialize)]
+pub struct DegradedCapability {
+    pub capability: &'static str,
+    pub fallback: &'static str,
+}
+
+/// Fine-grained tokens only see organizations and search results for the
+/// resources they were granted, so these GraphQL queries go through REST.
+pub const FINE_GRAINED_DEGRADED: &[DegradedCapability] = &[
+    DegradedCapability {
+        capability: "organizations",
+        fallback: "REST /users/{user}/orgs (public memberships)",
+    },
+    DegradedCapability {
+        capability: "issue_review_counts",
+        fallback: "REST /search/issues, one call per repository and count (search rate limit)",
+    },
+    DegradedCapability {
+        capability: "merged_pull_requests",
+        fallback: "REST /search/issues pages",
+    },
+];
+
+/// A token's kind and the capabilities degraded for it, as `/readyz` and
+/// `/rate-limit` report them.
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenCapabilities {
+    pub kind: TokenKind,
+    pub degraded: &'static [DegradedCapability],
+}
+
+pub fn capabilities(token: &str) -> TokenCapabilities {
+    let kind = TokenKind::of(token);
+    TokenCapabilities { kind, degraded: if kind.rest_only() { FINE_GRAINED_DEGRADED } else { &[] } }
+}
+
+// ------------------- Request Accounting -------------------
+
+tokio::task_local! {
+    static REQUESTS: Cell<u32>;
+}
+
+/// Runs `future` with a fresh GitHub request counter (see [`requests_made`]).
+pub async fn counting_requests<F: Future>(future: F) -> F::Output {
+    REQUESTS.scope(Cell::new(0), future).await
+}
+
+/// Counts one GitHub API call against the current task, if it is counting.
+fn record_request() {
+    let _ = REQUESTS.try_with(|count| count.set(count.get() + 1));
+    crate::deadline::note_request();
+}
+
+/// The last rate limit GitHub reported for one of its resources (`core`,
+/// `graphql`, `search`, ...).
+#[derive(Debug, Clone, Serialize)]
+pub struct RateBudget {
+    pub resource: String,
+    pub remaining: u64,
+    /// Unix time the budget refills.
+    pub reset: u64,
+}
+
+/// The last rate limit seen per resource, kept in the [`Runtime`](crate::state::Runtime).
+#[derive(Default)]
+pub struct RateBudgets(Mutex<BTreeMap<String, RateBudget>>);
+
+impl RateBudgets {
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, RateBudget>> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Notes the `X-RateLimit-*` headers of a response.
+    fn record(&self, resp: &Response) {
+        let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+        let number = |name: &str| header(name).and_then(|v| v.parse::<u64>().ok());
+        let (Some(remaining), Some(reset)) = (number("X-RateLimit-Remaining"), number("X-RateLimit-Reset")) else {
+            return;
+        };
+        let resource = header("X-RateLimit-Resource").unwrap_or("core").to_string();
+        self.lock().insert(resource.clone(), RateBudget { resource, remaining, reset });
+    }
+
+    /// The rate limits seen on the most recent GitHub responses, per resource.
+    pub fn observed(&self) -> Vec<RateBudget> {
+        self.lock().values().cloned().collect()
+    }
+
+    /// When scans can call GitHub again, if a scan resource (`core` or
+    /// `graphql`) was last seen exhausted and has not refilled yet.
+    pub fn exhausted_until(&self) -> Option<u64> {
+        let now = crate::storage::now_secs();
+        self.lock()
+            .values()
+            .filter(|b| matches!(b.resource.as_str(), "core" | "graphql") && b.remaining == 0 && b.reset > now)
+            .map(|b| b.reset)
+            .max()
+    }
+}
+
+/// GitHub subsystem a call goes to, the `class` label of the latency histogram.
+#[derive(Debug, Clone, Copy)]
+pub enum EndpointClass {
+    Graphql,
+    Trees,
+    Blobs,
+    Commits,
+    Search,
+    Repos,
+}
+
+impl EndpointClass {
+    fn as_str(self) -> &'static str {
+        match self {
+            EndpointClass::Graphql => "graphql",
+            EndpointClass::Trees => "trees",
+            EndpointClass::Blobs => "blobs",
+            EndpointClass::Commits => "commits",
+            EndpointClass::Search => "search",
+            EndpointClass::Repos => "repos",
+        }
+    }
+}
+
+/// Sends a GitHub request, counting it and recording its latency. Batch
+/// scans are held back first while interactive ones are active, and nothing
+/// is sent in serve-only mode ([`readonly::ServeOnly`]). Transient
+/// failures are retried as the current [`pacing`] profile allows. A 403 for
+/// missing OAuth scopes fails with [`ScopeError`]; other statuses are left
+/// to the caller.
+pub async fn send(class: EndpointClass, request: RequestBuilder) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
+    if readonly::enabled() {
+        return Err(readonly::ServeOnly.into());
+    }
+    queue::yield_to_interactive().await;
+    let pacing = pacing::current();
+    let mut request = request;
+    let mut retries = 0;
+    let result = loop {
+        // Fixtures are replayed as recorded, so only live calls are retried.
+        let next = match fixtures::mode() {
+            fixtures::Mode::Off if retries < pacing.max_retries => request.try_clone(),
+            _ => None,
+        };
+        let result = attempt(class, request).await;
+        let wait = transient_wait(&result).and_then(|requested| pacing.backoff(retries, requested));
+        match (next, wait) {
+            (Some(next), Some(wait)) => {
+                tracing::warn!("Retrying GitHub {} call in {}ms after {}", class.as_str(), wait.as_millis(), describe(&result));
+                tokio::time::sleep(wait).await;
+                request = next;
+                retries += 1;
+            }
+            _ => break result,
+        }
+    };
+
+    let resp = result?;
+    if let Some(e) = ScopeError::from_response(&resp) {
+        return Err(e.into());
+    }
+    Ok(resp)
+}
+
+type Attempt = Result<Response, Box<dyn std::error::Error + Send + Sync>>;
+
+async fn attempt(class: EndpointClass, request: RequestBuilder) -> Attempt {
+    record_request();
+    let started = Instant::now();
+    let result = match fixtures::mode() {
+        fixtures::Mode::Off => request.send().await.map_err(Into::into),
+        fixtures::Mode::Record => fixtures::record(request).await,
+        fixtures::Mode::Replay => fixtures::replay(request).await,
+    };
+    if let Ok(resp) = &result {
+        crate::state::runtime().budgets.record(resp);
+    }
+    metrics::observe_github(class.as_str(), &describe(&result), started.elapsed());
+    result
+}
+
+/// The status code of an attempt, or `error` when it got no response.
+fn describe(result: &Attempt) -> String {
+    match result {
+        Ok(resp) => resp.status().as_u16().to_string(),
+        Err(_) => "error".to_string(),
+    }
+}
+
+/// Whether an attempt failed transiently, with the least wait GitHub asked
+/// for before another: its `Retry-After`, or the reset of an exhausted rate
+/// limit. `None` when the outcome is final.
+fn transient_wait(result: &Attempt) -> Option<Duration> {
+    let resp = match result {
+        Ok(resp) => resp,
+        Err(_) => return Some(Duration::ZERO),
+    };
+    let status = resp.status();
+    let header = |name: &str| resp.headers().get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.trim().parse::<u64>().ok());
+    let retry_after = header("Retry-After").map(Duration::from_secs);
+    let exhausted = header("X-RateLimit-Remaining") == Some(0);
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Some(retry_after.unwrap_or_default());
+    }
+    if status == reqwest::StatusCode::FORBIDDEN && (retry_after.is_some() || exhausted) {
+        let until_reset = header("X-RateLimit-Reset").map(|reset| Duration::from_secs(reset.saturating_sub(crate::storage::now_secs())));
+        return Some(retry_after.or(until_reset).unwrap_or_default());
+    }
+    None
+}
+
+// ------------------- Scope Errors -------------------
+
+/// The token lacks an OAuth scope a GitHub call needs. Handlers answer it
+/// with 403 instead of a generic upstream failure, since only a token with
+/// more scopes can fix it.
+#[derive(Debug)]
+pub struct ScopeError {
+    /// REST path or GraphQL field that was refused.
+    pub endpoint: String,
+    /// Scopes of which GitHub requires one.
+    pub required: Vec<String>,
+}
+
+impl std::fmt::Display for ScopeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.required.as_slice() {
+            [] => write!(f, "GitHub token lacks a scope required by {}", self.endpoint),
+            [scope] => write!(f, "GitHub token lacks the {scope} scope required by {}", self.endpoint),
+            scopes => write!(f, "GitHub token needs one of the {} scopes for {}", scopes.join(", "), self.endpoint),
+        }
+    }
+}
+
+impl std::error::Error for ScopeError {}
+
+impl ScopeError {
+    /// A REST 403 whose `X-Accepted-OAuth-Scopes` are not among the token's
+    /// `X-OAuth-Scopes`.
+    fn from_response(resp: &Response) -> Option<ScopeError> {
+        if resp.status() != reqwest::StatusCode::FORBIDDEN {
+            return None;
+        }
+        let scopes = |name: &str| -> Vec<String> {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+                .unwrap_or_default()
+        };
+        // Fine-grained tokens send no `X-OAuth-Scopes`, and an exhausted rate
+        // limit is also a 403; neither is a scope problem.
+        let classic = resp.headers().contains_key("X-OAuth-Scopes");
+        let exhausted = resp.headers().get("X-RateLimit-Remaining").is_some_and(|v| v == "0");
+        let accepted = scopes("X-Accepted-OAuth-Scopes");
+        let granted = scopes("X-OAuth-Scopes");
+        if !classic || exhausted || accepted.is_empty() || accepted.iter().any(|s| granted.contains(s)) {
+            return None;
+        }
+        Some(ScopeError { endpoint: resp.url().path().to_string(), required: accepted })
+    }
+
+    /// A GraphQL `INSUFFICIENT_SCOPES` error, whose message reads "The
+    /// 'organizations' field requires one of the following scopes: ['read:org'], ...".
+    fn from_graphql(errors: &serde_json::Value) -> Option<ScopeError> {
+        let error = errors.as_array()?.iter().find(|e| e["type"] == "INSUFFICIENT_SCOPES")?;
+        let message = error["message"].as_str().unwrap_or_default();
+
+        let field = message.split_once("The '").and_then(|(_, rest)| rest.split_once('\'')).map(|(field, _)| field);
+        let path = error["path"]
+            .as_array()
+            .map(|p| p.iter().filter_map(|s| s.as_str()).collect::<Vec<_>>().join("."))
+            .filter(|p| !p.is_empty());
+        let required = message
+            .split_once("following scopes: [")
+            .and_then(|(_, rest)| rest.split_once(']'))
+            .map(|(list, _)| list.split(',').map(|s| s.trim().trim_matches('\'').to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        Some(ScopeError {
+            endpoint: format!("GraphQL {}", path.as_deref().or(field).unwrap_or("query")),
+            required,
+        })
+    }
+}
+
+/// GitHub API calls made so far inside [`counting_requests`]; 0 outside it.
+pub fn requests_made() -> u32 {
+    REQUESTS.try_with(Cell::get).unwrap_or(0)
+}
+
+// ------------------- GraphQL Helper -------------------
+
+#[tracing::instrument(name = "github.graphql", skip_all)]
+pub async fn graphql_request(
+    client: &Client,
+    token: &str,
+    query: &str,
+    variables: Option<serde_json::Value>,
+) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+    let mut body = serde_json::json!({ "query": query });
+    if let Some(vars) = variables {
+        body["variables"] = vars;
+    }
+
+    let request = client.post("https://api.github.com/graphql").bearer_auth(token).json(&body);
+    let resp = send(EndpointClass::Graphql, request).await?;
+
+    if !resp.status().is_success() {
+        reporting::github_response("https://api.github.com/graphql", resp.status());
+    }
+
+    let json: serde_json::Value = resp.json().await?;
+    if let Some(errors) = json.get("errors") {
+        if let Some(e) = ScopeError::from_graphql(errors) {
+            return Err(e.into());
+        }
+        return Err(format!("GraphQL errors: {}", errors).into());
+    }
+
+    Ok(json["data"].clone())
+}
+
+/// Longest GitHub login.
+const MAX_LOGIN_LEN: usize = 39;
+
+/// Whether `login` can be a GitHub user or organization login: 1-39 ASCII
+/// letters, digits and hyphens. Logins go into API paths and search
+/// queries made with the server token, so nothing else may get through.
+pub fn is_valid_login(login: &str) -> bool {
+    (1..=MAX_LOGIN_LEN).contains(&login.len()) && login.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Normalises `https://github.com/owner/repo[.git]` or `owner/repo` to
+/// `owner/repo`. Each part must be made of `[A-Za-z0-9._-]` and must not be
+/// `.` or `..`; deeper paths such as `/tree/main` are rejected.
+pub fn parse_repo_url(input: &str) -> Option<String> {
+    let trimmed = input.trim().trim_end_matches('/');
+    let path = trimmed
+        .strip_prefix("https://github.com/")
+        .or_else(|| trimmed.strip_prefix("http://github.com/"))
+        .or_else(|| trimmed.strip_prefix("github.com/"))
+        .unwrap_or(trimmed);
+
+    let (owner, name) = path.split_once('/')?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    if !is_repo_part(owner) || !is_repo_part(name) {
+        return None;
+    }
+
+    Some(format!("{owner}/{name}"))
+}
+
+fn is_repo_part(part: &str) -> bool {
+    !part.is_empty()
+        && part != "."
+        && part != ".."
+        && part.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repo_url_accepts_urls_and_slugs() {
+        for input in [
+            "MystenLabs/sui",
+            "https://github.com/MystenLabs/sui",
+            "http://github.com/MystenLabs/sui",
+            "github.com/MystenLabs/sui",
+            "https://github.com/MystenLabs/sui.git",
+            "https://github.com/MystenLabs/sui/",
+            "  MystenLabs/sui  ",
+        ] {
+            assert_eq!(parse_repo_url(input).as_deref(), Some("MystenLabs/sui"), "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_repo_url_needs_an_owner_and_a_name() {
+        for input in ["", "MystenLabs", "https://github.com/MystenLabs", "https://github.com/", "/sui", "MystenLabs/.git"] {
+            assert_eq!(parse_repo_url(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn parse_repo_url_keeps_dotted_names() {
+        assert_eq!(parse_repo_url("sui-foundation/sui.io").as_deref(), Some("sui-foundation/sui.io"));
+        assert_eq!(parse_repo_url("a_b/c.d-e.git").as_deref(), Some("a_b/c.d-e"));
+    }
+
+    #[test]
+    fn parse_repo_url_rejects_extra_segments_and_odd_characters() {
+        for input in [
+            "https://github.com/MystenLabs/sui/tree/main/crates",
+            "MystenLabs/sui/issues",
+            "../sui",
+            "MystenLabs/..",
+            "MystenLabs/.",
+            "MystenLabs/sui?tab=readme",
+            "MystenLabs/sui#readme",
+            "MystenLabs/sui%2Fother",
+            "Mysten Labs/sui",
+            "MystenLabs/sui&x=1",
+        ] {
+            assert_eq!(parse_repo_url(input), None, "{input}");
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{github, pacing};
+
+// ------------------- Structs -------------------
+
+/// Protocol-level activity in the Sui Improvement Proposals repository.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Governance {
+    pub repository: String,
+    pub sips_authored: Vec<SipProposal>,
+    pub sips_merged: u32,
+    /// Pull requests in the SIPs repository the user reviewed.
+    pub sips_reviewed: u32,
+    /// Pull requests and issues in the SIPs repository the user commented on.
+    pub sips_commented: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SipProposal {
+    pub number: u64,
+    pub title: String,
+    pub url: String,
+    pub state: ProposalState,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalState {
+    Open,
+    Closed,
+    Merged,
+    #[serde(other)]
+    Unknown,
+}
+
+impl ProposalState {
+    /// Maps a GraphQL `PullRequestState` (`OPEN`, `CLOSED`, `MERGED`).
+    fn from_github(state: &str) -> Self {
+        match state {
+            "OPEN" => ProposalState::Open,
+            "CLOSED" => ProposalState::Closed,
+            "MERGED" => ProposalState::Merged,
+            _ => ProposalState::Unknown,
+        }
+    }
+}
+
+const DEFAULT_SIPS_REPO: &str = "sui-foundation/sips";
+
+/// Authored proposals fetched per account.
+const MAX_LISTED_SIPS: u32 = 50;
+
+// ------------------- Core Logic -------------------
+
+/// Looks up SIPs authored, reviewed and commented on by any of `usernames`
+/// in `SIPS_REPO` with one GraphQL request per account. Returns `None` when
+/// there is no activity at all.
+#[tracing::instrument(name = "governance", skip(client, token))]
+pub async fn fetch_governance(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+) -> Result<Option<Governance>, Box<dyn std::error::Error + Send + Sync>> {
+    let repository = std::env::var("SIPS_REPO").unwrap_or_else(|_| DEFAULT_SIPS_REPO.to_string());
+    let query = r#"
+    query($authored:String!, $reviewed:String!, $commented:String!, $first:Int!) {
+      authored: search(query:$authored, type:ISSUE, first:$first) {
+        nodes { ... on PullRequest { number title url state createdAt } }
+      }
+      reviewed: search(query:$reviewed, type:ISSUE, first:1) { issueCount }
+      commented: search(query:$commented, type:ISSUE, first:1) { issueCount }
+    }
+    "#;
+
+    let mut governance = Governance { repository: repository.clone(), ..Default::default() };
+
+    for username in usernames {
+        let variables = serde_json::json!({
+            "authored": format!("repo:{repository} is:pr author:{username}"),
+            "reviewed": format!("repo:{repository} is:pr reviewed-by:{username} -author:{username}"),
+            "commented": format!("repo:{repository} commenter:{username} -author:{username}"),
+            "first": MAX_LISTED_SIPS,
+        });
+        let data = github::graphql_request(client, token, query, Some(variables)).await?;
+
+        for node in data["authored"]["nodes"].as_array().into_iter().flatten() {
+            let Some(number) = node["number"].as_u64() else {
+                continue;
+            };
+            let state = ProposalState::from_github(node["state"].as_str().unwrap_or_default());
+            if state == ProposalState::Merged {
+                governance.sips_merged += 1;
+            }
+            governance.sips_authored.push(SipProposal {
+                number,
+                title: node["title"].as_str().unwrap_or_default().to_string(),
+                url: node["url"].as_str().unwrap_or_default().to_string(),
+                state,
+                created_at: node["createdAt"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        governance.sips_reviewed += data["reviewed"]["issueCount"].as_u64().unwrap_or(0) as u32;
+        governance.sips_commented += data["commented"]["issueCount"].as_u64().unwrap_or(0) as u32;
+
+        pacing::pause().await;
+    }
+
+    let active = !governance.sips_authored.is_empty() || governance.sips_reviewed > 0 || governance.sips_commented > 0;
+    Ok(active.then_some(governance))
+}
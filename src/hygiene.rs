@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+
+// ------------------- Commit Hygiene -------------------
+
+/// Conventional Commits types (`feat: ...`, `fix(scope)!: ...`).
+const CONVENTIONAL_TYPES: &[&str] =
+    &["feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert"];
+
+/// Subjects that say nothing about the change when they stand alone.
+const LOW_EFFORT_WORDS: &[&str] = &[
+    "wip", "fix", "fixes", "fixed", "update", "updates", "updated", "change", "changes", "minor", "tmp", "temp", "test",
+    "stuff", "misc", "commit", "save",
+];
+
+/// Low-effort subjects are at most this many words (`fix typo`, `wip again`).
+const LOW_EFFORT_MAX_WORDS: usize = 2;
+
+/// How carefully a user writes commit messages in their Move repositories,
+/// a soft signal of engineering maturity for grant reviews
+/// (`commit_hygiene=true`). Merge commits are left out. Commits read from
+/// local clones carry their subject only, so they count as one-liners.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommitHygiene {
+    pub messages: u32,
+    /// Mean subject (first line) length in characters.
+    pub average_subject_length: f64,
+    /// Share of messages that are a lone `wip`, `fix`, `update`, ... line.
+    pub low_effort_ratio: f64,
+    /// Share of subjects following Conventional Commits.
+    pub conventional_ratio: f64,
+    /// Raw totals the ratios come from, so incremental re-scans can add to them.
+    pub subject_chars: u64,
+    pub low_effort: u32,
+    pub conventional: u32,
+}
+
+impl CommitHygiene {
+    fn from_totals(messages: u32, subject_chars: u64, low_effort: u32, conventional: u32) -> Self {
+        let per_message = |n: f64, scale: f64| if messages == 0 { 0.0 } else { (n / messages as f64 * scale).round() / scale };
+        CommitHygiene {
+            messages,
+            average_subject_length: per_message(subject_chars as f64, 10.0),
+            low_effort_ratio: per_message(low_effort as f64, 100.0),
+            conventional_ratio: per_message(conventional as f64, 100.0),
+            subject_chars,
+            low_effort,
+            conventional,
+        }
+    }
+
+    /// Both sets of messages together.
+    pub fn combine(&self, other: &CommitHygiene) -> CommitHygiene {
+        CommitHygiene::from_totals(
+            self.messages + other.messages,
+            self.subject_chars + other.subject_chars,
+            self.low_effort + other.low_effort,
+            self.conventional + other.conventional,
+        )
+    }
+}
+
+/// Hygiene of `commits` (commits API objects), merge commits aside.
+pub fn hygiene(commits: &[serde_json::Value]) -> CommitHygiene {
+    let (mut messages, mut subject_chars, mut low_effort, mut conventional) = (0, 0, 0, 0);
+    for commit in commits {
+        if commit["parents"].as_array().is_some_and(|p| p.len() > 1) {
+            continue;
+        }
+        let Some(message) = commit["commit"]["message"].as_str() else {
+            continue;
+        };
+        let message = message.trim();
+        let subject = message.lines().next().unwrap_or_default().trim();
+        messages += 1;
+        subject_chars += subject.chars().count() as u64;
+        if is_low_effort(message, subject) {
+            low_effort += 1;
+        }
+        if is_conventional(subject) {
+            conventional += 1;
+        }
+    }
+    CommitHygiene::from_totals(messages, subject_chars, low_effort, conventional)
+}
+
+/// Every repository's hygiene together; `None` when none was analysed.
+pub fn total<'a>(repositories: impl Iterator<Item = &'a CommitHygiene>) -> Option<CommitHygiene> {
+    let mut total: Option<CommitHygiene> = None;
+    for repo in repositories {
+        total = Some(match total {
+            Some(total) => total.combine(repo),
+            None => repo.clone(),
+        });
+    }
+    total
+}
+
+/// A one-line message of at most two words led by a word like `wip` or
+/// `fix`, or of a word shorter than three letters.
+fn is_low_effort(message: &str, subject: &str) -> bool {
+    if message.lines().count() > 1 {
+        return false;
+    }
+    let subject = subject.to_lowercase();
+    let words: Vec<&str> = subject.split_whitespace().collect();
+    match words.first() {
+        None => true,
+        Some(first) => {
+            let first = first.trim_matches(|c: char| !c.is_alphanumeric());
+            words.len() <= LOW_EFFORT_MAX_WORDS && (LOW_EFFORT_WORDS.contains(&first) || (words.len() == 1 && first.len() < 3))
+        }
+    }
+}
+
+/// `type(scope)!: description`, with the scope and `!` optional.
+fn is_conventional(subject: &str) -> bool {
+    let Some((head, description)) = subject.split_once(':') else {
+        return false;
+    };
+    if !description.starts_with(' ') || description.trim().is_empty() {
+        return false;
+    }
+    let head = head.strip_suffix('!').unwrap_or(head);
+    let kind = match head.split_once('(') {
+        Some((kind, scope)) => match scope.strip_suffix(')') {
+            Some(scope) if !scope.is_empty() && !scope.contains(['(', ')']) => kind,
+            _ => return false,
+        },
+        None => head,
+    };
+    CONVENTIONAL_TYPES.contains(&kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conventional_subjects() {
+        for subject in ["feat: add staking", "fix(move): handle zero coins", "refactor!: drop v1 API", "chore(deps)!: bump sui"] {
+            assert!(is_conventional(subject), "{subject}");
+        }
+    }
+
+    #[test]
+    fn unconventional_subjects() {
+        for subject in [
+            "add staking",
+            "feat:add staking",
+            "feat: ",
+            "feature: add staking",
+            "Feat: add staking",
+            "fix(): empty scope",
+            "fix(a(b)): nested scope",
+            "fix(move: unclosed scope",
+            "fix!(move): bang before scope",
+        ] {
+            assert!(!is_conventional(subject), "{subject}");
+        }
+    }
+
+    #[test]
+    fn hygiene_skips_merges_and_counts_ratios() {
+        let commit = |message: &str, parents: usize| {
+            serde_json::json!({ "commit": { "message": message }, "parents": vec![serde_json::json!({}); parents] })
+        };
+        let commits = [
+            commit("feat: add pool", 1),
+            commit("wip", 1),
+            commit("Merge branch 'main'", 2),
+            commit("Tidy up the swap module", 1),
+        ];
+        let hygiene = hygiene(&commits);
+        assert_eq!((hygiene.messages, hygiene.conventional, hygiene.low_effort), (3, 1, 1));
+        assert_eq!(hygiene.conventional_ratio, 0.33);
+    }
+}
@@ -0,0 +1,260 @@
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::{collections::BTreeMap, convert::Infallible, sync::OnceLock};
+
+// ------------------- Messages -------------------
+
+/// A translatable message: a catalog key plus named arguments substituted
+/// into `{name}` placeholders.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Message { key, args: Vec::new() }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+}
+
+const DEFAULT_LANG: &str = "en";
+
+type Catalog = BTreeMap<String, String>;
+
+/// Built-in catalogs; `I18N_DIR` may add languages or override entries with
+/// `<lang>.json` files mapping keys to templates.
+const BUILTIN: &[(&str, &[(&str, &str)])] = &[
+    (
+        "en",
+        &[
+            ("username_empty", "username must not be empty"),
+            ("username_invalid", "`{username}` is not a GitHub username"),
+            ("too_many_aliases", "at most {max} aliases can be merged, got {count}"),
+            ("not_org_member", "{username} is not a public member of the {org} organization"),
+            ("batch_too_large", "at most {max} usernames per batch"),
+            ("invalid_email", "email must be a valid address"),
+            ("user_not_found", "GitHub user {username} not found"),
+            ("not_stored", "no stored scan of {username} on this read-only deployment"),
+            ("unknown_policy", "unknown policy {name}; available: {available}"),
+            ("unknown_ecosystem", "unknown ecosystem {name}; available: {available}"),
+            ("ecosystem_quick", "ecosystem needs a full or deep scan; quick scans cannot tell Move ecosystems apart"),
+            ("avatar_not_scanned", "no stored scan of {username}; avatars are served for scanned users only"),
+            ("certificate_not_found", "certificate {id} not found"),
+            ("invalid_repo_url", "{url} is not a GitHub repository URL"),
+            ("too_many_repos", "at most {max} repositories per request"),
+            ("org_empty", "org must not be empty"),
+            ("org_invalid", "`{org}` is not a GitHub organization"),
+            ("org_not_found", "GitHub organization {org} not found"),
+            ("freshness_invalid", "invalid min_freshness {value}: use seconds or a number with s, m, h or d"),
+            ("leaderboard_sort_invalid", "unknown sort {sort}: expected {expected}"),
+            ("window_date_invalid", "{date} is not a YYYY-MM-DD date"),
+            ("analysis_disabled", "{param}=true is not enabled on this deployment ({env})"),
+            ("window_reversed", "window start {start} is after its end {end}"),
+            ("window_too_long", "event windows span at most {max} days"),
+        ],
+    ),
+    (
+        "es",
+        &[
+            ("username_empty", "el nombre de usuario no puede estar vacío"),
+            ("username_invalid", "`{username}` no es un nombre de usuario de GitHub"),
+            ("too_many_aliases", "se pueden combinar como máximo {max} alias, se recibieron {count}"),
+            ("not_org_member", "{username} no es miembro público de la organización {org}"),
+            ("batch_too_large", "como máximo {max} usuarios por lote"),
+            ("invalid_email", "el correo electrónico debe ser una dirección válida"),
+            ("user_not_found", "no se encontró el usuario de GitHub {username}"),
+            ("not_stored", "no hay ningún análisis guardado de {username} en esta instancia de solo lectura"),
+            ("unknown_policy", "política desconocida {name}; disponibles: {available}"),
+            ("unknown_ecosystem", "ecosistema desconocido {name}; disponibles: {available}"),
+            ("ecosystem_quick", "ecosystem requiere un análisis full o deep; los análisis rápidos no distinguen ecosistemas Move"),
+            ("avatar_not_scanned", "no hay ningún análisis guardado de {username}; solo se sirven avatares de usuarios analizados"),
+            ("certificate_not_found", "no se encontró el certificado {id}"),
+            ("invalid_repo_url", "{url} no es una URL de repositorio de GitHub"),
+            ("too_many_repos", "como máximo {max} repositorios por solicitud"),
+            ("org_empty", "la organización no puede estar vacía"),
+            ("org_invalid", "`{org}` no es una organización de GitHub"),
+            ("org_not_found", "no se encontró la organización de GitHub {org}"),
+            ("freshness_invalid", "min_freshness no válido {value}: use segundos o un número con s, m, h o d"),
+            ("leaderboard_sort_invalid", "orden desconocido {sort}: se esperaba {expected}"),
+            ("window_date_invalid", "{date} no es una fecha AAAA-MM-DD"),
+            ("analysis_disabled", "{param}=true no está habilitado en este despliegue ({env})"),
+            ("window_reversed", "el inicio de la ventana {start} es posterior a su fin {end}"),
+            ("window_too_long", "las ventanas de evento abarcan como máximo {max} días"),
+        ],
+    ),
+    (
+        "zh",
+        &[
+            ("username_empty", "用户名不能为空"),
+            ("username_invalid", "`{username}` 不是有效的 GitHub 用户名"),
+            ("too_many_aliases", "最多可合并 {max} 个别名，实际收到 {count} 个"),
+            ("not_org_member", "{username} 不是 {org} 组织的公开成员"),
+            ("batch_too_large", "每批最多 {max} 个用户名"),
+            ("invalid_email", "邮箱地址无效"),
+            ("user_not_found", "未找到 GitHub 用户 {username}"),
+            ("not_stored", "此只读部署中没有 {username} 的已存储扫描结果"),
+            ("unknown_policy", "未知策略 {name}；可用策略：{available}"),
+            ("unknown_ecosystem", "未知生态 {name}；可用生态：{available}"),
+            ("ecosystem_quick", "ecosystem 需要 full 或 deep 扫描；quick 扫描无法区分 Move 生态"),
+            ("avatar_not_scanned", "没有 {username} 的已存储扫描结果；仅提供已扫描用户的头像"),
+            ("certificate_not_found", "未找到证书 {id}"),
+            ("invalid_repo_url", "{url} 不是 GitHub 仓库地址"),
+            ("too_many_repos", "每次请求最多 {max} 个仓库"),
+            ("org_empty", "组织不能为空"),
+            ("org_invalid", "`{org}` 不是有效的 GitHub 组织"),
+            ("org_not_found", "未找到 GitHub 组织 {org}"),
+            ("freshness_invalid", "无效的 min_freshness {value}：请使用秒数，或带 s、m、h、d 的数字"),
+            ("leaderboard_sort_invalid", "未知的排序方式 {sort}：应为 {expected}"),
+            ("window_date_invalid", "{date} 不是 YYYY-MM-DD 格式的日期"),
+            ("analysis_disabled", "此部署未启用 {param}=true（{env}）"),
+            ("window_reversed", "时间窗口开始日期 {start} 晚于结束日期 {end}"),
+            ("window_too_long", "活动时间窗口最多 {max} 天"),
+        ],
+    ),
+    (
+        "ko",
+        &[
+            ("username_empty", "사용자 이름은 비워 둘 수 없습니다"),
+            ("username_invalid", "`{username}`은(는) GitHub 사용자 이름이 아닙니다"),
+            ("too_many_aliases", "최대 {max}개의 별칭만 병합할 수 있습니다 (받은 개수: {count})"),
+            ("not_org_member", "{username}은(는) {org} 조직의 공개 멤버가 아닙니다"),
+            ("batch_too_large", "배치당 최대 {max}개의 사용자 이름만 허용됩니다"),
+            ("invalid_email", "유효한 이메일 주소가 아닙니다"),
+            ("user_not_found", "GitHub 사용자 {username}을(를) 찾을 수 없습니다"),
+            ("not_stored", "이 읽기 전용 배포에는 {username}의 저장된 스캔이 없습니다"),
+            ("unknown_policy", "알 수 없는 정책 {name}입니다. 사용 가능: {available}"),
+            ("unknown_ecosystem", "알 수 없는 생태계 {name}입니다. 사용 가능: {available}"),
+            ("ecosystem_quick", "ecosystem에는 full 또는 deep 스캔이 필요합니다. quick 스캔으로는 Move 생태계를 구분할 수 없습니다"),
+            ("avatar_not_scanned", "{username}의 저장된 스캔이 없습니다. 스캔된 사용자의 아바타만 제공합니다"),
+            ("certificate_not_found", "인증서 {id}을(를) 찾을 수 없습니다"),
+            ("invalid_repo_url", "{url}은(는) GitHub 저장소 URL이 아닙니다"),
+            ("too_many_repos", "요청당 최대 {max}개의 저장소만 허용됩니다"),
+            ("org_empty", "조직은 비워 둘 수 없습니다"),
+            ("org_invalid", "`{org}`은(는) GitHub 조직이 아닙니다"),
+            ("org_not_found", "GitHub 조직 {org}을(를) 찾을 수 없습니다"),
+            ("freshness_invalid", "잘못된 min_freshness {value}: 초 단위 숫자 또는 s, m, h, d가 붙은 숫자를 사용하세요"),
+            ("leaderboard_sort_invalid", "알 수 없는 정렬 {sort}: {expected} 중 하나여야 합니다"),
+            ("window_date_invalid", "{date}은(는) YYYY-MM-DD 형식의 날짜가 아닙니다"),
+            ("analysis_disabled", "이 배포에서는 {param}=true가 활성화되어 있지 않습니다 ({env})"),
+            ("window_reversed", "기간 시작일 {start}이(가) 종료일 {end}보다 늦습니다"),
+            ("window_too_long", "이벤트 기간은 최대 {max}일입니다"),
+        ],
+    ),
+];
+
+fn catalogs() -> &'static BTreeMap<String, Catalog> {
+    static CATALOGS: OnceLock<BTreeMap<String, Catalog>> = OnceLock::new();
+    CATALOGS.get_or_init(|| {
+        let mut catalogs: BTreeMap<String, Catalog> = BUILTIN
+            .iter()
+            .map(|(lang, entries)| {
+                (lang.to_string(), entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+            })
+            .collect();
+
+        if let Ok(dir) = std::env::var("I18N_DIR") {
+            match std::fs::read_dir(&dir) {
+                Ok(files) => {
+                    for path in files.flatten().map(|f| f.path()) {
+                        let Some(lang) = path.extension().filter(|e| *e == "json").and(path.file_stem()) else {
+                            continue;
+                        };
+                        let lang = lang.to_string_lossy().to_lowercase();
+                        match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|raw| {
+                            serde_json::from_str::<Catalog>(&raw).map_err(|e| e.to_string())
+                        }) {
+                            Ok(entries) => catalogs.entry(lang).or_default().extend(entries),
+                            Err(e) => tracing::warn!("Ignoring catalog {}: {e}", path.display()),
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Ignoring I18N_DIR={dir}: {e}"),
+            }
+        }
+
+        catalogs
+    })
+}
+
+// ------------------- Locale -------------------
+
+/// The language a response should use: `lang=` from the query string, else
+/// the best supported `Accept-Language` entry, else English.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    pub lang: String,
+}
+
+impl Locale {
+    /// Renders `message`, falling back to English for keys the language lacks.
+    pub fn render(&self, message: &Message) -> String {
+        let catalogs = catalogs();
+        let template = catalogs
+            .get(&self.lang)
+            .and_then(|c| c.get(message.key))
+            .or_else(|| catalogs.get(DEFAULT_LANG).and_then(|c| c.get(message.key)))
+            .map(String::as_str)
+            .unwrap_or(message.key);
+
+        message
+            .args
+            .iter()
+            .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{name}}}"), value))
+    }
+}
+
+/// Primary subtag of a language tag, lowercased (`pt-BR` → `pt`).
+fn primary(tag: &str) -> String {
+    tag.trim().split(['-', '_']).next().unwrap_or_default().to_lowercase()
+}
+
+fn negotiate(query: Option<&str>, accept_language: Option<&str>) -> String {
+    let catalogs = catalogs();
+
+    let requested = query
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .find_map(|pair| pair.strip_prefix("lang="))
+        .map(primary);
+    if let Some(lang) = requested.filter(|l| catalogs.contains_key(l)) {
+        return lang;
+    }
+
+    let mut ranked: Vec<(String, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = primary(parts.next()?);
+            let q = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            Some((tag, q))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ranked
+        .into_iter()
+        .map(|(tag, _)| tag)
+        .find(|tag| catalogs.contains_key(tag))
+        .unwrap_or_else(|| DEFAULT_LANG.to_string())
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for Locale {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let accept_language = parts
+            .headers
+            .get(axum::http::header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok());
+        Ok(Locale { lang: negotiate(parts.uri.query(), accept_language) })
+    }
+}
@@ -0,0 +1,270 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{
+    admin, github, i18n, jwt, queue, readonly, reporting,
+    scan::{self, OwnedRepository, ScanLimits, ScanMode, ScanOptions},
+    state::AppState,
+    storage,
+};
+
+// ------------------- GitHub App -------------------
+
+/// Seconds before expiry at which a cached installation token is replaced.
+const TOKEN_REFRESH_MARGIN_SECS: u64 = 300;
+
+/// Lifetime GitHub gives installation tokens.
+const INSTALLATION_TOKEN_SECS: u64 = 3600;
+
+/// The GitHub App the service authenticates as to read org-internal
+/// repositories: `GITHUB_APP_ID`, `GITHUB_APP_PRIVATE_KEY_PATH` (the PEM key
+/// GitHub issues) and `GITHUB_APP_INTERNAL_ORGS`, the comma-separated orgs
+/// that opted in to having their private and internal repositories scanned.
+/// `None` when the first two are not both set. Read once at startup into
+/// [`AppState`].
+pub fn config() -> Result<Option<AppConfig>, Box<dyn std::error::Error + Send + Sync>> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    let (Some(app_id), Some(key_path)) = (var("GITHUB_APP_ID"), var("GITHUB_APP_PRIVATE_KEY_PATH")) else {
+        return Ok(None);
+    };
+    let app_id = app_id.trim().parse::<u64>().map_err(|_| format!("GITHUB_APP_ID {app_id} is not a number"))?;
+    let pem = std::fs::read_to_string(&key_path).map_err(|e| format!("cannot read GITHUB_APP_PRIVATE_KEY_PATH {key_path}: {e}"))?;
+    let key = jwt::signing_key(&pem).map_err(|e| format!("GITHUB_APP_PRIVATE_KEY_PATH: {e}"))?;
+    let internal_orgs = var("GITHUB_APP_INTERNAL_ORGS")
+        .map(|orgs| orgs.split(',').map(|o| o.trim().to_lowercase()).filter(|o| !o.is_empty()).collect())
+        .unwrap_or_default();
+    Ok(Some(AppConfig { app_id, key, internal_orgs }))
+}
+
+pub struct AppConfig {
+    app_id: u64,
+    key: ring::signature::RsaKeyPair,
+    internal_orgs: Vec<String>,
+}
+
+impl AppConfig {
+    fn allows(&self, org: &str) -> bool {
+        self.internal_orgs.iter().any(|o| o.eq_ignore_ascii_case(org))
+    }
+}
+
+/// A JWT identifying the App, valid for nine minutes (GitHub accepts at
+/// most ten), backdated for clock drift.
+fn app_jwt(config: &AppConfig, now: u64) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let claims = serde_json::json!({ "iat": now.saturating_sub(60), "exp": now + 540, "iss": config.app_id.to_string() });
+    jwt::sign_rs256(&config.key, &claims)
+}
+
+/// Org (lowercase) -> installation token and when it expires.
+fn installation_tokens() -> std::sync::MutexGuard<'static, HashMap<String, (String, u64)>> {
+    static TOKENS: OnceLock<Mutex<HashMap<String, (String, u64)>>> = OnceLock::new();
+    TOKENS.get_or_init(Default::default).lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// A valid token of the App's installation on `org`, minted again when the
+/// cached one is about to expire; `None` when the App is not installed there.
+async fn installation_token(
+    client: &Client,
+    config: &AppConfig,
+    org: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let now = storage::now_secs();
+    let key = org.to_lowercase();
+    if let Some((token, expires_at)) = installation_tokens().get(&key)
+        && *expires_at > now + TOKEN_REFRESH_MARGIN_SECS
+    {
+        return Ok(Some(token.clone()));
+    }
+
+    let jwt = app_jwt(config, now)?;
+    let url = format!("https://api.github.com/orgs/{org}/installation");
+    let resp = github::send(github::EndpointClass::Repos, github::get(client, &jwt, &url)).await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        return Err(format!("GitHub App installation lookup for {org} failed with {}", resp.status()).into());
+    }
+    let installation: serde_json::Value = resp.json().await?;
+    let id = installation["id"].as_u64().ok_or("GitHub App installation has no id")?;
+
+    let url = format!("https://api.github.com/app/installations/{id}/access_tokens");
+    let resp = github::send(github::EndpointClass::Repos, client.post(&url).bearer_auth(&jwt)).await?;
+    if !resp.status().is_success() {
+        return Err(format!("GitHub App token exchange for {org} failed with {}", resp.status()).into());
+    }
+    let body: serde_json::Value = resp.json().await?;
+    let token = body["token"].as_str().ok_or("GitHub App token response has no token")?.to_string();
+    installation_tokens().insert(key, (token.clone(), now + INSTALLATION_TOKEN_SECS));
+    Ok(Some(token))
+}
+
+// ------------------- Internal Repositories -------------------
+
+fn installation_repository(node: &serde_json::Value) -> OwnedRepository {
+    OwnedRepository {
+        name: node["full_name"].as_str().unwrap_or_default().to_string(),
+        url: node["html_url"].as_str().unwrap_or_default().to_string(),
+        default_branch: node["default_branch"].as_str().unwrap_or("main").to_string(),
+        description: node["description"].as_str().map(String::from),
+        pushed_at: node["pushed_at"].as_str().map(String::from),
+        disk_usage_kb: node["size"].as_u64(),
+        topics: node["topics"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t.as_str().map(String::from))
+            .collect(),
+    }
+}
+
+/// Up to `max_repos` private and internal repositories of `org` the
+/// installation can read; public ones are covered by ordinary scans.
+async fn fetch_internal_repositories(
+    client: &Client,
+    token: &str,
+    org: &str,
+    max_repos: usize,
+) -> Result<Vec<OwnedRepository>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut repositories = Vec::new();
+    for page in 1.. {
+        let url = format!("https://api.github.com/installation/repositories?per_page=100&page={page}");
+        let resp = github::send(github::EndpointClass::Repos, github::get(client, token, &url)).await?;
+        if !resp.status().is_success() {
+            return Err(format!("GitHub App repository listing for {org} failed with {}", resp.status()).into());
+        }
+        let body: serde_json::Value = resp.json().await?;
+        let nodes = body["repositories"].as_array().cloned().unwrap_or_default();
+        repositories.extend(
+            nodes
+                .iter()
+                .filter(|r| r["owner"]["login"].as_str().is_some_and(|o| o.eq_ignore_ascii_case(org)))
+                .filter(|r| r["visibility"].as_str().map_or(r["private"].as_bool() == Some(true), |v| v != "public"))
+                .map(installation_repository),
+        );
+        if nodes.len() < 100 || repositories.len() >= max_repos {
+            break;
+        }
+        crate::pacing::pause().await;
+    }
+    repositories.truncate(max_repos);
+    Ok(repositories)
+}
+
+/// A user's Move work in an org's internal repositories, with the
+/// repositories identified only by rank so nothing about them is exposed.
+#[derive(Debug, Serialize)]
+pub struct InternalContributions {
+    pub org: String,
+    pub username: String,
+    /// Private and internal repositories of the org that were checked.
+    pub repositories_scanned: usize,
+    pub move_repositories: usize,
+    pub total_commits: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
+    /// Most commits first, named `<org>/internal-<n>`.
+    pub repositories: Vec<InternalRepository>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InternalRepository {
+    pub repo: String,
+    pub commit_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub move_since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
+}
+
+/// Scans the internal repositories of `org` for `usernames`' Move work with
+/// the installation's token. `None` when the App is not installed on `org`.
+pub async fn internal_contributions(
+    client: &Client,
+    config: &AppConfig,
+    org: &str,
+    usernames: &[String],
+) -> Result<Option<InternalContributions>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(token) = installation_token(client, config, org).await? else {
+        return Ok(None);
+    };
+    let limits = ScanLimits::ceiling();
+    let repositories = fetch_internal_repositories(client, &token, org, limits.max_repos).await?;
+    let repositories_scanned = repositories.len();
+    let result = scan::scan_private_repos(client, &token, usernames, ScanOptions::new(ScanMode::Full, limits), repositories).await?;
+
+    let mut move_repositories: Vec<_> = result.repositories.iter().filter(|r| r.commit_count > 0).collect();
+    move_repositories.sort_by_key(|r| std::cmp::Reverse(r.commit_count));
+    Ok(Some(InternalContributions {
+        org: org.to_string(),
+        username: usernames[0].clone(),
+        repositories_scanned,
+        move_repositories: move_repositories.len(),
+        total_commits: result.total_commits,
+        last_commit_at: result.last_commit_at.clone(),
+        repositories: move_repositories
+            .into_iter()
+            .enumerate()
+            .map(|(at, r)| InternalRepository {
+                repo: format!("{org}/internal-{}", at + 1),
+                commit_count: r.commit_count,
+                move_since: r.move_since.clone(),
+                last_commit_at: r.last_commit_at.clone(),
+            })
+            .collect(),
+    }))
+}
+
+// ------------------- Handler -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct InternalContributionsQuery {
+    org: String,
+    username: String,
+}
+
+/// `GET /internal-contributions?org=<org>&username=<github_user>`: commit
+/// counts of the user in the org's private and internal Sui Move
+/// repositories, read through the GitHub App's installation on an org
+/// listed in `GITHUB_APP_INTERNAL_ORGS`. Reviewers only; never stored.
+pub async fn internal_contributions_handler(
+    locale: i18n::Locale,
+    headers: HeaderMap,
+    Query(params): Query<InternalContributionsQuery>,
+    State(AppState { client, github_app, .. }): State<AppState>,
+) -> Result<Json<InternalContributions>, (StatusCode, String)> {
+    let reviewer = admin::require_reviewer(&headers)?;
+    let Some(config) = github_app else {
+        return Err((StatusCode::NOT_FOUND, "GitHub App mode is not configured".to_string()));
+    };
+    let org = params.org.trim();
+    if org.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, locale.render(&i18n::Message::new("org_empty"))));
+    }
+    if !config.allows(org) {
+        return Err((StatusCode::FORBIDDEN, format!("{org} has not opted in to internal repository scans (GITHUB_APP_INTERNAL_ORGS)")));
+    }
+    let usernames = scan::parse_aliases(&params.username).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+
+    tracing::info!("{reviewer} requested the internal contributions of {} in {org}", usernames[0]);
+    let scan = internal_contributions(&client, &config, org, &usernames);
+    match queue::run(queue::Lane::Interactive, scan).await {
+        Ok(Some(report)) => Ok(Json(report)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("the GitHub App is not installed on {org}"))),
+        Err(e) if e.is::<queue::Cancelled>() || e.is::<readonly::ServeOnly>() => Err(crate::upstream_error(org, e)),
+        // Upstream errors may name repositories, so only their kind is logged.
+        Err(e) => {
+            tracing::warn!("Internal repository scan of {org} failed: {:?}", reporting::error_kind(&*e));
+            Err((StatusCode::BAD_GATEWAY, format!("scanning the internal repositories of {org} failed")))
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use base64::Engine;
+use ring::signature::RsaKeyPair;
+
+// ------------------- RS256 JWTs -------------------
+
+/// Reads a PKCS#1 (`RSA PRIVATE KEY`) or PKCS#8 (`PRIVATE KEY`) PEM.
+pub fn signing_key(pem: &str) -> Result<RsaKeyPair, String> {
+    let body: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+    let der = base64::engine::general_purpose::STANDARD.decode(body.trim()).map_err(|e| e.to_string())?;
+    let key = if pem.contains("BEGIN RSA PRIVATE KEY") { RsaKeyPair::from_der(&der) } else { RsaKeyPair::from_pkcs8(&der) };
+    key.map_err(|e| format!("invalid private key: {e}"))
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// A JWT carrying `claims`, signed RS256 with `key`.
+pub fn sign_rs256(key: &RsaKeyPair, claims: &serde_json::Value) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let header = serde_json::json!({ "alg": "RS256", "typ": "JWT" });
+    let signing_input = format!("{}.{}", base64url(&serde_json::to_vec(&header)?), base64url(&serde_json::to_vec(claims)?));
+
+    let mut signature = vec![0; key.public().modulus_len()];
+    key.sign(&ring::signature::RSA_PKCS1_SHA256, &ring::rand::SystemRandom::new(), signing_input.as_bytes(), &mut signature)
+        .map_err(|_| "signing the JWT failed")?;
+    Ok(format!("{signing_input}.{}", base64url(&signature)))
+}
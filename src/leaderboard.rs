@@ -0,0 +1,194 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::HashMap};
+
+use crate::{
+    admin, certificates, i18n,
+    storage::{Storage, StoredScan},
+};
+
+// ------------------- Structs -------------------
+
+/// Entries returned when `limit` is not given, and the most ever returned.
+const DEFAULT_LIMIT: usize = 100;
+const MAX_LIMIT: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    /// Comma-separated sort keys, most significant first.
+    sort: Option<String>,
+    /// Only users carrying this label.
+    label: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Leaderboard {
+    /// The sort keys applied, including the default tiebreakers.
+    pub sort: Vec<SortKey>,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// One stored Move developer's metrics, as of their latest scan.
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub username: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Commit-weighted authorship confidence, as on certificates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    pub commits: u32,
+    /// Move lines authored (deep scans only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loc: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
+    /// Packages published on chain by the user's bound wallets, or else the
+    /// number of version tags and publish commits in their repositories.
+    pub packages_published: u32,
+    pub scanned_at: u64,
+}
+
+impl LeaderboardEntry {
+    fn from_scan(scan: &StoredScan, labels: Vec<String>) -> Self {
+        let result = &scan.result;
+        let packages_published = match &result.chain_activity {
+            Some(activity) => activity.packages_published,
+            None => result.repositories.iter().map(|r| r.releases.len() as u32).sum(),
+        };
+        LeaderboardEntry {
+            rank: 0,
+            username: scan.username.clone(),
+            labels,
+            score: certificates::score(result),
+            commits: result.total_commits,
+            loc: result.move_lines_authored,
+            last_commit_at: result.last_commit_at.clone(),
+            packages_published,
+            scanned_at: scan.scanned_at,
+        }
+    }
+}
+
+// ------------------- Sorting -------------------
+
+/// One ordering over entries, highest first. Entries lacking the metric
+/// rank below every entry that has it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    Score,
+    Commits,
+    Loc,
+    RecentActivity,
+    PackagesPublished,
+}
+
+impl SortKey {
+    const ALL: [SortKey; 5] =
+        [SortKey::Score, SortKey::Commits, SortKey::Loc, SortKey::RecentActivity, SortKey::PackagesPublished];
+
+    fn name(self) -> &'static str {
+        match self {
+            SortKey::Score => "score",
+            SortKey::Commits => "commits",
+            SortKey::Loc => "loc",
+            SortKey::RecentActivity => "recent_activity",
+            SortKey::PackagesPublished => "packages_published",
+        }
+    }
+
+    fn compare(self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+        match self {
+            SortKey::Score => b.score.unwrap_or(f64::MIN).total_cmp(&a.score.unwrap_or(f64::MIN)),
+            SortKey::Commits => b.commits.cmp(&a.commits),
+            SortKey::Loc => b.loc.cmp(&a.loc),
+            SortKey::RecentActivity => b.last_commit_at.cmp(&a.last_commit_at),
+            SortKey::PackagesPublished => b.packages_published.cmp(&a.packages_published),
+        }
+    }
+}
+
+/// Tiebreakers appended after the requested keys, in this order.
+const TIEBREAKERS: [SortKey; 3] = [SortKey::Score, SortKey::Commits, SortKey::RecentActivity];
+
+/// Sort keys composed in order: each later key only decides between entries
+/// every earlier key ranks equal, and the username decides last.
+#[derive(Debug, Clone)]
+pub struct Sort(Vec<SortKey>);
+
+impl Sort {
+    /// Parses `score,commits,...`; the default tiebreakers follow the
+    /// requested keys. `None` sorts by score.
+    pub fn parse(spec: Option<&str>) -> Result<Self, i18n::Message> {
+        let mut keys = Vec::new();
+        for name in spec.unwrap_or_default().split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let Some(key) = SortKey::ALL.into_iter().find(|k| k.name() == name) else {
+                let expected: Vec<&str> = SortKey::ALL.iter().map(|k| k.name()).collect();
+                return Err(i18n::Message::new("leaderboard_sort_invalid").arg("sort", name).arg("expected", expected.join("|")));
+            };
+            keys.push(key);
+        }
+        for key in TIEBREAKERS {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        Ok(Sort(keys))
+    }
+
+    fn compare(&self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+        self.0
+            .iter()
+            .fold(Ordering::Equal, |ordering, key| ordering.then_with(|| key.compare(a, b)))
+            .then_with(|| a.username.cmp(&b.username))
+    }
+}
+
+/// Ranks the latest stored scans of users with Move code, keeping only
+/// users carrying `label` when one is given. `labels` is keyed by
+/// lowercased username.
+pub fn build(
+    scans: &[StoredScan],
+    labels: &HashMap<String, Vec<String>>,
+    label: Option<&str>,
+    sort: &Sort,
+    limit: usize,
+) -> Leaderboard {
+    let mut entries: Vec<LeaderboardEntry> = scans
+        .iter()
+        .filter(|s| s.result.has_move_files)
+        .map(|s| LeaderboardEntry::from_scan(s, labels.get(&s.username.to_lowercase()).cloned().unwrap_or_default()))
+        .filter(|e| label.is_none_or(|label| e.labels.iter().any(|l| l.eq_ignore_ascii_case(label))))
+        .collect();
+    entries.sort_by(|a, b| sort.compare(a, b));
+    entries.truncate(limit);
+    for (rank, entry) in entries.iter_mut().enumerate() {
+        entry.rank = rank + 1;
+    }
+    Leaderboard { sort: sort.0.clone(), entries }
+}
+
+// ------------------- Handler -------------------
+
+/// `GET /leaderboard?sort=<key>,<key>&label=<label>&limit=<n>`: stored Move
+/// developers who have not opted out, ranked by the requested metrics.
+pub async fn leaderboard_handler(
+    locale: i18n::Locale,
+    Query(params): Query<LeaderboardQuery>,
+    State(storage): State<Storage>,
+) -> Result<Json<Leaderboard>, (StatusCode, String)> {
+    let sort = Sort::parse(params.sort.as_deref()).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let (scans, labels) = storage
+        .blocking(|storage| Ok((storage.discoverable_developers(0)?, storage.all_labels()?)))
+        .await
+        .map_err(admin::internal)?;
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    Ok(Json(build(&scans, &labels, params.label.as_deref().map(str::trim), &sort, limit)))
+}
@@ -0,0 +1,1277 @@
+#![recursion_limit = "256"]
+
+use clap::{Parser, Subcommand};
+use axum::{
+    Router, extract::{Path, Query, State}, middleware, http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE}}, response::{IntoResponse, Json, Response}, routing::{delete, get, post}
+};
+use tower_http::{cors::CorsLayer, sensitive_headers::SetSensitiveRequestHeadersLayer, trace::TraceLayer};
+use dotenv::dotenv;
+use futures_util::StreamExt;
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+mod actions;
+mod activity;
+mod admin;
+mod annotations;
+mod archive;
+mod audit;
+mod authorship;
+mod avatars;
+pub mod bench;
+mod blobs;
+mod cache;
+mod certificates;
+mod chain;
+mod claims;
+mod classify;
+mod deadline;
+mod detect;
+mod docs;
+mod edition;
+mod doctor;
+mod ecosystem;
+mod ecosystems;
+mod fixtures;
+mod github;
+mod governance;
+mod hygiene;
+mod i18n;
+mod installations;
+mod jwt;
+mod leaderboard;
+mod metrics;
+mod mirror;
+mod notify;
+mod orgs;
+mod pacing;
+mod policy;
+mod privacy;
+mod profile;
+mod queue;
+mod readonly;
+mod releases;
+mod repo_rules;
+mod reporting;
+mod resolve;
+mod ruleset;
+mod scan;
+mod search;
+mod sheets;
+mod similarity;
+mod slack;
+mod snapshot;
+mod state;
+mod stats;
+mod storage;
+mod telemetry;
+mod templates;
+mod verify;
+mod webhook;
+mod window;
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Deserialize)]
+struct DeveloperQuery {
+    username: String,
+    #[serde(default)]
+    mode: scan::ScanMode,
+    /// Only enumerate repositories and return the projected cost of a full scan.
+    #[serde(default)]
+    estimate: bool,
+    max_repos: Option<usize>,
+    max_tree_entries: Option<usize>,
+    max_commit_pages: Option<u32>,
+    /// Reject the request unless one of the accounts publicly belongs to this org.
+    require_org: Option<String>,
+    /// Compare the user's Move files against other stored users' files.
+    #[serde(default)]
+    similarity: bool,
+    /// Clone detected Move packages and confirm they build with `sui move build`
+    /// (`VERIFY_BUILD_ENABLED` and a reviewer token).
+    #[serde(default)]
+    verify_build: bool,
+    /// Store the canonical JSON report on Walrus or IPFS (`ARCHIVE_BACKEND`;
+    /// `ARCHIVE_ENABLED` and a reviewer token).
+    #[serde(default)]
+    archive: bool,
+    /// Include per-stage timing and GitHub request counts (`diagnostics`).
+    #[serde(default)]
+    debug: bool,
+    /// Seconds past the cache TTL a stored result may still be served (with
+    /// `stale: true`) while it is refreshed in the background.
+    max_stale: Option<u64>,
+    /// Oldest stored result acceptable (`90`, `15m`, `2h`, `1d`), TTL aside;
+    /// an older one forces a rescan.
+    min_freshness: Option<String>,
+    /// Leave merge commits out of commit counts.
+    #[serde(default)]
+    exclude_merges: bool,
+    /// Report merged pull requests per repository alongside commit counts.
+    #[serde(default)]
+    count_merged_prs: bool,
+    /// Measure commit message quality (`commit_hygiene`).
+    #[serde(default)]
+    commit_hygiene: bool,
+    /// Named verdict policy from `VERDICT_POLICY_PATH`.
+    policy: Option<String>,
+    /// Narrow the result and verdict to one Move ecosystem (`MOVE_ECOSYSTEMS`).
+    ecosystem: Option<String>,
+    /// Read trees and history from local clones instead of the REST API.
+    #[serde(default)]
+    strategy: scan::ScanStrategy,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchRequest {
+    #[serde(default)]
+    usernames: Vec<String>,
+    /// Also scan every user carrying this label.
+    label: Option<String>,
+    #[serde(default)]
+    mode: scan::ScanMode,
+    /// Scan every user, even those recently confirmed to have no Move code.
+    #[serde(default)]
+    force: bool,
+    /// Oldest cached result reused per user (`15m`, `2h`, ...), TTL aside.
+    min_freshness: Option<String>,
+    policy: Option<String>,
+    /// Narrow each result and verdict to one Move ecosystem.
+    ecosystem: Option<String>,
+    /// An event to measure each user's activity in, against the period of
+    /// the same length before it.
+    window: Option<window::EventWindow>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum BatchStatus {
+    Scanned,
+    Cached,
+    /// Confirmed to have no Move code within `NON_DEVELOPER_SKIP_SECS`; not scanned.
+    KnownNonDeveloper,
+    /// Serve-only deployment without a stored scan of the user.
+    NotStored,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchEntry {
+    username: String,
+    status: BatchStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<scan::UserMoveFilesResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_kind: Option<reporting::ErrorKind>,
+    /// Activity within the batch's `window`, for users with Move code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window: Option<window::WindowActivity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    window_error: Option<String>,
+}
+
+/// Users accepted per batch request.
+const MAX_BATCH_USERS: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct CheckReposRequest {
+    /// Account (or comma-separated aliases) the commits are attributed to.
+    username: String,
+    /// `https://github.com/owner/repo` URLs or `owner/repo` names.
+    repos: Vec<String>,
+    #[serde(default)]
+    mode: scan::ScanMode,
+    policy: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckReposResponse {
+    #[serde(flatten)]
+    result: scan::UserMoveFilesResponse,
+    /// Listed repositories GitHub does not know (or the token cannot see).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    repos_not_found: Vec<String>,
+}
+
+/// Repositories accepted per `/check-repos` request.
+const MAX_CHECK_REPOS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct ResolveEmailQuery {
+    email: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ProfileFormat {
+    #[default]
+    Json,
+    /// schema.org `Person` plus the `sui:` vocabulary, as `application/ld+json`.
+    Jsonld,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileQuery {
+    #[serde(default)]
+    format: ProfileFormat,
+}
+
+#[derive(Debug, Deserialize)]
+struct EcosystemGraphQuery {
+    #[serde(default)]
+    min_commits: u32,
+}
+
+// ------------------- CLI -------------------
+
+#[derive(Debug, Parser)]
+#[command(name = "sui-contributors", version, about = "Sui Move GitHub Users API")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the HTTP API (the default when no subcommand is given)
+    Serve {
+        /// Never call GitHub: answer only from stored scans, without a
+        /// GitHub token (also `SERVE_ONLY=true`)
+        #[arg(long)]
+        serve_only: bool,
+    },
+    /// Validate config, GitHub access and a fixture scan, printing a pass/fail checklist
+    Doctor,
+    /// Scan a user and exit 0 if they pass a verdict policy, 1 otherwise,
+    /// printing GitHub Actions annotations for each criterion
+    Verify {
+        /// GitHub username, or several comma-separated accounts of one person
+        username: String,
+        /// Named policy from `VERDICT_POLICY_PATH` (default policy if omitted)
+        #[arg(long)]
+        policy: Option<String>,
+        /// Stop at the first Move repository instead of counting commits
+        #[arg(long)]
+        quick: bool,
+    },
+    /// Check tree filtering, Move parsing and aggregation over recorded
+    /// fixtures (`GITHUB_FIXTURES=record`) against a budget, exiting 1 when a
+    /// stage is over budget or has no fixtures (`cargo bench --bench scan`
+    /// only times them)
+    Bench {
+        /// Fixtures directory (default `GITHUB_FIXTURES_DIR` or `fixtures`)
+        #[arg(long)]
+        fixtures: Option<String>,
+        #[arg(long, default_value_t = 100)]
+        iterations: u32,
+        /// JSON object of stage name -> maximum median in milliseconds
+        #[arg(long)]
+        budget: String,
+    },
+    /// Write every table of `DATABASE_PATH` (scans and their history,
+    /// certificates, labels, wallets, ...) to a versioned JSON snapshot
+    ExportData {
+        /// Snapshot file, or `-` for stdout
+        path: String,
+    },
+    /// Restore a snapshot from `export-data` into an empty `DATABASE_PATH`
+    ImportData {
+        /// Snapshot file, or `-` for stdin
+        path: String,
+    },
+}
+
+// ------------------- Main -------------------
+
+/// The binary's entry point (`src/main.rs`); the library target exists so
+/// `benches/` can reach the scan stages.
+pub async fn main() {
+    dotenv().ok();
+
+    match Cli::parse().command.unwrap_or(Command::Serve { serve_only: false }) {
+        Command::Serve { serve_only } => serve(serve_only).await,
+        Command::Doctor => std::process::exit(cli(doctor::run()).await),
+        Command::Verify { username, policy, quick } => {
+            let mode = if quick { scan::ScanMode::Quick } else { scan::ScanMode::Full };
+            std::process::exit(cli(actions::run(&username, policy.as_deref(), mode)).await)
+        }
+        Command::Bench { fixtures, iterations, budget } => {
+            std::process::exit(bench::run(fixtures.as_deref(), iterations, &budget))
+        }
+        Command::ExportData { path } => std::process::exit(snapshot::export(&path)),
+        Command::ImportData { path } => std::process::exit(snapshot::import(&path)),
+    }
+}
+
+/// Runs a command that calls GitHub under a runtime read from the environment.
+async fn cli(command: impl Future<Output = i32>) -> i32 {
+    state::scope(Arc::new(state::Runtime::from_env()), command).await
+}
+
+async fn serve(serve_only: bool) {
+    let _sentry_guard = reporting::init();
+    let tracer_provider = telemetry::init();
+
+    if serve_only || readonly::requested_by_env() {
+        readonly::enable();
+    }
+    let github_token = if readonly::enabled() {
+        tracing::info!("Serve-only mode: answering from stored scans, GitHub is never called");
+        String::new()
+    } else {
+        fixtures::github_token().expect("GITHUB_TOKEN environment variable not set")
+    };
+
+    let client = github::build_client().expect("Invalid HTTP_USER_AGENT or HTTP_CLIENT_ID");
+    let storage = storage::open_from_env().expect("Failed to open database");
+
+    let pipeline = detect::Pipeline::from_env().expect("Invalid SCAN_DETECTORS");
+    tracing::info!("Scan detectors: {}", pipeline.names().join(", "));
+    let policies = policy::PolicySet::from_env().expect("Invalid VERDICT_POLICY_PATH");
+    tracing::info!("Verdict policies: {}", policies.names().join(", "));
+    let runtime = Arc::new(state::Runtime::new(policies, pipeline));
+    let github_app = installations::config().expect("Invalid GitHub App configuration");
+    let state = state::AppState::new(client.clone(), github_token.clone(), storage.clone(), github_app, runtime.clone());
+    // Background jobs spawned by the server keep this scope.
+    state::scope(runtime, run_server(state, client, github_token, storage)).await;
+    telemetry::shutdown(tracer_provider);
+}
+
+async fn run_server(state: state::AppState, client: Client, github_token: String, storage: storage::Storage) {
+    if !readonly::enabled() {
+        tracing::info!("GitHub token kind: {:?}", state.capabilities.kind);
+        for degraded in state.capabilities.degraded {
+            tracing::warn!("Degraded for this token: {} (using {})", degraded.capability, degraded.fallback);
+        }
+        templates::spawn_refresh_job(client.clone(), github_token.clone(), storage.clone());
+        spawn_preload(client.clone(), github_token.clone(), storage.clone());
+    }
+    cache::spawn_retention_job(storage.clone());
+    stats::spawn_rollup_job(storage.clone());
+    chain::spawn_health_checks(client.clone());
+
+    let app_cors = CorsLayer::new()
+    .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+    .allow_origin("https://www.suiref.xyz".parse::<HeaderValue>().unwrap())
+    // .allow_origin(Any)
+    .allow_headers([AUTHORIZATION, CONTENT_TYPE, HeaderName::from_static("x-github-token"), HeaderName::from_static(queue::SCAN_CANCEL_TOKEN_HEADER), HeaderName::from_static("prefer"), HeaderName::from_static("x-pacing-profile")])
+    .expose_headers([HeaderName::from_static(queue::SCAN_ID_HEADER), HeaderName::from_static(queue::SCAN_CANCEL_TOKEN_HEADER)])
+    ; // enabled cors for only this endpoint
+
+    let app = Router::new()
+        .route("/", get(root))
+        .route("/metrics", get(metrics::metrics_handler))
+        .route("/readyz", get(readyz_handler))
+        .route("/rate-limit", get(rate_limit_handler))
+        .route(
+            "/check-sui-developer",
+            get(check_sui_developer_handler)
+                .layer(middleware::from_fn(queue::cancellable))
+                .layer(middleware::from_fn(queue::shed_load)),
+        )
+        .route(
+            "/check-sui-developers",
+            post(check_sui_developers_handler)
+                .layer(middleware::from_fn(queue::cancellable))
+                .layer(middleware::from_fn(queue::shed_load)),
+        )
+        .route(
+            "/check-repos",
+            post(check_repos_handler)
+                .layer(middleware::from_fn(queue::cancellable))
+                .layer(middleware::from_fn(queue::shed_load)),
+        )
+        .route("/scans/{id}/cancel", post(queue::cancel_scan))
+        .route("/queue", get(queue::queue_status))
+        .route(
+            "/org-external-contributors",
+            get(orgs::external_contributors_handler)
+                .layer(middleware::from_fn(queue::cancellable))
+                .layer(middleware::from_fn(queue::shed_load)),
+        )
+        .route(
+            "/internal-contributions",
+            get(installations::internal_contributions_handler)
+                .layer(middleware::from_fn(queue::cancellable))
+                .layer(middleware::from_fn(queue::shed_load)),
+        )
+        .route("/resolve-email", get(resolve_email_handler))
+        .route(
+            "/verify-claim",
+            post(claims::verify_claim)
+                .layer(middleware::from_fn(queue::cancellable))
+                .layer(middleware::from_fn(queue::shed_load)),
+        )
+        .route("/verify-claim/key", get(claims::signing_public_key))
+        .route("/ecosystem-graph", get(ecosystem_graph_handler))
+        .route("/leaderboard", get(leaderboard::leaderboard_handler))
+        .route("/search-developers", get(search::search_developers))
+        .route("/stats/ecosystem", get(stats::ecosystem_stats))
+        .route("/ruleset", get(ruleset::ruleset))
+        .route("/opt-out", post(privacy::opt_out))
+        .route("/profile/{username}", get(profile_handler))
+        .route("/avatar/{username}", get(avatars::avatar))
+        .route("/admin/templates", get(admin::list_templates).post(admin::add_template))
+        .route("/admin/templates/{id}", delete(admin::remove_template))
+        .route("/cohorts/{id}/audit-sample", get(audit::audit_sample))
+        .route("/certificates", get(certificates::list_certificates))
+        .route("/certificates/{id}", get(certificates::get_certificate))
+        .route("/integrations/slack/command", post(slack::command))
+        .route("/github/webhook", post(webhook::receive))
+        .route("/admin/cache", delete(admin::flush_cache))
+        .route("/admin/reanalyze", post(ruleset::reanalyze_handler))
+        .route("/admin/wallets/{username}", get(admin::get_wallets).put(admin::set_wallets))
+        .route("/admin/labels/{username}", get(admin::get_labels).put(admin::set_labels))
+        .route("/admin/sheets/export", post(sheets::export_handler))
+        .route("/users/{username}/data", delete(admin::delete_user_data))
+        .route("/annotations/{username}", get(annotations::list_annotations).post(annotations::add_annotation))
+        .route("/annotations/{username}/{id}", delete(annotations::remove_annotation))
+        .route_layer(middleware::from_fn(deadline::enforce))
+        .route_layer(middleware::from_fn(pacing::select))
+        .layer(middleware::from_fn_with_state(state.clone(), state::enter))
+        .with_state(state)
+        .layer(app_cors)
+        .layer(TraceLayer::new_for_http())
+        .layer(SetSensitiveRequestHeadersLayer::new([AUTHORIZATION, HeaderName::from_static("x-github-token")]))
+        .layer(sentry::integrations::tower::SentryHttpLayer::new().enable_transaction())
+        .layer(sentry::integrations::tower::NewSentryLayer::new_from_top());
+
+    let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
+    let listener = TcpListener::bind(format!("0.0.0.0:{port}"))
+        .await
+        .expect("Failed to bind port");
+
+    tracing::info!("🚀 Server running on http://0.0.0.0:{port}");
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await
+        .unwrap();
+}
+
+// ------------------- Handlers -------------------
+
+async fn root() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "service": "Sui Move GitHub Users API",
+        "endpoints": {
+            "/check-sui-developer?username=<github_user>": "Check if a specific GitHub user has .move files with repo and commit details",
+            "/check-sui-developer?username=<github_user>,<alias>": "Merge the results of up to 5 accounts belonging to the same person, with per-owner subtotals (owners)",
+            "/check-sui-developer?username=<github_user>&mode=quick": "Stop at the first repository with .move files and skip commit counting",
+            "/check-sui-developer?username=<github_user>&mode=deep": "Full scan plus blame attribution of Move lines (move_lines_authored)",
+            "/check-sui-developer?username=<github_user>&estimate=true": "Estimate the GitHub API calls and time a full scan would take",
+            "/check-sui-developer?username=<github_user>&similarity=true": "Report Move files highly similar to other scanned users' code",
+            "/check-sui-developer?username=<github_user>&verify_build=true": "Clone detected Move packages and report whether each compiles (VERIFY_BUILD_ENABLED; ADMIN_TOKEN or REVIEWER_TOKENS)",
+            "/check-sui-developer?username=<github_user>&archive=true": "Archive the canonical JSON report on Walrus or IPFS and return its content ID (ARCHIVE_ENABLED; ADMIN_TOKEN or REVIEWER_TOKENS)",
+            "/check-sui-developer?username=<github_user>&debug=true": "Include per-stage timing and GitHub request counts (diagnostics)",
+            "/check-sui-developer?username=<github_user>&max_stale=<secs>": "Accept a cached result up to this long past its TTL (stale: true) while it refreshes",
+            "/check-sui-developer?username=<github_user>&min_freshness=<15m|2h|1d>": "Serve the cached result only if it is at most this old, else rescan (blocking, or 202 and refresh with Prefer: respond-async); also a batch body field",
+            "/check-sui-developer?username=<github_user>&exclude_merges=true&count_merged_prs=true": "Drop merge commits and report merged PRs per repo (credits squash merges)",
+            "/check-sui-developer?username=<github_user>&ecosystem=sui|aptos|movement": "Narrow the result and verdict to the Move repositories of one ecosystem, told apart by Move.toml dependencies and SDKs (MOVE_ECOSYSTEMS lists those served, default sui; with several, every response adds a per-ecosystem ecosystems verdict; not in quick mode)",
+            "/check-sui-developer?username=<github_user>&commit_hygiene=true": "Add commit_hygiene: average subject length, share of lone wip/fix one-liners and Conventional Commits adherence, per repo and overall (not in quick mode)",
+            "/check-sui-developer?username=<github_user>&mode=deep&strategy=clone": "Read trees, history and blame from size-capped local clones instead of the REST API (CLONE_MAX_REPO_KB)",
+            "/check-sui-developer?username=<github_user>&policy=<name>": "Evaluate the verdict against a named VERDICT_POLICY_PATH policy (rule expressions over scan metrics)",
+            "/check-sui-developer?username=<github_user>&require_org=<org>": "Only scan if the user is a public member of the given GitHub org",
+            "/check-sui-developer?username=<github_user>&max_repos=&max_tree_entries=&max_commit_pages=": "Bound the scan depth (clamped to server ceilings)",
+            "POST /check-sui-developers": "Batch scan of {\"usernames\": [...]}; users recently confirmed to have no Move code are skipped unless \"force\": true",
+            "POST /check-sui-developers with \"window\": {\"start\": \"YYYY-MM-DD\", \"end\": \"YYYY-MM-DD\"}": "Per user, commits and Move lines added during the event minus the same repositories' activity over the equally long period before it (delta_commits, delta_loc; delta_loc is null when loc_is_partial)",
+            "POST /check-sui-developers (Accept: application/x-ndjson)": "Stream each batch entry as a JSON line as soon as it completes (up to 500 usernames)",
+            "POST /check-repos": "Scan {\"username\": ..., \"repos\": [<github_url>, ...]} attributing commits in the listed repositories, without enumerating the account, with per-owner subtotals (owners)",
+            "/org-external-contributors?org=<org>&max_repos=<n>": "Contributors to the org's Sui Move repositories who are not org members, ranked by commits",
+            "/internal-contributions?org=<org>&username=<github_user>": "The user's commits in the private and internal Sui Move repositories of an org that opted in (GITHUB_APP_INTERNAL_ORGS), read through the GitHub App's installation (GITHUB_APP_ID, GITHUB_APP_PRIVATE_KEY_PATH); repositories are named internal-<n> only, never stored (ADMIN_TOKEN or REVIEWER_TOKENS)",
+            "/resolve-email?email=<commit_email>": "Find the GitHub accounts and Move repos associated with a commit email",
+            "/ecosystem-graph?min_commits=<n>": "Developer ↔ Move repository graph built from stored scans",
+            "/leaderboard?sort=score|commits|loc|recent_activity|packages_published&limit=<n>": "Stored Move developers ranked by comma-separated sort keys; score, commits and recent_activity break ties",
+            "/search-developers?q=<text>&min_score=<0..1>&framework=sui&page=<n>": "Prefix search over stored developers' usernames, repository names and Move package names, 20 per page (opted-out developers excluded)",
+            "/check-sui-developer?username=<github_user>&lang=es": "Localize error messages (es, zh, ko, I18N_DIR catalogs; Accept-Language also honoured)",
+            "/profile/<github_user>": "GitHub profile merged with the latest stored Sui scan",
+            "/avatar/<github_user>?size=40|80|120|240|460": "The GitHub avatar of a user with a stored scan, from a local cache (AVATAR_TTL, default 7d), refreshed when their profile's avatar changes",
+            "/profile/<github_user>?format=jsonld": "The profile as a schema.org Person with Sui-developer terms and any valid certificate (application/ld+json)",
+            "/cohorts/<id>/audit-sample?n=10&seed=<seed>&policy=<name>": "A random sample of the verified users labelled cohort:<id>, with links to the files and commits they were verified on and the seed that redraws it (ADMIN_TOKEN or REVIEWER_TOKENS)",
+            "/certificates/<id>": "Certificate issued when a scan passes its verdict policy (username, score, policy, expiry, valid)",
+            "/certificates?username=<github_user>": "Every certificate issued to a user, newest first",
+            "POST /github/webhook": "GitHub push/create webhook (GITHUB_WEBHOOK_SECRET) keeping stored scans of tracked users and WEBHOOK_ORGS fresh; a redelivery (same X-GitHub-Delivery) is ignored",
+            "/check-sui-developer (503)": "Returned with queue_length and estimated_wait_secs while SCAN_QUEUE_MAX_DEPTH scans are queued (SCAN_WORKERS run at once, interactive checks ahead of batch, refresh and preload scans)",
+            "POST /integrations/slack/command": "Slack slash command (SLACK_SIGNING_SECRET): `/sui-check <github_user>` posts the summary card to the channel",
+            "POST /scans/<id>/cancel": "Cancel a running or queued scan by the X-Scan-Id its response carries, sending back its X-Scan-Cancel-Token (or ADMIN_TOKEN); disconnecting also cancels",
+            "/queue?id=<scan id>": "Running, queued and recently finished scans with estimated start times from worker slots, scan durations and the GitHub rate budget (scan IDs shown to admins, or your own via id)",
+            "X-GitHub-Token: <token>": "Run scan, resolve and profile requests on the caller's own GitHub quota (ALLOW_CLIENT_GITHUB_TOKENS=false disables)",
+            "X-Pacing-Profile: aggressive|balanced|gentle": "Run the request's GitHub calls under another pacing profile of delays, retries and backoff (admin; PACING_PROFILE sets the default, PACING_PROFILES_PATH adds profiles)",
+            "serve --serve-only": "Never call GitHub and need no token (SERVE_ONLY=true): checks, batches, profiles and avatars come from stored data (404 for users without a stored scan); endpoints that must reach GitHub return 503",
+            "/readyz": "Readiness: database reachable, plus the server token's kind and degraded capabilities (503 when not ready)",
+            "/rate-limit": "Remaining GitHub quota (core, graphql, search) of the request's token, with its kind and degraded capabilities",
+            "/metrics": "Prometheus metrics, including GitHub request latency histograms per endpoint class and status",
+            "/ruleset": "Detection and analysis rules scans are produced under (version, detectors, category rules digest) and the changelog of built-in versions; each result records its ruleset",
+            "POST /admin/reanalyze?all=<bool>&username=<github_user>": "Re-run the analysis of stored scans (categories, template matches, subtotals) whose ruleset differs from the current one, from inputs stored with each scan, without calling GitHub (admin)",
+            "DELETE /admin/cache": "Drop cached scan results and re-run the PRELOAD_USERS warm-up (admin)",
+            "DELETE /users/<github_user>/data": "Erase every stored scan, fingerprint, wallet binding and certificate of a user, and scans that merged them in as an alias (admin; SCAN_RETENTION_SECS expires stored data automatically)",
+            "POST /annotations/<github_user>": "Reviewer note or override {\"kind\": note|boilerplate|original|identity_confirmed, \"repo\", \"note\"} merged into later responses (ADMIN_TOKEN or REVIEWER_TOKENS; GET lists, DELETE /annotations/<github_user>/<id> removes)",
+            "/stats/ecosystem?from=<YYYY-MM-DD>&to=<YYYY-MM-DD>": "Daily rollups of verified developers, new developers, Move repositories and commits across stored scans",
+            "POST /opt-out": "Leave the leaderboard, ecosystem graph and org contributor discovery for {\"username\", \"gist_id\"} (a gist containing `sui-contributors opt-out: <username>`) or with your own token in X-GitHub-Token; self-initiated checks still work",
+            "POST /verify-claim": "Signed attest/deny of {\"username\", \"repo\", \"claimed_commits\", \"tolerance\"} against the counted commits (ATTESTATION_SIGNING_KEY; GET /verify-claim/key for the public key)",
+            "/admin/wallets/<github_user>": "Bind Sui wallet addresses to a user; scans then include chain_activity (admin)",
+            "POST /admin/sheets/export": "Write the latest stored scans of {\"usernames\": [...]} and/or {\"label\": ...} to GOOGLE_SHEET_ID, one row per username (admin)",
+            "/admin/labels/<github_user>": "Attach labels such as cohort:lagos-2025 or grantee to a user (admin); /leaderboard?label= and batch {\"label\": ...} select by label"
+        },
+        "example": "/check-sui-developer?username=dotandev"
+    }))
+}
+
+/// Ready once the database answers. Fine-grained server tokens are ready
+/// too, with the capabilities they degrade listed.
+async fn readyz_handler(State(state): State<state::AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let github_token = &state.capabilities;
+    match state.storage.ping() {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({ "status": "ready", "github_token": github_token }))),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({ "status": "unavailable", "storage": e.to_string(), "github_token": github_token })),
+        ),
+    }
+}
+
+/// GitHub quota left on the token the request runs with.
+async fn rate_limit_handler(
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let url = "https://api.github.com/rate_limit";
+    let limits: serde_json::Value = async {
+        let resp = github::send(github::EndpointClass::Repos, github::get(&client, &token, url)).await?;
+        if !resp.status().is_success() {
+            reporting::github_response(url, resp.status());
+            return Err(format!("GitHub rate limit lookup failed with {}", resp.status()).into());
+        }
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>(resp.json().await?)
+    }
+    .await
+    .map_err(|e| upstream_error("rate_limit", e))?;
+
+    let resources: serde_json::Map<String, serde_json::Value> = ["core", "graphql", "search"]
+        .into_iter()
+        .map(|name| {
+            let r = &limits["resources"][name];
+            (name.to_string(), serde_json::json!({ "limit": r["limit"], "remaining": r["remaining"], "reset": r["reset"] }))
+        })
+        .collect();
+    Ok(Json(serde_json::json!({ "token": github::capabilities(&token), "resources": resources })))
+}
+
+#[tracing::instrument(skip_all, fields(username = %params.username))]
+async fn check_sui_developer_handler(
+    locale: i18n::Locale,
+    headers: HeaderMap,
+    Query(params): Query<DeveloperQuery>,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+    State(storage): State<storage::Storage>,
+    State(runtime): State<Arc<state::Runtime>>,
+) -> Result<Response, (StatusCode, String)> {
+    let username = &params.username;
+    let usernames = scan::parse_aliases(username).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let limits = scan::ScanLimits::requested(params.max_repos, params.max_tree_entries, params.max_commit_pages);
+    let policy = runtime
+        .policies
+        .select(params.policy.as_deref())
+        .map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let ecosystem =
+        ecosystems::select(params.ecosystem.as_deref(), params.mode).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let min_freshness = parse_min_freshness(params.min_freshness.as_deref()).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+
+    // Builds run untrusted build scripts and archives are published, so both
+    // need the deployment's opt-in and a reviewer or admin token.
+    for (requested, enabled, param, env) in [
+        (params.verify_build, verify::enabled(), "verify_build", "VERIFY_BUILD_ENABLED"),
+        (params.archive, archive::enabled(), "archive", "ARCHIVE_ENABLED"),
+    ] {
+        if requested && !enabled {
+            let message = i18n::Message::new("analysis_disabled").arg("param", param).arg("env", env);
+            return Err((StatusCode::FORBIDDEN, locale.render(&message)));
+        }
+    }
+    if params.verify_build || params.archive {
+        admin::require_reviewer(&headers)?;
+    }
+
+    // Serve-only deployments answer every check from the stored result,
+    // including `require_org`, which is checked against the stored scan's
+    // Sui organizations since GitHub cannot be asked.
+    if readonly::enabled() {
+        return match readonly::stored_scan(&storage, &usernames) {
+            Ok(Some(mut stored)) => {
+                if let Some(required) = params.require_org.as_deref()
+                    && !stored.sui_organizations.iter().any(|o| o.eq_ignore_ascii_case(required))
+                {
+                    let message = i18n::Message::new("not_org_member").arg("username", username).arg("org", required);
+                    return Err((StatusCode::FORBIDDEN, locale.render(&message)));
+                }
+                attach_verdict_for(&storage, &mut stored, policy, ecosystem);
+                Ok(Json(stored).into_response())
+            }
+            Ok(None) => {
+                let message = i18n::Message::new("not_stored").arg("username", username);
+                Err((StatusCode::NOT_FOUND, locale.render(&message)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        };
+    }
+
+    // Fetched once: the scan reuses the memberships instead of asking again.
+    let organizations = match params.require_org.as_deref() {
+        Some(required) => {
+            let organizations = scan::fetch_organizations(&client, &token, &usernames)
+                .await
+                .map_err(|e| upstream_error(username, e))?;
+
+            if !organizations.iter().any(|o| o.eq_ignore_ascii_case(required)) {
+                let message = i18n::Message::new("not_org_member").arg("username", username).arg("org", required);
+                return Err((StatusCode::FORBIDDEN, locale.render(&message)));
+            }
+            Some(organizations)
+        }
+        None => None,
+    };
+
+    let options = scan::ScanOptions {
+        exclude_merges: params.exclude_merges,
+        count_merged_prs: params.count_merged_prs,
+        commit_hygiene: params.commit_hygiene,
+        strategy: params.strategy,
+        ..scan::ScanOptions::new(params.mode, limits)
+    };
+    let analyses = Analyses {
+        similarity: params.similarity,
+        verify_build: params.verify_build,
+        archive: params.archive,
+    };
+
+    // Plain scans are answered from the stored result while it is fresh enough.
+    let cacheable = !params.estimate && !params.debug && options.is_plain() && analyses == Analyses::default();
+    if cacheable && let Some(max_age) = min_freshness {
+        match cache::lookup_fresh(&storage, &usernames, params.mode, limits, max_age) {
+            Ok(Some(mut cached)) => {
+                attach_verdict_for(&storage, &mut cached, policy, ecosystem);
+                return Ok(Json(cached).into_response());
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Scan cache lookup failed for {username}: {e}"),
+        }
+        // Too old: `Prefer: respond-async` refreshes in the background, to be
+        // picked up by repeating the request; otherwise the scan below blocks.
+        if prefers_async(&headers) {
+            spawn_refresh(client, token, storage, usernames.clone(), options);
+            let body = serde_json::json!({ "username": username, "status": "refreshing" });
+            return Ok((StatusCode::ACCEPTED, Json(body)).into_response());
+        }
+    } else if cacheable {
+        let max_stale = params.max_stale.unwrap_or_else(cache::default_max_stale_secs);
+        match cache::lookup(&storage, &usernames, params.mode, limits, max_stale) {
+            Ok(Some(mut cached)) => {
+                attach_verdict_for(&storage, &mut cached, policy, ecosystem);
+                if cached.stale {
+                    spawn_refresh(client, token, storage, usernames, options);
+                }
+                return Ok(Json(cached).into_response());
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Scan cache lookup failed for {username}: {e}"),
+        }
+    }
+
+    let result = if params.estimate {
+        scan::estimate_scan(&client, &token, &usernames, limits).await.map(|e| Json(e).into_response())
+    } else {
+        let scan = github::counting_requests(async {
+            let scanned = match organizations {
+                Some(organizations) => {
+                    scan::get_user_move_repos_with_organizations(&client, &token, &usernames, options, organizations).await
+                }
+                None => scan::get_user_move_repos(&client, &token, &usernames, options).await,
+            };
+            match scanned {
+                Ok(mut r) => {
+                    if !params.debug {
+                        r.diagnostics = None;
+                    }
+                    post_process_scan(&client, &token, &storage, &mut r, analyses).await;
+                    attach_verdict_for(&storage, &mut r, policy, ecosystem);
+                    Ok(Json(r).into_response())
+                }
+                Err(e) => Err(e),
+            }
+        });
+        queue::run(queue::Lane::Interactive, scan).await
+    };
+
+    match result {
+        Ok(response) => Ok(response),
+        Err(e) => Err(upstream_error(username, e)),
+    }
+}
+
+/// Seconds of a `min_freshness` parameter, if given.
+fn parse_min_freshness(value: Option<&str>) -> Result<Option<u64>, i18n::Message> {
+    value
+        .map(|v| cache::parse_duration_secs(v).ok_or_else(|| i18n::Message::new("freshness_invalid").arg("value", v)))
+        .transpose()
+}
+
+/// Whether the client sent `Prefer: respond-async` (RFC 7240).
+fn prefers_async(headers: &HeaderMap) -> bool {
+    headers
+        .get_all("prefer")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|preference| preference.trim().eq_ignore_ascii_case("respond-async"))
+}
+
+/// Optional analyses requested alongside a scan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Analyses {
+    similarity: bool,
+    verify_build: bool,
+    archive: bool,
+}
+
+/// Scans a cohort of users one after another with plain scans. Users recently
+/// confirmed to have no Move code are skipped unless `force` is set, and
+/// fresh cached results are reused. With `Accept: application/x-ndjson` each
+/// entry is streamed as one JSON line as soon as it is ready, and larger
+/// cohorts are accepted.
+#[tracing::instrument(skip_all, fields(users = body.usernames.len()))]
+async fn check_sui_developers_handler(
+    locale: i18n::Locale,
+    headers: HeaderMap,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+    State(storage): State<storage::Storage>,
+    State(runtime): State<Arc<state::Runtime>>,
+    Json(body): Json<BatchRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    let streamed = headers
+        .get(ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains(NDJSON));
+    let policy = runtime
+        .policies
+        .select(body.policy.as_deref())
+        .map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?
+        .clone();
+    let min_freshness = parse_min_freshness(body.min_freshness.as_deref()).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let window = body.window.as_ref().map(window::EventWindow::parse).transpose().map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    let ecosystem = ecosystems::select(body.ecosystem.as_deref(), body.mode).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+
+    let labelled = match body.label.as_deref().map(str::trim) {
+        Some(label) => storage.labelled_users(label).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        None => Vec::new(),
+    };
+    let max_users = if streamed { MAX_STREAMED_BATCH_USERS } else { MAX_BATCH_USERS };
+    if body.usernames.len() + labelled.len() > max_users {
+        let message = i18n::Message::new("batch_too_large").arg("max", max_users);
+        return Err((StatusCode::BAD_REQUEST, locale.render(&message)));
+    }
+
+    let mut usernames: Vec<String> = Vec::new();
+    for username in body.usernames.iter().chain(&labelled) {
+        let username = username.trim();
+        if !username.is_empty() && !usernames.iter().any(|u| u.eq_ignore_ascii_case(username)) {
+            usernames.push(username.to_string());
+        }
+    }
+
+    let batch = Batch { client, token, storage, policy, ecosystem, mode: body.mode, force: body.force, min_freshness, window };
+    if streamed {
+        // The body outlives this handler, so it carries the runtime and the
+        // request's scan scope along: cancelling or disconnecting still
+        // stops the batch.
+        let scope = queue::current_scope();
+        let stop = scope.clone();
+        let lines = futures_util::stream::iter(usernames)
+            .take_while(move |_| std::future::ready(!stop.as_ref().is_some_and(|s| s.is_cancelled())))
+            .then(move |username| {
+                let batch = batch.clone();
+                let scope = scope.clone();
+                // Boxed: a scan future is too large to move around the stream inline.
+                state::scope(runtime.clone(), Box::pin(async move {
+                    match scope {
+                        Some(scope) => scope.run(batch.entry(username)).await,
+                        None => batch.entry(username).await,
+                    }
+                }))
+            })
+            .map(|entry| {
+                serde_json::to_vec(&entry).map(|mut line| {
+                    line.push(b'\n');
+                    line
+                })
+            });
+        return Ok(([(CONTENT_TYPE, NDJSON)], axum::body::Body::from_stream(lines)).into_response());
+    }
+
+    let mut entries = Vec::new();
+    for username in usernames {
+        if queue::is_cancelled() {
+            break;
+        }
+        entries.push(batch.entry(username).await);
+    }
+    Ok(Json(entries).into_response())
+}
+
+/// Media type of a streamed batch: one [`BatchEntry`] per line.
+const NDJSON: &str = "application/x-ndjson";
+
+/// Users accepted per streamed batch request.
+const MAX_STREAMED_BATCH_USERS: usize = 500;
+
+/// Everything one batch entry needs, cloned into a streamed response.
+#[derive(Clone)]
+struct Batch {
+    client: Client,
+    token: String,
+    storage: storage::Storage,
+    policy: policy::Policy,
+    ecosystem: Option<ecosystems::MoveEcosystem>,
+    mode: scan::ScanMode,
+    force: bool,
+    min_freshness: Option<u64>,
+    window: Option<window::Window>,
+}
+
+impl Batch {
+    async fn entry(&self, username: String) -> BatchEntry {
+        let mut entry = self.scan_entry(username).await;
+        if let (Some(window), Some(result)) = (&self.window, &entry.result)
+            && result.has_move_files
+        {
+            match queue::run(queue::Lane::Batch, window::measure(&self.client, &self.token, result, window)).await {
+                Ok(activity) => entry.window = Some(activity),
+                Err(e) => entry.window_error = Some(e.to_string()),
+            }
+        }
+        entry
+    }
+
+    async fn scan_entry(&self, username: String) -> BatchEntry {
+        let Batch { client, token, storage, policy, ecosystem, mode, force, min_freshness, .. } = self;
+        let limits = scan::ScanLimits::ceiling();
+        let usernames = std::slice::from_ref(&username);
+
+        if readonly::enabled() {
+            return match readonly::stored_scan(storage, usernames) {
+                Ok(Some(mut stored)) => {
+                    attach_verdict_for(storage, &mut stored, policy, *ecosystem);
+                    BatchEntry { username, status: BatchStatus::Cached, result: Some(stored), error: None, error_kind: None, window: None, window_error: None }
+                }
+                Ok(None) => BatchEntry { username, status: BatchStatus::NotStored, result: None, error: None, error_kind: None, window: None, window_error: None },
+                Err(e) => BatchEntry {
+                    username,
+                    status: BatchStatus::Failed,
+                    result: None,
+                    error: Some(e.to_string()),
+                    error_kind: None,
+                    window: None,
+                    window_error: None,
+                },
+            };
+        }
+        if !force {
+            if storage.is_known_non_developer(&username, cache::non_developer_skip_secs()).unwrap_or(false) {
+                return BatchEntry { username, status: BatchStatus::KnownNonDeveloper, result: None, error: None, error_kind: None, window: None, window_error: None };
+            }
+            let cached = match min_freshness {
+                Some(max_age) => cache::lookup_fresh(storage, usernames, *mode, limits, *max_age),
+                None => cache::lookup(storage, usernames, *mode, limits, 0),
+            };
+            if let Ok(Some(mut cached)) = cached {
+                attach_verdict_for(storage, &mut cached, policy, *ecosystem);
+                return BatchEntry { username, status: BatchStatus::Cached, result: Some(cached), error: None, error_kind: None, window: None, window_error: None };
+            }
+        }
+
+        // Post-processing calls GitHub too, so it runs in the scan's batch slot.
+        let scan = async {
+            let mut result = scan::get_user_move_repos(client, token, usernames, scan::ScanOptions::new(*mode, limits)).await?;
+            result.diagnostics = None;
+            post_process_scan(client, token, storage, &mut result, Analyses::default()).await;
+            Ok(result)
+        };
+        match queue::run(queue::Lane::Batch, scan).await {
+            Ok(mut result) => {
+                attach_verdict_for(storage, &mut result, policy, *ecosystem);
+                BatchEntry { username, status: BatchStatus::Scanned, result: Some(result), error: None, error_kind: None, window: None, window_error: None }
+            }
+            Err(e) => {
+                if !e.is::<queue::Cancelled>() {
+                    reporting::scan_failure(&username, e.as_ref());
+                }
+                BatchEntry {
+                    username,
+                    status: BatchStatus::Failed,
+                    result: None,
+                    error: Some(e.to_string()),
+                    error_kind: Some(reporting::error_kind(e.as_ref())),
+                    window: None,
+                    window_error: None,
+                }
+            }
+        }
+    }
+}
+
+/// Scans a client-supplied list of repositories instead of the user's own,
+/// e.g. the repositories an applicant reports. The result is never stored
+/// or certified, since it does not cover the whole account.
+#[tracing::instrument(skip_all, fields(username = %body.username, repos = body.repos.len()))]
+async fn check_repos_handler(
+    locale: i18n::Locale,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+    State(storage): State<storage::Storage>,
+    State(runtime): State<Arc<state::Runtime>>,
+    Json(body): Json<CheckReposRequest>,
+) -> Result<Json<CheckReposResponse>, (StatusCode, String)> {
+    let usernames = scan::parse_aliases(&body.username).map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+    if body.repos.len() > MAX_CHECK_REPOS {
+        let message = i18n::Message::new("too_many_repos").arg("max", MAX_CHECK_REPOS);
+        return Err((StatusCode::BAD_REQUEST, locale.render(&message)));
+    }
+    let policy = runtime
+        .policies
+        .select(body.policy.as_deref())
+        .map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+
+    let mut names: Vec<String> = Vec::new();
+    for url in &body.repos {
+        let Some(name) = github::parse_repo_url(url) else {
+            let message = i18n::Message::new("invalid_repo_url").arg("url", url);
+            return Err((StatusCode::BAD_REQUEST, locale.render(&message)));
+        };
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            names.push(name);
+        }
+    }
+
+    let options = scan::ScanOptions::new(body.mode, scan::ScanLimits::ceiling());
+    let scan = async {
+        let (repositories, repos_not_found) = scan::fetch_listed_repositories(&client, &token, &names).await?;
+        let result = scan::scan_listed_repos(&client, &token, &usernames, options, repositories).await?;
+        Ok::<_, Box<dyn std::error::Error + Send + Sync>>((result, repos_not_found))
+    };
+
+    match queue::run(queue::Lane::Interactive, scan).await {
+        Ok((mut result, repos_not_found)) => {
+            result.diagnostics = None;
+            result.repo_cursors.clear();
+            if let Err(e) = templates::flag_template_copies(&storage, &mut result) {
+                tracing::warn!("Template matching failed for {}: {e}", result.username);
+            }
+            result.verdict = Some(policy.evaluate(&result, storage::now_secs()));
+            Ok(Json(CheckReposResponse { result, repos_not_found }))
+        }
+        Err(e) => Err(upstream_error(&body.username, e)),
+    }
+}
+
+/// Analysis layered on a finished scan: template flagging, optional
+/// similarity matching, chain activity, build verification and archival,
+/// then persistence. Failures here are logged but never
+/// fail the request.
+async fn post_process_scan(
+    client: &Client,
+    token: &str,
+    storage: &storage::Storage,
+    result: &mut scan::UserMoveFilesResponse,
+    analyses: Analyses,
+) {
+    let stage = scan::Checkpoint::now();
+    if let Err(e) = templates::flag_template_copies(storage, result) {
+        tracing::warn!("Template matching failed for {}: {e}", result.username);
+    }
+    result.record_stage("template_matching", &stage);
+
+    let stage = scan::Checkpoint::now();
+    if result.mode != scan::ScanMode::Quick {
+        if let Err(e) = edition::annotate(client, token, storage, result).await {
+            tracing::warn!("Move edition detection failed for {}: {e}", result.username);
+        }
+        result.record_stage("move_edition", &stage);
+    }
+
+    let stage = scan::Checkpoint::now();
+    if analyses.similarity
+        && result.mode != scan::ScanMode::Quick
+        && let Err(e) = similarity::analyze(client, token, storage, result).await
+    {
+        tracing::warn!("Similarity analysis failed for {}: {e}", result.username);
+    }
+    if analyses.similarity {
+        result.record_stage("similarity", &stage);
+    }
+
+    let stage = scan::Checkpoint::now();
+    if let Err(e) = attach_chain_activity(client, storage, result).await {
+        tracing::warn!("Chain activity lookup failed for {}: {e}", result.username);
+    }
+    if result.chain_activity.is_some() {
+        result.record_stage("chain_activity", &stage);
+    }
+
+    let stage = scan::Checkpoint::now();
+    if analyses.verify_build {
+        if let Err(e) = verify::verify_builds(client, token, result).await {
+            tracing::warn!("Build verification failed for {}: {e}", result.username);
+        }
+        result.record_stage("verify_build", &stage);
+    }
+
+    let stage = scan::Checkpoint::now();
+    if analyses.archive {
+        match archive::archive_report(client, result).await {
+            Ok(receipt) => result.archive = Some(receipt),
+            Err(e) => tracing::warn!("Archiving report failed for {}: {e}", result.username),
+        }
+        result.record_stage("archive", &stage);
+    }
+
+    cache::mark_negative(result, storage::now_secs());
+
+    // Quick scans skip commit counting, so only complete results are kept;
+    // negative quick results are complete and cached like any other.
+    if result.mode != scan::ScanMode::Quick || !result.has_move_files {
+        if let Err(e) = storage.save_scan(result) {
+            tracing::warn!("Failed to store scan for {}: {e}", result.username);
+        } else if let Err(e) = storage.set_repo_cursors(&result.username, &result.repo_cursors) {
+            tracing::warn!("Failed to store repository cursors for {}: {e}", result.username);
+        }
+    }
+
+    let non_developer = !result.has_move_files && result.aliases.is_empty();
+    if let Err(e) = storage.set_non_developer(&result.username, non_developer) {
+        tracing::warn!("Failed to update non-developer list for {}: {e}", result.username);
+    }
+}
+
+/// Maps a failed GitHub call to a response: 403 naming the missing scope and
+/// the refused endpoint when the token lacks one, 409 for a cancelled scan,
+/// 503 in serve-only mode, else 502.
+fn upstream_error(subject: &str, e: Box<dyn std::error::Error + Send + Sync>) -> (StatusCode, String) {
+    if e.is::<queue::Cancelled>() {
+        return (StatusCode::CONFLICT, e.to_string());
+    }
+    if e.is::<readonly::ServeOnly>() {
+        return (StatusCode::SERVICE_UNAVAILABLE, e.to_string());
+    }
+    if let Some(scope) = e.downcast_ref::<github::ScopeError>() {
+        tracing::warn!("Request for {subject} refused: {scope}");
+        let hint = format!("grant it to the server token or send a token that has it in {}", github::CLIENT_TOKEN_HEADER);
+        return (StatusCode::FORBIDDEN, format!("{scope}; {hint}"));
+    }
+    reporting::scan_failure(subject, e.as_ref());
+    (StatusCode::BAD_GATEWAY, e.to_string())
+}
+
+/// Evaluates the selected verdict policy and certifies a pass. Runs after the
+/// scan is stored so every policy applies to cached results too.
+fn attach_verdict(storage: &storage::Storage, result: &mut scan::UserMoveFilesResponse, policy: &policy::Policy) {
+    attach_verdict_for(storage, result, policy, None);
+}
+
+/// [`attach_verdict`] with the result first narrowed to `ecosystem`, if
+/// given. Certificates vouch for Sui developers, so only Sui verdicts are
+/// certified.
+fn attach_verdict_for(
+    storage: &storage::Storage,
+    result: &mut scan::UserMoveFilesResponse,
+    policy: &policy::Policy,
+    ecosystem: Option<ecosystems::MoveEcosystem>,
+) {
+    if let Err(e) = annotations::apply(storage, result) {
+        tracing::warn!("Failed to load annotations of {}: {e}", result.username);
+    }
+    let now = storage::now_secs();
+    ecosystems::classify(result, policy, now);
+    if let Some(ecosystem) = ecosystem {
+        ecosystems::restrict(result, ecosystem);
+    }
+    result.verdict = Some(policy.evaluate(result, now));
+    if ecosystem.is_some_and(|e| e != ecosystems::MoveEcosystem::Sui) {
+        return;
+    }
+    if let Err(e) = certificates::certify(storage, result) {
+        tracing::warn!("Failed to certify {}: {e}", result.username);
+    }
+}
+
+/// Re-runs a plain scan in the background so the next request gets a fresh
+/// cached result. Does nothing if a refresh of the user is already running.
+fn spawn_refresh(
+    client: Client,
+    token: String,
+    storage: storage::Storage,
+    usernames: Vec<String>,
+    options: scan::ScanOptions,
+) {
+    let Some(guard) = cache::begin_refresh(&usernames[0]) else {
+        return;
+    };
+
+    state::spawn(async move {
+        let _guard = guard;
+        refresh_scan(&client, &token, &storage, &usernames, options).await;
+    });
+}
+
+/// Runs a plain scan and stores it, logging failures.
+async fn refresh_scan(
+    client: &Client,
+    token: &str,
+    storage: &storage::Storage,
+    usernames: &[String],
+    options: scan::ScanOptions,
+) {
+    // Refreshes build on the stored scan, re-checking only what changed.
+    let previous = cache::snapshot(storage, usernames, options).unwrap_or_else(|e| {
+        tracing::warn!("Could not load previous scan of {}: {e}", usernames[0]);
+        None
+    });
+
+    let scan = async {
+        let mut result = scan::rescan_user_move_repos(client, token, usernames, options, previous).await?;
+        result.diagnostics = None;
+        post_process_scan(client, token, storage, &mut result, Analyses::default()).await;
+        Ok(())
+    };
+    if let Err(e) = queue::run(queue::Lane::Batch, scan).await {
+        tracing::warn!("Background refresh failed for {}: {e}", usernames[0]);
+    }
+}
+
+/// Accounts listed in `PRELOAD_USERS` (comma-separated).
+fn preload_users() -> Vec<String> {
+    std::env::var("PRELOAD_USERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .collect()
+}
+
+/// Scans every `PRELOAD_USERS` account lacking a fresh cached result, one at
+/// a time in the background, so demo and leaderboard requests start warm.
+pub fn spawn_preload(client: Client, token: String, storage: storage::Storage) {
+    let users = preload_users();
+    if users.is_empty() {
+        return;
+    }
+
+    state::spawn(async move {
+        let options = scan::ScanOptions::new(scan::ScanMode::Full, scan::ScanLimits::ceiling());
+        tracing::info!("Preloading {} users", users.len());
+
+        for username in users {
+            let usernames = [username];
+            if let Ok(Some(cached)) = cache::lookup(&storage, &usernames, options.mode, options.limits, 0)
+                && !cached.stale
+            {
+                continue;
+            }
+            let Some(_guard) = cache::begin_refresh(&usernames[0]) else {
+                continue;
+            };
+            refresh_scan(&client, &token, &storage, &usernames, options).await;
+            pacing::pause().await;
+        }
+    });
+}
+
+/// Fills `chain_activity` when any scanned account has bound wallet addresses.
+async fn attach_chain_activity(
+    client: &Client,
+    storage: &storage::Storage,
+    result: &mut scan::UserMoveFilesResponse,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut usernames = vec![result.username.clone()];
+    usernames.extend(result.aliases.iter().cloned());
+
+    let addresses = storage.wallets(&usernames)?;
+    if !addresses.is_empty() {
+        result.chain_activity = Some(chain::chain_activity(client, &addresses).await?);
+    }
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+async fn resolve_email_handler(
+    locale: i18n::Locale,
+    Query(params): Query<ResolveEmailQuery>,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+) -> Result<Json<resolve::EmailResolution>, (StatusCode, String)> {
+    let email = params.email.trim();
+    if !email.contains('@') {
+        return Err((StatusCode::BAD_REQUEST, locale.render(&i18n::Message::new("invalid_email"))));
+    }
+
+    resolve::resolve_email(&client, &token, email)
+        .await
+        .map(Json)
+        .map_err(|e| upstream_error(email, e))
+}
+
+async fn ecosystem_graph_handler(
+    Query(params): Query<EcosystemGraphQuery>,
+    State(storage): State<storage::Storage>,
+) -> Result<Json<ecosystem::EcosystemGraph>, (StatusCode, String)> {
+    let min_commits = params.min_commits.max(1);
+    let scans = storage
+        .blocking(move |storage| storage.discoverable_developers(min_commits))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(ecosystem::build_graph(&scans, params.min_commits)))
+}
+
+#[tracing::instrument(skip_all, fields(username = %username))]
+async fn profile_handler(
+    locale: i18n::Locale,
+    Path(username): Path<String>,
+    Query(params): Query<ProfileQuery>,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+    State(storage): State<storage::Storage>,
+) -> Result<Response, (StatusCode, String)> {
+    if !github::is_valid_login(&username) {
+        let message = i18n::Message::new("username_invalid").arg("username", &username);
+        return Err((StatusCode::BAD_REQUEST, locale.render(&message)));
+    }
+    let profile = match profile::build_profile(&client, &token, &storage, &username).await {
+        Ok(Some(profile)) => profile,
+        Ok(None) => {
+            let message = i18n::Message::new("user_not_found").arg("username", &username);
+            return Err((StatusCode::NOT_FOUND, locale.render(&message)));
+        }
+        Err(e) => return Err(upstream_error(&username, e)),
+    };
+
+    match params.format {
+        ProfileFormat::Json => Ok(Json(profile).into_response()),
+        ProfileFormat::Jsonld => {
+            let now = storage::now_secs();
+            let certificate = storage
+                .certificates(&profile.username)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .into_iter()
+                .find(|c| c.is_valid(now));
+            let body = Json(profile.to_json_ld(certificate.as_ref()));
+            Ok(([(CONTENT_TYPE, "application/ld+json")], body).into_response())
+        }
+    }
+}
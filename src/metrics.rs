@@ -0,0 +1,47 @@
+use axum::http::{StatusCode, header::CONTENT_TYPE};
+use axum::response::IntoResponse;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, Registry, TextEncoder};
+use std::{sync::OnceLock, time::Duration};
+
+// ------------------- Registry -------------------
+
+/// Latency buckets in seconds, from cached GraphQL answers to slow searches.
+const LATENCY_BUCKETS: &[f64] = &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+struct Metrics {
+    registry: Registry,
+    github_latency: HistogramVec,
+}
+
+fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| {
+        let registry = Registry::new();
+        let github_latency = HistogramVec::new(
+            HistogramOpts::new("github_request_duration_seconds", "GitHub API request latency by endpoint class and status")
+                .buckets(LATENCY_BUCKETS.to_vec()),
+            &["class", "status"],
+        )
+        .expect("valid histogram");
+        registry.register(Box::new(github_latency.clone())).expect("metric registered once");
+        Metrics { registry, github_latency }
+    })
+}
+
+/// Records one GitHub call; `status` is the HTTP status code, or `error`
+/// when no response arrived.
+pub fn observe_github(class: &str, status: &str, elapsed: Duration) {
+    metrics().github_latency.with_label_values(&[class, status]).observe(elapsed.as_secs_f64());
+}
+
+// ------------------- Handler -------------------
+
+/// `GET /metrics` in the Prometheus text exposition format.
+pub async fn metrics_handler() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    match encoder.encode(&metrics().registry.gather(), &mut body) {
+        Ok(()) => (StatusCode::OK, [(CONTENT_TYPE, encoder.format_type().to_string())], body),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, [(CONTENT_TYPE, "text/plain".to_string())], e.to_string().into_bytes()),
+    }
+}
@@ -0,0 +1,177 @@
+use std::{path::PathBuf, time::Duration};
+use tempfile::TempDir;
+use tokio::process::Command;
+use tracing::Instrument;
+
+use crate::{
+    authorship, reporting,
+    scan::{OwnedRepository, TreeEntry, env_or},
+    verify::{self, RunError},
+};
+
+// ------------------- Local Mirrors -------------------
+
+/// Largest repository (GitHub's `diskUsage`, in KB) that will be cloned.
+const DEFAULT_MAX_REPO_KB: u64 = 200 * 1024;
+
+/// Wall-clock limit for the clone and for each git command run on it.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Commits per page of the commits API, for applying `max_commit_pages`.
+const COMMITS_PER_PAGE: usize = 100;
+
+/// A blobless clone of one repository's default branch, removed with its
+/// temporary directory on drop. History and trees are local, so listing
+/// files and commits costs no REST quota; file contents are only fetched
+/// from the git remote when blamed.
+///
+/// The clone is blobless rather than shallow because commit counts and
+/// `move_since` need the full history. It runs the `git` CLI, with the same
+/// scrubbed environment as build verification, because libgit2 (`git2`)
+/// cannot make partial clones.
+pub struct Mirror {
+    _workdir: TempDir,
+    git_dir: PathBuf,
+    timeout: Duration,
+}
+
+/// Clones `repo` without a checkout. `Err` carries the reason the caller
+/// should fall back to the API: too large, or the clone failed.
+pub async fn clone(repo: &OwnedRepository) -> Result<Mirror, String> {
+    let max_kb = env_or("CLONE_MAX_REPO_KB", DEFAULT_MAX_REPO_KB);
+    if let Some(size_kb) = repo.disk_usage_kb.filter(|kb| *kb > max_kb) {
+        return Err(format!("repository is {size_kb} KB, above the {max_kb} KB limit"));
+    }
+
+    let timeout = Duration::from_secs(env_or("CLONE_TIMEOUT_SECS", DEFAULT_TIMEOUT_SECS));
+    let workdir = tempfile::tempdir().map_err(|e| e.to_string())?;
+    let git_dir = workdir.path().join("repo");
+
+    let clone_url = format!("https://github.com/{}.git", repo.name);
+    let mut clone = verify::scrubbed(Command::new("git"), workdir.path());
+    clone
+        .args(["clone", "--bare", "--filter=blob:none", "--single-branch", "--no-tags", "--quiet", "--branch"])
+        .arg(&repo.default_branch)
+        .arg(&clone_url)
+        .arg(&git_dir);
+
+    verify::run(clone, timeout)
+        .instrument(tracing::info_span!("git.clone", repo = %reporting::repo_label(&repo.name)))
+        .await
+        .map_err(|e| format!("clone failed: {e}"))?;
+
+    Ok(Mirror { _workdir: workdir, git_dir, timeout })
+}
+
+impl Mirror {
+    async fn git(&self, args: &[&str]) -> Result<String, RunError> {
+        let mut command = verify::scrubbed(Command::new("git"), &self.git_dir);
+        command.env("TZ", "UTC").args(args);
+        verify::run_output(command, self.timeout).await
+    }
+
+    /// Up to `max_entries` files at `HEAD`, as the Git Trees API lists them.
+    pub async fn tree(&self, max_entries: usize) -> Result<Vec<TreeEntry>, Box<dyn std::error::Error + Send + Sync>> {
+        let listing = self.git(&["ls-tree", "-r", "--full-tree", "HEAD"]).await.map_err(|e| e.to_string())?;
+        Ok(listing
+            .lines()
+            .filter_map(|line| {
+                let (meta, path) = line.split_once('\t')?;
+                let sha = meta.split_whitespace().nth(2)?;
+                Some(TreeEntry { path: path.to_string(), sha: sha.to_string() })
+            })
+            .take(max_entries)
+            .collect())
+    }
+
+    /// Commits on the default branch authored by one of `usernames`, newest
+    /// first and shaped like commits API objects so the bot, merge and
+    /// confidence rules apply unchanged. Local history has no account links,
+    /// so authors are matched by email and name.
+    pub async fn commits(
+        &self,
+        usernames: &[String],
+        max_pages: u32,
+        paths: &[&str],
+    ) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+        let format = "--format=%H%x1f%P%x1f%an%x1f%ae%x1f%ad%x1f%cn%x1f%ce%x1f%cd%x1f%s%x1e";
+        let mut args = vec!["log", format, "--date=format-local:%Y-%m-%dT%H:%M:%SZ", "HEAD", "--"];
+        args.extend_from_slice(paths);
+        let log = self.git(&args).await.map_err(|e| e.to_string())?;
+
+        Ok(log
+            .split('\x1e')
+            .filter_map(|record| {
+                let fields: Vec<&str> = record.trim_start_matches('\n').split('\x1f').collect();
+                let [sha, parents, author_name, author_email, author_date, committer_name, committer_email, committer_date, subject] =
+                    fields[..]
+                else {
+                    return None;
+                };
+                if !authorship::identity_matches(author_name, author_email, usernames) {
+                    return None;
+                }
+
+                // Noreply addresses are how GitHub links commits to an account.
+                let login = author_email
+                    .strip_suffix("@users.noreply.github.com")
+                    .map(|local| local.split_once('+').map_or(local, |(_, login)| login))
+                    .filter(|login| usernames.iter().any(|u| u.eq_ignore_ascii_case(login)));
+                let committer_login = (committer_email == "noreply@github.com").then_some("web-flow");
+
+                Some(serde_json::json!({
+                    "sha": sha,
+                    "parents": parents.split_whitespace().map(|p| serde_json::json!({ "sha": p })).collect::<Vec<_>>(),
+                    "author": { "login": login },
+                    "committer": { "login": committer_login },
+                    "commit": {
+                        "message": subject,
+                        "author": { "name": author_name, "email": author_email, "date": author_date },
+                        "committer": { "name": committer_name, "email": committer_email, "date": committer_date },
+                    },
+                }))
+            })
+            .take(max_pages as usize * COMMITS_PER_PAGE)
+            .collect())
+    }
+
+    /// Date of the earliest matching commit touching a `.move` file.
+    pub async fn move_since(&self, usernames: &[String]) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let commits = self.commits(usernames, u32::MAX, &["*.move"]).await?;
+        Ok(commits.iter().filter_map(|c| c["commit"]["author"]["date"].as_str()).min().map(String::from))
+    }
+
+    /// Contents of `path` at `HEAD`, or `None` when git cannot read it. The
+    /// blob is fetched from the remote on first read.
+    pub async fn read(&self, path: &str) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.git(&["cat-file", "blob", &format!("HEAD:{path}")]).await {
+            Ok(content) => Ok(Some(content)),
+            Err(RunError::Failed(_)) => Ok(None),
+            Err(e) => Err(e.to_string().into()),
+        }
+    }
+
+    /// Lines of `path` at `HEAD` last changed by one of `usernames`.
+    pub async fn authored_lines(&self, path: &str, usernames: &[String]) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+        let blame = self
+            .git(&["blame", "--line-porcelain", "HEAD", "--", path])
+            .instrument(tracing::info_span!("git.blame", path = %path))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Each line's header lists `author` before `author-mail`.
+        let mut author = "";
+        let mut lines = 0;
+        for line in blame.lines() {
+            if let Some(name) = line.strip_prefix("author ") {
+                author = name;
+            } else if let Some(mail) = line.strip_prefix("author-mail ") {
+                let email = mail.trim_start_matches('<').trim_end_matches('>');
+                if authorship::identity_matches(author, email, usernames) {
+                    lines += 1;
+                }
+            }
+        }
+        Ok(lines)
+    }
+}
@@ -0,0 +1,229 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, future::Future, pin::Pin, time::Duration};
+
+use crate::storage;
+
+// ------------------- Events -------------------
+
+/// Something the service reports to the configured channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A user passed a policy and was issued a new certificate.
+    DeveloperVerified,
+    /// A scan failed on GitHub or inside the service.
+    ScanFailed,
+    /// A daily ecosystem rollup was computed.
+    EcosystemRollup,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    pub kind: EventKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// The passed policy, for `developer_verified`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy: Option<String>,
+    /// One line for chat channels and email subjects.
+    pub summary: String,
+    /// Event-specific fields, sent as-is to webhooks.
+    pub details: serde_json::Value,
+    pub at: u64,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, summary: String, details: serde_json::Value) -> Self {
+        Event { kind, username: None, policy: None, summary, details, at: storage::now_secs() }
+    }
+
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    pub fn policy(mut self, policy: &str) -> Self {
+        self.policy = Some(policy.to_string());
+        self
+    }
+}
+
+// ------------------- Notifiers -------------------
+
+pub type NotifyResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = NotifyResult> + Send + 'a>>;
+
+/// A channel events are delivered to. New channels implement this (the
+/// built-in ones are [`ChannelConfig`] variants); the code raising events
+/// only calls [`publish`].
+pub trait Notifier: Send + Sync {
+    fn deliver<'a>(&'a self, client: &'a Client, event: &'a Event) -> NotifyFuture<'a>;
+}
+
+/// Built-in channels, as configured under `channels` in `NOTIFICATIONS_PATH`.
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ChannelConfig {
+    /// A Discord channel webhook.
+    Discord { webhook_url: String },
+    /// A Slack incoming webhook.
+    Slack { webhook_url: String },
+    /// A Telegram chat, through a bot.
+    Telegram { bot_token: String, chat_id: String },
+    /// Email through a transactional mail HTTP API taking
+    /// `{from, to, subject, text}` with a bearer key (Resend and compatibles).
+    Email { api_url: String, api_key: String, from: String, to: Vec<String> },
+    /// The event JSON POSTed to `url`, signed in `X-Signature-256` with
+    /// HMAC-SHA256 of `secret` when one is set.
+    Webhook { url: String, secret: Option<String> },
+}
+
+/// Sends `request` and fails on a non-success status.
+/// Sends a channel request. Errors leave out the URL, which carries the
+/// bot token or webhook secret of most channels.
+async fn send(request: reqwest::RequestBuilder) -> NotifyResult {
+    let resp = request.send().await.map_err(reqwest::Error::without_url)?;
+    if !resp.status().is_success() {
+        return Err(format!("channel answered {}: {}", resp.status(), resp.text().await.unwrap_or_default()).into());
+    }
+    Ok(())
+}
+
+impl Notifier for ChannelConfig {
+    fn deliver<'a>(&'a self, client: &'a Client, event: &'a Event) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let request = match self {
+                ChannelConfig::Discord { webhook_url } => {
+                    client.post(webhook_url).json(&serde_json::json!({ "content": event.summary }))
+                }
+                ChannelConfig::Slack { webhook_url } => client.post(webhook_url).json(&serde_json::json!({ "text": event.summary })),
+                ChannelConfig::Telegram { bot_token, chat_id } => client
+                    .post(format!("https://api.telegram.org/bot{bot_token}/sendMessage"))
+                    .json(&serde_json::json!({ "chat_id": chat_id, "text": event.summary })),
+                ChannelConfig::Email { api_url, api_key, from, to } => {
+                    let text = format!("{}\n\n{}", event.summary, serde_json::to_string_pretty(&event.details)?);
+                    client
+                        .post(api_url)
+                        .bearer_auth(api_key)
+                        .json(&serde_json::json!({ "from": from, "to": to, "subject": event.summary, "text": text }))
+                }
+                ChannelConfig::Webhook { url, secret } => {
+                    let body = serde_json::to_vec(event)?;
+                    let mut request = client.post(url).header("Content-Type", "application/json");
+                    if let Some(secret) = secret {
+                        let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+                        let signature = hex::encode(ring::hmac::sign(&key, &body).as_ref());
+                        request = request.header("X-Signature-256", format!("sha256={signature}"));
+                    }
+                    request.body(body)
+                }
+            };
+            send(request).await
+        })
+    }
+}
+
+// ------------------- Routing -------------------
+
+/// Which channels an event goes to: every route matching its kind, and its
+/// policy when the route names one.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Route {
+    event: EventKind,
+    policy: Option<String>,
+    channels: Vec<String>,
+}
+
+impl Route {
+    fn matches(&self, event: &Event) -> bool {
+        self.event == event.kind && self.policy.as_ref().is_none_or(|p| event.policy.as_ref() == Some(p))
+    }
+}
+
+/// `NOTIFICATIONS_PATH` file layout: channel name -> channel, and routes.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NotificationsFile {
+    channels: BTreeMap<String, ChannelConfig>,
+    #[serde(default)]
+    routes: Vec<Route>,
+}
+
+/// Time allowed for one delivery.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Dispatcher {
+    client: Client,
+    channels: BTreeMap<String, Box<dyn Notifier>>,
+    routes: Vec<Route>,
+}
+
+pub fn load(path: &str) -> Result<Dispatcher, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: NotificationsFile = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    for route in &file.routes {
+        if route.event == EventKind::Unknown {
+            return Err("a route has an unknown event".to_string());
+        }
+        if let Some(missing) = route.channels.iter().find(|c| !file.channels.contains_key(*c)) {
+            return Err(format!("route for {:?} names unknown channel {missing}", route.event));
+        }
+    }
+    let channels = file.channels.into_iter().map(|(name, channel)| (name, Box::new(channel) as Box<dyn Notifier>)).collect();
+    Ok(Dispatcher::new(channels, file.routes))
+}
+
+impl Dispatcher {
+    /// The channels and routes of the JSON file at `NOTIFICATIONS_PATH`, or
+    /// none (events are dropped).
+    pub fn from_env() -> Self {
+        if let Ok(path) = std::env::var("NOTIFICATIONS_PATH") {
+            match load(&path) {
+                Ok(dispatcher) => return dispatcher,
+                Err(e) => tracing::warn!("Ignoring NOTIFICATIONS_PATH={path}: {e}"),
+            }
+        }
+        Dispatcher::new(BTreeMap::new(), Vec::new())
+    }
+
+    fn new(channels: BTreeMap<String, Box<dyn Notifier>>, routes: Vec<Route>) -> Self {
+        let client = Client::builder().timeout(DELIVERY_TIMEOUT).build().unwrap_or_default();
+        Dispatcher { client, channels, routes }
+    }
+
+    /// Names of the channels `event` is routed to, each once.
+    fn targets(&self, event: &Event) -> Vec<&str> {
+        let mut targets: Vec<&str> = Vec::new();
+        for channel in self.routes.iter().filter(|r| r.matches(event)).flat_map(|r| &r.channels) {
+            if !targets.contains(&channel.as_str()) {
+                targets.push(channel);
+            }
+        }
+        targets
+    }
+}
+
+/// Delivers `event` to its routed channels in the background; failures are
+/// logged and never reach the caller.
+pub fn publish(event: Event) {
+    let runtime = crate::state::runtime();
+    let targets: Vec<String> = runtime.notifications.targets(&event).into_iter().map(String::from).collect();
+    if targets.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        let dispatcher = &runtime.notifications;
+        for name in targets {
+            let Some(channel) = dispatcher.channels.get(&name) else {
+                continue;
+            };
+            if let Err(e) = channel.deliver(&dispatcher.client, &event).await {
+                tracing::warn!("Notification {:?} to {name} failed: {e}", event.kind);
+            }
+        }
+    });
+}
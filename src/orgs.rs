@@ -0,0 +1,235 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::Instrument;
+
+use crate::{
+    detect, github, i18n, pacing, queue, reporting,
+    scan::{self, OwnedRepository, ScanLimits},
+    storage::Storage,
+};
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct OrgContributorsQuery {
+    org: String,
+    max_repos: Option<usize>,
+    max_tree_entries: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrgExternalContributors {
+    pub org: String,
+    pub repositories_scanned: usize,
+    /// Repositories the detector pipeline flagged as Sui Move.
+    pub move_repositories: Vec<String>,
+    /// Public members when the token does not belong to the org, else all.
+    pub members_known: usize,
+    /// Non-members with commits to the Move repositories, most commits
+    /// first. Developers who opted out are left out.
+    pub contributors: Vec<ExternalContributor>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExternalContributor {
+    pub login: String,
+    /// Commits on the default branches, as counted by GitHub's contributors API.
+    pub commits: u32,
+    pub repositories: Vec<String>,
+}
+
+// ------------------- Discovery -------------------
+
+/// Contributor pages read per repository (100 contributors each).
+const MAX_CONTRIBUTOR_PAGES: u32 = 5;
+
+/// Up to `max_repos` public non-fork repositories owned by `org`; `None` when
+/// the organization does not exist.
+async fn fetch_org_repositories(
+    client: &Client,
+    token: &str,
+    org: &str,
+    max_repos: usize,
+) -> Result<Option<Vec<OwnedRepository>>, Box<dyn std::error::Error + Send + Sync>> {
+    let query = format!(
+        r#"
+    query($login:String!, $first:Int!, $after:String) {{
+      organization(login:$login) {{
+        repositories(first:$first, after:$after, isFork:false, privacy:PUBLIC, orderBy:{{field:PUSHED_AT, direction:DESC}}) {{
+          nodes {{ {} }}
+          pageInfo {{ hasNextPage endCursor }}
+        }}
+      }}
+    }}
+    "#,
+        scan::REPOSITORY_FIELDS
+    );
+
+    let mut repositories = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let first = (max_repos - repositories.len()).clamp(1, 50);
+        let vars = serde_json::json!({ "login": org, "first": first, "after": after });
+        let data = match github::graphql_request(client, token, &query, Some(vars)).await {
+            Ok(data) if data["organization"].is_null() => return Ok(None),
+            Ok(data) => data,
+            Err(e) if e.to_string().contains("NOT_FOUND") => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let page = &data["organization"]["repositories"];
+        repositories.extend(page["nodes"].as_array().into_iter().flatten().map(scan::owned_repository));
+        after = page["pageInfo"]["endCursor"].as_str().map(String::from);
+        if !page["pageInfo"]["hasNextPage"].as_bool().unwrap_or(false) || repositories.len() >= max_repos {
+            break;
+        }
+        pacing::pause().await;
+    }
+
+    repositories.truncate(max_repos);
+    Ok(Some(repositories))
+}
+
+/// Lowercased logins of the org's members visible to `token`.
+async fn fetch_members(client: &Client, token: &str, org: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut members = Vec::new();
+    for page in 1.. {
+        let url = format!("https://api.github.com/orgs/{org}/members?per_page=100&page={page}");
+        let resp = github::send(github::EndpointClass::Repos, github::get(client, token, &url)).await?;
+        if !resp.status().is_success() {
+            reporting::github_response(&url, resp.status());
+            break;
+        }
+        let logins: Vec<serde_json::Value> = resp.json().await?;
+        members.extend(logins.iter().filter_map(|m| m["login"].as_str()).map(str::to_lowercase));
+        if logins.len() < 100 {
+            break;
+        }
+        pacing::pause().await;
+    }
+    Ok(members)
+}
+
+/// Commit counts per contributor login of `repo`, bots left out.
+async fn fetch_contributors(
+    client: &Client,
+    token: &str,
+    repo: &str,
+) -> Result<Vec<(String, u32)>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut contributors = Vec::new();
+    for page in 1..=MAX_CONTRIBUTOR_PAGES {
+        let url = format!("https://api.github.com/repos/{repo}/contributors?per_page=100&page={page}");
+        let resp = github::send(github::EndpointClass::Repos, github::get(client, token, &url))
+            .instrument(tracing::info_span!("github.contributors", repo = %repo))
+            .await?;
+        // 204 for an empty repository.
+        if resp.status() == StatusCode::NO_CONTENT {
+            break;
+        }
+        if !resp.status().is_success() {
+            reporting::github_response(&url, resp.status());
+            break;
+        }
+        let page: Vec<serde_json::Value> = resp.json().await?;
+        contributors.extend(page.iter().filter(|c| c["type"] != "Bot").filter_map(|c| {
+            let login = c["login"].as_str().filter(|l| !l.ends_with("[bot]"))?;
+            Some((login.to_string(), c["contributions"].as_u64().unwrap_or(0) as u32))
+        }));
+        if page.len() < 100 {
+            break;
+        }
+        pacing::pause().await;
+    }
+    Ok(contributors)
+}
+
+/// Enumerates `org`'s repositories, keeps the ones the detector pipeline
+/// flags as Sui Move and ranks their contributors who are not org members.
+/// `None` when the organization does not exist.
+pub async fn external_contributors(
+    client: &Client,
+    token: &str,
+    org: &str,
+    limits: ScanLimits,
+) -> Result<Option<OrgExternalContributors>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(repositories) = fetch_org_repositories(client, token, org, limits.max_repos).await? else {
+        return Ok(None);
+    };
+    let members = fetch_members(client, token, org).await?;
+
+    let runtime = crate::state::runtime();
+    let pipeline = &runtime.pipeline;
+    let mut move_repositories = Vec::new();
+    let mut totals: BTreeMap<String, ExternalContributor> = BTreeMap::new();
+    for repo in &repositories {
+        let entries = scan::fetch_tree(client, token, &repo.name, &repo.default_branch, limits.max_tree_entries).await?;
+        pacing::pause().await;
+        if pipeline.run(&detect::RepoContext { client, token, repo, entries: &entries }).await?.is_empty() {
+            continue;
+        }
+        move_repositories.push(repo.name.clone());
+
+        for (login, commits) in fetch_contributors(client, token, &repo.name).await? {
+            if members.contains(&login.to_lowercase()) {
+                continue;
+            }
+            let contributor = totals.entry(login.to_lowercase()).or_insert_with(|| ExternalContributor {
+                login,
+                commits: 0,
+                repositories: Vec::new(),
+            });
+            contributor.commits += commits;
+            contributor.repositories.push(repo.name.clone());
+        }
+        pacing::pause().await;
+    }
+
+    let mut contributors: Vec<ExternalContributor> = totals.into_values().collect();
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.login.cmp(&b.login)));
+    Ok(Some(OrgExternalContributors {
+        org: org.to_string(),
+        repositories_scanned: repositories.len(),
+        move_repositories,
+        members_known: members.len(),
+        contributors,
+    }))
+}
+
+// ------------------- Handler -------------------
+
+/// `GET /org-external-contributors?org=<org>`: community contributors to an
+/// organization's Move repositories, for DevRel outreach.
+#[tracing::instrument(skip_all, fields(org = %params.org))]
+pub async fn external_contributors_handler(
+    locale: i18n::Locale,
+    Query(params): Query<OrgContributorsQuery>,
+    State(client): State<Client>,
+    github::RequestToken(token): github::RequestToken,
+    State(storage): State<Storage>,
+) -> Result<Json<OrgExternalContributors>, (StatusCode, String)> {
+    let org = params.org.trim();
+    if org.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, locale.render(&i18n::Message::new("org_empty"))));
+    }
+    if !github::is_valid_login(org) {
+        return Err((StatusCode::BAD_REQUEST, locale.render(&i18n::Message::new("org_invalid").arg("org", org))));
+    }
+    let limits = ScanLimits::requested(params.max_repos, params.max_tree_entries, None);
+    let opted_out = storage.opted_out().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let discovery = external_contributors(&client, &token, org, limits);
+    match queue::run(queue::Lane::Interactive, discovery).await {
+        Ok(Some(mut report)) => {
+            report.contributors.retain(|c| !opted_out.contains(&c.login.to_lowercase()));
+            Ok(Json(report))
+        }
+        Ok(None) => Err((StatusCode::NOT_FOUND, locale.render(&i18n::Message::new("org_not_found").arg("org", org)))),
+        Err(e) => Err(crate::upstream_error(org, e)),
+    }
+}
@@ -0,0 +1,161 @@
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::{admin, state};
+
+// ------------------- Profiles -------------------
+
+/// How hard GitHub is driven: the pause between consecutive calls, and how
+/// often and how patiently transient failures (network errors, 5xx, 429 and
+/// rate limits) are retried.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PacingProfile {
+    /// Pause between consecutive GitHub calls, in milliseconds.
+    pub delay_ms: u64,
+    /// Pause after each REST search call; search shares a limit of 30 calls per minute.
+    pub search_delay_ms: u64,
+    /// Retries of a transient failure before it is returned.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each further one.
+    pub backoff_base_ms: u64,
+    /// Longest wait before a retry. A failure GitHub asks to retry only
+    /// later than this (`Retry-After`, rate limit reset) is not retried.
+    pub backoff_ceiling_ms: u64,
+}
+
+/// Used when `PACING_PROFILE` is unset.
+const DEFAULT_PROFILE: &str = "balanced";
+
+fn builtin() -> BTreeMap<String, PacingProfile> {
+    let profile = |delay_ms, search_delay_ms, max_retries, backoff_base_ms, backoff_ceiling_ms| PacingProfile {
+        delay_ms,
+        search_delay_ms,
+        max_retries,
+        backoff_base_ms,
+        backoff_ceiling_ms,
+    };
+    BTreeMap::from([
+        ("aggressive".to_string(), profile(100, 1_000, 1, 500, 5_000)),
+        ("balanced".to_string(), profile(300, 2_000, 3, 1_000, 30_000)),
+        ("gentle".to_string(), profile(1_000, 5_000, 5, 2_000, 120_000)),
+    ])
+}
+
+impl PacingProfile {
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(self.delay_ms)
+    }
+
+    /// The wait before retry number `retry` (0 for the first), at least
+    /// `requested` by GitHub; `None` when that exceeds the ceiling.
+    pub fn backoff(&self, retry: u32, requested: Duration) -> Option<Duration> {
+        let ceiling = Duration::from_millis(self.backoff_ceiling_ms);
+        if requested > ceiling {
+            return None;
+        }
+        let exponential = Duration::from_millis(self.backoff_base_ms.saturating_mul(1 << retry.min(16)));
+        Some(exponential.max(requested).min(ceiling))
+    }
+}
+
+/// The built-in `aggressive`, `balanced` and `gentle` profiles, plus any
+/// defined or redefined in the JSON object of name -> profile at
+/// `PACING_PROFILES_PATH`. `PACING_PROFILE` names the deployment default.
+pub struct Profiles {
+    default: String,
+    profiles: BTreeMap<String, PacingProfile>,
+}
+
+impl Profiles {
+    pub fn get(&self, name: &str) -> Option<&PacingProfile> {
+        self.profiles.get(name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    fn default_profile(&self) -> &PacingProfile {
+        &self.profiles[&self.default]
+    }
+
+    /// The profiles of `PACING_PROFILES_PATH` and `PACING_PROFILE`, or just
+    /// the built-in ones when those are invalid.
+    pub fn from_env() -> Self {
+        let path = std::env::var("PACING_PROFILES_PATH").ok();
+        let default = std::env::var("PACING_PROFILE").ok();
+        load(path.as_deref(), default.as_deref()).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring pacing configuration: {e}");
+            Profiles { default: DEFAULT_PROFILE.to_string(), profiles: builtin() }
+        })
+    }
+}
+
+/// Builds the profiles from an optional `PACING_PROFILES_PATH` file and
+/// `PACING_PROFILE` default.
+pub fn load(path: Option<&str>, default: Option<&str>) -> Result<Profiles, String> {
+    let mut profiles = builtin();
+    if let Some(path) = path {
+        let raw = std::fs::read_to_string(path).map_err(|e| format!("{path}: {e}"))?;
+        let file: BTreeMap<String, PacingProfile> = serde_json::from_str(&raw).map_err(|e| format!("{path}: {e}"))?;
+        profiles.extend(file);
+    }
+    let default = default.map(str::trim).filter(|d| !d.is_empty()).unwrap_or(DEFAULT_PROFILE).to_string();
+    if !profiles.contains_key(&default) {
+        let available: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        return Err(format!("PACING_PROFILE {default} is not one of {}", available.join(", ")));
+    }
+    Ok(Profiles { default, profiles })
+}
+
+// ------------------- Request Overrides -------------------
+
+/// Header through which admins run a request under another profile.
+pub const PACING_HEADER: &str = "X-Pacing-Profile";
+
+tokio::task_local! {
+    static OVERRIDE: PacingProfile;
+}
+
+/// The profile GitHub calls made now follow: the request's override, if
+/// any, else the deployment default.
+pub fn current() -> PacingProfile {
+    OVERRIDE.try_with(PacingProfile::clone).unwrap_or_else(|_| state::runtime().pacing.default_profile().clone())
+}
+
+/// Sleeps for the current profile's pause between GitHub calls.
+pub async fn pause() {
+    tokio::time::sleep(current().delay()).await;
+}
+
+/// Sleeps for the current profile's pause after a REST search call.
+pub async fn pause_search() {
+    tokio::time::sleep(Duration::from_millis(current().search_delay_ms)).await;
+}
+
+/// Runs the request under the profile named in `X-Pacing-Profile`, which
+/// only admins may send. Work spawned in the background, and streamed
+/// bodies once the response has started, keep the deployment default.
+pub async fn select(request: Request, next: Next) -> Response {
+    let Some(value) = request.headers().get(PACING_HEADER) else {
+        return next.run(request).await;
+    };
+    if let Err(rejection) = admin::require_admin(request.headers()) {
+        return rejection.into_response();
+    }
+    let name = value.to_str().unwrap_or_default().trim().to_string();
+    let runtime = state::runtime();
+    let profiles = &runtime.pacing;
+    let Some(profile) = profiles.get(&name).cloned() else {
+        let message = format!("unknown pacing profile {name}; available: {}", profiles.names().join(", "));
+        return (StatusCode::BAD_REQUEST, message).into_response();
+    };
+    OVERRIDE.scope(profile, next.run(request)).await
+}
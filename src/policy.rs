@@ -0,0 +1,506 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{i18n, scan::UserMoveFilesResponse};
+
+// ------------------- Policies -------------------
+
+/// A named set of criteria a scan must meet to count as a Sui developer.
+/// Every criterion is a rule expression over scan metrics, such as
+/// `commits >= 20 && (move_lines >= 500 || packages_published >= 1)`.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    pub name: String,
+    criteria: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    source: String,
+    expr: Expr,
+}
+
+/// All configured policies and the one used when a request names none.
+#[derive(Debug)]
+pub struct PolicySet {
+    default: String,
+    policies: BTreeMap<String, Policy>,
+}
+
+/// `VERDICT_POLICY_PATH` file layout: policy name -> rule list.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    default: String,
+    policies: BTreeMap<String, Vec<String>>,
+}
+
+const DEFAULT_POLICY: &str = "default";
+
+/// Only requires one Move repository, which matches `has_move_files`.
+const DEFAULT_CRITERIA: &[&str] = &["repositories >= 1"];
+
+/// The built-in `default` policy alone, used when `VERDICT_POLICY_PATH` is unset.
+pub fn builtin() -> PolicySet {
+    let criteria = DEFAULT_CRITERIA.iter().map(|c| c.to_string()).collect();
+    let policies = BTreeMap::from([(DEFAULT_POLICY.to_string(), criteria)]);
+    build(PolicyFile { default: DEFAULT_POLICY.to_string(), policies }).expect("default policy is valid")
+}
+
+pub fn load(path: &str) -> Result<PolicySet, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    build(serde_json::from_str(&raw).map_err(|e| e.to_string())?)
+}
+
+fn build(file: PolicyFile) -> Result<PolicySet, String> {
+    let mut policies = BTreeMap::new();
+    for (name, sources) in file.policies {
+        let criteria = sources
+            .into_iter()
+            .map(|source| match parse(&source) {
+                Ok(expr) => Ok(Rule { source, expr }),
+                Err(e) => Err(format!("policy {name}: `{source}`: {e}")),
+            })
+            .collect::<Result<_, _>>()?;
+        policies.insert(name.clone(), Policy { name, criteria });
+    }
+
+    if !policies.contains_key(&file.default) {
+        return Err(format!("default policy {} is not defined", file.default));
+    }
+    Ok(PolicySet { default: file.default, policies })
+}
+
+impl PolicySet {
+    /// The JSON file at `VERDICT_POLICY_PATH`, or the built-in `default`
+    /// policy when it is unset.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("VERDICT_POLICY_PATH") {
+            Ok(path) => load(&path).map_err(|e| format!("VERDICT_POLICY_PATH={path}: {e}")),
+            Err(_) => Ok(builtin()),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.policies.keys().map(String::as_str).collect()
+    }
+
+    /// The policy a request asked for with `policy=<name>`, or the default.
+    pub fn select(&self, name: Option<&str>) -> Result<&Policy, i18n::Message> {
+        let name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(&self.default);
+        self.policies.get(name).ok_or_else(|| {
+            i18n::Message::new("unknown_policy")
+                .arg("name", name)
+                .arg("available", self.policies.keys().cloned().collect::<Vec<_>>().join(", "))
+        })
+    }
+}
+
+// ------------------- Verdict -------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Verdict {
+    pub policy: String,
+    /// True when every criterion passed.
+    pub is_sui_developer: bool,
+    pub criteria: Vec<Criterion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Criterion {
+    pub rule: String,
+    pub passed: bool,
+    /// The metrics the rule refers to; `None` when the scan did not measure
+    /// one (e.g. `move_lines` outside deep mode), which fails its comparisons.
+    pub values: BTreeMap<String, Option<u64>>,
+}
+
+impl Policy {
+    /// Evaluates `result` against every criterion.
+    pub fn evaluate(&self, result: &UserMoveFilesResponse, now_secs: u64) -> Verdict {
+        let criteria: Vec<Criterion> = self
+            .criteria
+            .iter()
+            .map(|rule| {
+                let mut names = Vec::new();
+                rule.expr.metrics(&mut names);
+                Criterion {
+                    rule: rule.source.clone(),
+                    passed: rule.expr.eval(result, now_secs),
+                    values: names.into_iter().map(|m| (m.to_string(), metric(m, result, now_secs))).collect(),
+                }
+            })
+            .collect();
+
+        Verdict { policy: self.name.clone(), is_sui_developer: criteria.iter().all(|c| c.passed), criteria }
+    }
+}
+
+// ------------------- Metrics -------------------
+
+/// Names usable in rules.
+const METRICS: &[&str] = &[
+    "repositories",
+    "original_repositories",
+    "tooling_repositories",
+    "commits",
+    "move_lines",
+    "inactive_days",
+    "move_days",
+    "merged_pull_requests",
+    "issues_opened",
+    "reviews_given",
+    "organizations",
+    "sips_authored",
+    "doc_commits",
+    "transactions",
+    "packages_published",
+    "longest_streak_days",
+    "active_weeks_last_year",
+    "identity_confirmed",
+];
+
+fn metric(name: &str, result: &UserMoveFilesResponse, now_secs: u64) -> Option<u64> {
+    let days_ago = |date: &Option<String>| {
+        date.as_deref().and_then(days_since_epoch).map(|day| (now_secs / 86_400).saturating_sub(day))
+    };
+
+    match name {
+        "repositories" => Some(result.total_repositories as u64),
+        "original_repositories" => {
+            Some(result.repositories.iter().filter(|r| r.is_original()).count() as u64)
+        }
+        "tooling_repositories" => {
+            Some(result.repositories.iter().filter(|r| !r.tooling_evidence.is_empty()).count() as u64)
+        }
+        // Quick scans stop before counting commits, so there is nothing to compare.
+        "commits" => (result.mode != crate::scan::ScanMode::Quick).then_some(result.total_commits as u64),
+        "move_lines" => result.move_lines_authored.map(u64::from),
+        "inactive_days" => days_ago(&result.last_commit_at),
+        "move_days" => days_ago(&result.move_since),
+        "merged_pull_requests" => result.total_merged_pull_requests.map(u64::from),
+        "issues_opened" => result.issues_opened.map(u64::from),
+        "reviews_given" => result.reviews_given.map(u64::from),
+        "organizations" => Some(result.sui_organizations.len() as u64),
+        "sips_authored" => result.governance.as_ref().map(|g| g.sips_authored.len() as u64),
+        "doc_commits" => result.documentation_contributions.as_ref().map(|d| d.total_commits as u64),
+        "transactions" => result.chain_activity.as_ref().map(|c| c.transaction_count),
+        "packages_published" => result.chain_activity.as_ref().map(|c| c.packages_published as u64),
+        "longest_streak_days" => result.activity.as_ref().map(|a| a.longest_streak_days as u64),
+        "active_weeks_last_year" => result.activity.as_ref().map(|a| a.active_weeks_last_year as u64),
+        "identity_confirmed" => Some(result.identity_confirmed as u64),
+        _ => None,
+    }
+}
+
+/// Days since 1970-01-01 of the `YYYY-MM-DD` prefix of an ISO 8601 timestamp.
+pub fn days_since_epoch(timestamp: &str) -> Option<u64> {
+    let mut parts = timestamp.get(..10)?.split('-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // Civil-from-days inverse, counting years from March so leap days fall last.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    u64::try_from(era * 146_097 + day_of_era - 719_468).ok()
+}
+
+// ------------------- Rule Expressions -------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Ge,
+    Gt,
+    Le,
+    Lt,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Compare { metric: &'static str, op: Op, value: f64 },
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+impl Expr {
+    fn eval(&self, result: &UserMoveFilesResponse, now_secs: u64) -> bool {
+        match self {
+            Expr::Compare { metric: name, op, value } => metric(name, result, now_secs).is_some_and(|actual| {
+                let actual = actual as f64;
+                match op {
+                    Op::Ge => actual >= *value,
+                    Op::Gt => actual > *value,
+                    Op::Le => actual <= *value,
+                    Op::Lt => actual < *value,
+                    Op::Eq => actual == *value,
+                    Op::Ne => actual != *value,
+                }
+            }),
+            Expr::Not(inner) => !inner.eval(result, now_secs),
+            Expr::And(all) => all.iter().all(|e| e.eval(result, now_secs)),
+            Expr::Or(any) => any.iter().any(|e| e.eval(result, now_secs)),
+        }
+    }
+
+    fn metrics(&self, names: &mut Vec<&'static str>) {
+        match self {
+            Expr::Compare { metric, .. } => {
+                if !names.contains(metric) {
+                    names.push(metric);
+                }
+            }
+            Expr::Not(inner) => inner.metrics(names),
+            Expr::And(list) | Expr::Or(list) => list.iter().for_each(|e| e.metrics(names)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric() || **c == '_') {
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Ident(ident));
+            continue;
+        }
+        if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Number(number.parse().map_err(|_| format!("invalid number {number}"))?));
+            continue;
+        }
+
+        chars.next();
+        let next_is = |chars: &mut std::iter::Peekable<std::str::Chars>, want: char| chars.next_if_eq(&want).is_some();
+        tokens.push(match c {
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '&' if next_is(&mut chars, '&') => Token::And,
+            '|' if next_is(&mut chars, '|') => Token::Or,
+            '>' if next_is(&mut chars, '=') => Token::Op(Op::Ge),
+            '>' => Token::Op(Op::Gt),
+            '<' if next_is(&mut chars, '=') => Token::Op(Op::Le),
+            '<' => Token::Op(Op::Lt),
+            '=' if next_is(&mut chars, '=') => Token::Op(Op::Eq),
+            '!' if next_is(&mut chars, '=') => Token::Op(Op::Ne),
+            '!' => Token::Not,
+            other => return Err(format!("unexpected character {other:?}")),
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Parses `or := and ("||" and)*`, `and := unary ("&&" unary)*`,
+/// `unary := "!" unary | "(" or ")" | metric op number`.
+fn parse(source: &str) -> Result<Expr, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens: &tokens, at: 0 };
+    let expr = parser.or()?;
+    match parser.tokens.get(parser.at) {
+        None => Ok(expr),
+        Some(token) => Err(format!("unexpected {token:?}")),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.at);
+        self.at += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let matched = self.tokens.get(self.at) == Some(token);
+        if matched {
+            self.at += 1;
+        }
+        matched
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            terms.push(self.and()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Expr::Or(terms) })
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut terms = vec![self.unary()?];
+        while self.eat(&Token::And) {
+            terms.push(self.unary()?);
+        }
+        Ok(if terms.len() == 1 { terms.remove(0) } else { Expr::And(terms) })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::Open) {
+            let inner = self.or()?;
+            if !self.eat(&Token::Close) {
+                return Err("missing )".to_string());
+            }
+            return Ok(inner);
+        }
+
+        let metric = match self.next() {
+            Some(Token::Ident(name)) => METRICS
+                .iter()
+                .copied()
+                .find(|m| m == name)
+                .ok_or_else(|| format!("unknown metric {name} (expected one of {})", METRICS.join(", ")))?,
+            other => return Err(format!("expected a metric, found {other:?}")),
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => *op,
+            other => return Err(format!("expected a comparison after {metric}, found {other:?}")),
+        };
+        let value = match self.next() {
+            Some(Token::Number(n)) => *n,
+            other => return Err(format!("expected a number after {metric}, found {other:?}")),
+        };
+        Ok(Expr::Compare { metric, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::{ScanLimits, ScanMode};
+
+    fn scan(repositories: usize, commits: u32, move_lines: Option<u32>) -> UserMoveFilesResponse {
+        serde_json::from_value(serde_json::json!({
+            "username": "alice",
+            "has_move_files": repositories > 0,
+            "total_repositories": repositories,
+            "total_commits": commits,
+            "move_lines_authored": move_lines,
+            "repositories": [],
+            "mode": ScanMode::Full,
+            "limits": ScanLimits::ceiling(),
+        }))
+        .expect("a minimal scan deserializes")
+    }
+
+    #[test]
+    fn parse_binds_and_tighter_than_or() {
+        let expr = parse("commits >= 20 && move_lines >= 500 || repositories > 3").unwrap();
+        let Expr::Or(terms) = expr else { panic!("expected an or, got {expr:?}") };
+        assert!(matches!(&terms[0], Expr::And(all) if all.len() == 2));
+        assert!(matches!(terms[1], Expr::Compare { metric: "repositories", op: Op::Gt, value } if value == 3.0));
+    }
+
+    #[test]
+    fn parse_reads_every_operator_and_grouping() {
+        let operators = [
+            ("commits >= 1", Op::Ge),
+            ("commits > 1", Op::Gt),
+            ("commits <= 1", Op::Le),
+            ("commits < 1", Op::Lt),
+            ("commits == 1", Op::Eq),
+            ("commits != 1", Op::Ne),
+        ];
+        for (source, expected) in operators {
+            assert!(matches!(parse(source).unwrap(), Expr::Compare { op, .. } if op == expected), "{source}");
+        }
+        let negated = parse("!(commits >= 1 || repositories >= 1)").unwrap();
+        assert!(matches!(negated, Expr::Not(inner) if matches!(*inner, Expr::Or(_))));
+        assert!(matches!(parse("  repositories>=1.5 ").unwrap(), Expr::Compare { value, .. } if value == 1.5));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_rules() {
+        let malformed = [
+            "",
+            "commits",
+            "commits >=",
+            "commits >= x",
+            "stars >= 1",
+            "(commits >= 1",
+            "commits >= 1)",
+            "commits >= 1 &&",
+            "commits = 1",
+            "commits >= 1.2.3",
+            "commits >= 1 # note",
+        ];
+        for source in malformed {
+            assert!(parse(source).is_err(), "{source} should not parse");
+        }
+        assert!(parse("stars >= 1").unwrap_err().starts_with("unknown metric stars"));
+    }
+
+    #[test]
+    fn rules_evaluate_against_scan_metrics() {
+        let rule = parse("commits >= 20 && (move_lines >= 500 || repositories >= 3)").unwrap();
+        assert!(rule.eval(&scan(1, 25, Some(600)), 0));
+        assert!(rule.eval(&scan(3, 25, None), 0));
+        assert!(!rule.eval(&scan(1, 25, Some(100)), 0));
+        assert!(!rule.eval(&scan(3, 10, Some(600)), 0));
+    }
+
+    #[test]
+    fn rules_over_missing_metrics_fail() {
+        // Without authorship, move_lines is unknown rather than zero.
+        assert!(!parse("move_lines < 10").unwrap().eval(&scan(1, 25, None), 0));
+        assert!(parse("!(move_lines >= 10)").unwrap().eval(&scan(1, 25, None), 0));
+    }
+
+    #[test]
+    fn evaluate_reports_each_criterion() {
+        let file = PolicyFile {
+            default: "strict".to_string(),
+            policies: BTreeMap::from([(
+                "strict".to_string(),
+                vec!["repositories >= 1".to_string(), "commits >= 20".to_string()],
+            )]),
+        };
+        let policies = build(file).unwrap();
+        let verdict = policies.select(None).unwrap().evaluate(&scan(2, 5, None), 0);
+        assert_eq!(verdict.policy, "strict");
+        assert!(!verdict.is_sui_developer);
+        assert_eq!(verdict.criteria.iter().map(|c| c.passed).collect::<Vec<_>>(), [true, false]);
+        assert_eq!(verdict.criteria[1].values.get("commits"), Some(&Some(5)));
+        assert!(policies.select(Some("lenient")).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{github, reporting, state::AppState, storage};
+
+// ------------------- Opt-Outs -------------------
+
+/// Text an opt-out gist must contain, followed by the username.
+const GIST_PHRASE: &str = "sui-contributors opt-out:";
+
+/// How a developer proved they own the account they opted out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptOutMethod {
+    /// Their own GitHub token, sent in `X-GitHub-Token`, belongs to the account.
+    Oauth,
+    /// A gist owned by the account contains the opt-out phrase.
+    Gist,
+    #[serde(other)]
+    Unknown,
+}
+
+/// A developer excluded from the leaderboard, the ecosystem graph and
+/// organization contributor discovery. They can still verify themselves.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptOut {
+    pub username: String,
+    pub method: OptOutMethod,
+    pub opted_out_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OptOutRequest {
+    username: String,
+    /// A gist of `username` containing `sui-contributors opt-out: <username>`,
+    /// for developers who would rather not send a token.
+    gist_id: Option<String>,
+}
+
+/// The login `token` authenticates as.
+async fn token_login(client: &Client, token: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let url = "https://api.github.com/user";
+    let resp = github::send(github::EndpointClass::Repos, github::get(client, token, url)).await?;
+    if !resp.status().is_success() {
+        reporting::github_response(url, resp.status());
+        return Err(format!("GitHub rejected the token with {}", resp.status()).into());
+    }
+    let user: serde_json::Value = resp.json().await?;
+    Ok(user["login"].as_str().unwrap_or_default().to_string())
+}
+
+/// The gist's owner when one of its files carries the opt-out phrase for
+/// `username`; `None` when the gist does not exist or lacks the phrase.
+async fn gist_owner(
+    client: &Client,
+    token: &str,
+    gist_id: &str,
+    username: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("https://api.github.com/gists/{}", urlencoding::encode(gist_id));
+    let resp = github::send(github::EndpointClass::Repos, github::get(client, token, &url)).await?;
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !resp.status().is_success() {
+        reporting::github_response(&url, resp.status());
+        return Err(format!("GitHub gist lookup failed with {}", resp.status()).into());
+    }
+    let gist: serde_json::Value = resp.json().await?;
+    let phrase = format!("{GIST_PHRASE} {username}").to_lowercase();
+    let has_phrase = gist["files"]
+        .as_object()
+        .into_iter()
+        .flat_map(|files| files.values())
+        .any(|file| file["content"].as_str().is_some_and(|c| c.to_lowercase().contains(&phrase)));
+    Ok(has_phrase.then(|| gist["owner"]["login"].as_str().unwrap_or_default().to_string()))
+}
+
+// ------------------- Handler -------------------
+
+/// `POST /opt-out`: removes a developer from public rankings and discovery
+/// once they prove the account is theirs, through a gist (`gist_id`) or
+/// their own token in `X-GitHub-Token`. Opting out again refreshes the record.
+pub async fn opt_out(
+    headers: HeaderMap,
+    State(AppState { client, github_token, storage, .. }): State<AppState>,
+    Json(body): Json<OptOutRequest>,
+) -> Result<Json<OptOut>, (StatusCode, String)> {
+    let username = body.username.trim();
+    if !github::is_valid_login(username) {
+        return Err((StatusCode::BAD_REQUEST, format!("`{username}` is not a GitHub username")));
+    }
+
+    let user_token = headers.get(github::CLIENT_TOKEN_HEADER).and_then(|v| v.to_str().ok()).map(str::trim);
+    let (method, login) = match (body.gist_id.as_deref().map(str::trim), user_token) {
+        (Some(gist_id), _) => {
+            let owner = gist_owner(&client, &github_token, gist_id, username).await.map_err(|e| crate::upstream_error(username, e))?;
+            let Some(owner) = owner else {
+                let message = format!("gist {gist_id} does not exist or does not contain `{GIST_PHRASE} {username}`");
+                return Err((StatusCode::FORBIDDEN, message));
+            };
+            (OptOutMethod::Gist, owner)
+        }
+        (None, Some(token)) if github::client_tokens_allowed() && github::is_plausible_token(token) => {
+            let login = token_login(&client, token).await.map_err(|e| (StatusCode::FORBIDDEN, e.to_string()))?;
+            (OptOutMethod::Oauth, login)
+        }
+        (None, _) => {
+            let message = format!("prove the account is yours with a gist_id or your own token in {}", github::CLIENT_TOKEN_HEADER);
+            return Err((StatusCode::BAD_REQUEST, message));
+        }
+    };
+    if !login.eq_ignore_ascii_case(username) {
+        return Err((StatusCode::FORBIDDEN, format!("the proof belongs to {login}, not {username}")));
+    }
+
+    let opt_out = OptOut { username: login, method, opted_out_at: storage::now_secs() };
+    storage.save_opt_out(&opt_out).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    tracing::info!("{} opted out of public listings ({method:?})", opt_out.username);
+    Ok(Json(opt_out))
+}
@@ -0,0 +1,231 @@
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::{
+    avatars,
+    certificates::Certificate,
+    github, readonly,
+    scan::UserMoveFilesResponse,
+    storage::{Storage, StoredScan},
+};
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Serialize)]
+pub struct GithubProfile {
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar_url: Option<String>,
+    pub bio: Option<String>,
+    pub location: Option<String>,
+    pub company: Option<String>,
+    pub followers: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuiStats {
+    pub scanned_at: u64,
+    pub has_move_files: bool,
+    pub total_repositories: usize,
+    pub total_commits: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub move_lines_authored: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeveloperProfile {
+    pub username: String,
+    pub github: GithubProfile,
+    /// Stats from the latest stored scan; `null` if the user was never scanned.
+    pub sui: Option<SuiStats>,
+    pub scan: Option<UserMoveFilesResponse>,
+}
+
+// ------------------- Core Logic -------------------
+
+/// Fetches the GitHub profile in one GraphQL query and merges it with the
+/// latest stored scan. Never triggers a scan itself. Returns `Ok(None)` when
+/// the GitHub user does not exist, or in serve-only mode, where only the
+/// login is known, when the user has no stored scan.
+#[tracing::instrument(name = "profile", skip(client, token, storage))]
+pub async fn build_profile(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    username: &str,
+) -> Result<Option<DeveloperProfile>, Box<dyn std::error::Error + Send + Sync>> {
+    let query = r#"
+    query($login:String!) {
+      user(login:$login) {
+        login
+        name
+        avatarUrl
+        bio
+        location
+        company
+        followers { totalCount }
+      }
+    }
+    "#;
+
+    if readonly::enabled() {
+        return Ok(storage.latest_scan(username)?.map(|stored| {
+            let github = GithubProfile {
+                login: stored.username.clone(),
+                name: None,
+                avatar_url: None,
+                bio: None,
+                location: None,
+                company: None,
+                followers: 0,
+            };
+            merge(github, Some(stored))
+        }));
+    }
+
+    let data = match github::graphql_request(client, token, query, Some(serde_json::json!({ "login": username }))).await {
+        Ok(data) => data,
+        // GitHub reports unknown logins as a NOT_FOUND GraphQL error.
+        Err(e) if e.to_string().contains("NOT_FOUND") => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let user = &data["user"];
+    if user.is_null() {
+        return Ok(None);
+    }
+
+    let text = |key: &str| user[key].as_str().filter(|v| !v.is_empty()).map(|v| v.to_string());
+    let github = GithubProfile {
+        login: user["login"].as_str().unwrap_or(username).to_string(),
+        name: text("name"),
+        avatar_url: text("avatarUrl"),
+        bio: text("bio"),
+        location: text("location"),
+        company: text("company"),
+        followers: user["followers"]["totalCount"].as_u64().unwrap_or(0),
+    };
+
+    avatars::note_profile(storage, &github.login, github.avatar_url.as_deref());
+
+    let stored = storage.latest_scan(&github.login)?;
+    Ok(Some(merge(github, stored)))
+}
+
+fn merge(github: GithubProfile, stored: Option<StoredScan>) -> DeveloperProfile {
+    let sui = stored.as_ref().map(|s| SuiStats {
+        scanned_at: s.scanned_at,
+        has_move_files: s.result.has_move_files,
+        total_repositories: s.result.total_repositories,
+        total_commits: s.result.total_commits,
+        move_lines_authored: s.result.move_lines_authored,
+    });
+    DeveloperProfile {
+        username: github.login.clone(),
+        github,
+        sui,
+        scan: stored.map(|s| s.result),
+    }
+}
+
+// ------------------- JSON-LD -------------------
+
+/// Namespace of the Sui-developer terms (`JSONLD_VOCAB_URL`).
+const DEFAULT_VOCAB_URL: &str = "https://www.suiref.xyz/vocab#";
+
+fn vocab_url() -> String {
+    std::env::var("JSONLD_VOCAB_URL").unwrap_or_else(|_| DEFAULT_VOCAB_URL.to_string())
+}
+
+/// `YYYY-MM-DD` of a Unix timestamp.
+pub fn iso_date(secs: u64) -> String {
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+impl DeveloperProfile {
+    /// The profile as a schema.org `Person`, with the scan summary and any
+    /// valid certificate under the `sui:` vocabulary.
+    pub fn to_json_ld(&self, certificate: Option<&Certificate>) -> serde_json::Value {
+        let github_url = format!("https://github.com/{}", self.github.login);
+        let mut person = serde_json::json!({
+            "@context": {
+                "@vocab": "https://schema.org/",
+                "sui": vocab_url(),
+            },
+            "@type": "Person",
+            "@id": github_url,
+            "identifier": self.github.login,
+            "url": github_url,
+            "sameAs": [github_url],
+        });
+
+        let fields = [
+            ("name", self.github.name.clone()),
+            ("image", self.github.avatar_url.clone()),
+            ("description", self.github.bio.clone()),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                person[key] = value.into();
+            }
+        }
+        if let Some(location) = &self.github.location {
+            person["homeLocation"] = serde_json::json!({ "@type": "Place", "name": location });
+        }
+        if let Some(company) = &self.github.company {
+            person["worksFor"] = serde_json::json!({ "@type": "Organization", "name": company.trim_start_matches('@') });
+        }
+
+        if let Some(scan) = &self.scan {
+            person["memberOf"] = scan
+                .sui_organizations
+                .iter()
+                .map(|org| serde_json::json!({ "@type": "Organization", "name": org, "url": format!("https://github.com/{org}") }))
+                .collect();
+            person["sui:developer"] = serde_json::json!({
+                "@type": "sui:DeveloperStats",
+                "sui:hasMoveCode": scan.has_move_files,
+                "sui:repositories": scan.total_repositories,
+                "sui:commits": scan.total_commits,
+                "sui:moveLinesAuthored": scan.move_lines_authored,
+                "sui:lastCommit": scan.last_commit_at,
+                "dateModified": self.sui.as_ref().map(|s| iso_date(s.scanned_at)),
+            });
+            person["sui:contributedTo"] = scan
+                .repositories
+                .iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "@type": "SoftwareSourceCode",
+                        "name": r.repo_name,
+                        "codeRepository": r.repo_url,
+                        "programmingLanguage": "Move",
+                        "sui:commits": r.commit_count,
+                    })
+                })
+                .collect();
+        }
+
+        if let Some(certificate) = certificate {
+            person["sui:certificate"] = serde_json::json!({
+                "@type": "sui:Certificate",
+                "identifier": certificate.id,
+                "sui:policy": certificate.policy,
+                "sui:score": certificate.score,
+                "dateCreated": iso_date(certificate.issued_at),
+                "expires": iso_date(certificate.expires_at),
+            });
+        }
+
+        person
+    }
+}
@@ -0,0 +1,635 @@
+use axum::{
+    Json,
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::{github::RateBudgets, state::Runtime};
+
+// ------------------- Scan Queue -------------------
+
+/// Scans that run at the same time; others wait for a slot.
+const DEFAULT_WORKERS: usize = 4;
+
+/// Worker slots batch scans may never take, kept free for interactive ones.
+const DEFAULT_INTERACTIVE_RESERVED: usize = 1;
+
+/// Scans (running plus waiting) past which scan requests are shed.
+const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// Assumed scan duration until one has completed.
+const DEFAULT_SCAN_SECS: f64 = 20.0;
+
+/// Weight of the newest scan in the moving average of scan durations.
+const DURATION_SMOOTHING: f64 = 0.2;
+
+/// Extra delay before each GitHub call of a batch scan while interactive
+/// scans are active, leaving them most of the rate budget.
+const BATCH_YIELD: Duration = Duration::from_secs(1);
+
+/// Finished scans kept for `GET /queue`.
+const RECENT_SCANS: usize = 50;
+
+fn env_usize(name: &str, default: usize) -> usize {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+type Registry = Arc<Mutex<HashMap<String, Cancellation>>>;
+
+/// A registered scan's token and the secret its client cancels it with.
+#[derive(Clone)]
+struct Cancellation {
+    token: CancellationToken,
+    secret: String,
+}
+
+/// The scan queue of one [`Runtime`](crate::state::Runtime): its lines and
+/// worker slots, and the scan IDs that can be cancelled.
+pub struct Queue {
+    state: Arc<Mutex<QueueState>>,
+    registry: Registry,
+    /// Scans (running plus waiting) past which scan requests are shed.
+    max_depth: usize,
+}
+
+impl Queue {
+    /// Sized by `SCAN_WORKERS`, `SCAN_INTERACTIVE_RESERVED` and `SCAN_QUEUE_MAX_DEPTH`.
+    pub fn from_env() -> Self {
+        let workers = env_usize("SCAN_WORKERS", DEFAULT_WORKERS).max(1);
+        // At least one, so batch work always progresses.
+        let batch_workers = workers.saturating_sub(env_usize("SCAN_INTERACTIVE_RESERVED", DEFAULT_INTERACTIVE_RESERVED)).max(1);
+        Queue {
+            state: Arc::new(Mutex::new(QueueState { workers, batch_workers, ..Default::default() })),
+            registry: Registry::default(),
+            max_depth: env_usize("SCAN_QUEUE_MAX_DEPTH", DEFAULT_MAX_DEPTH).max(1),
+        }
+    }
+
+    /// Scans running or waiting for a slot.
+    fn depth(&self) -> usize {
+        let state = lock(&self.state);
+        state.running + state.waiting_interactive.len() + state.waiting_batch.len()
+    }
+
+    /// Expected wait before a scan submitted now starts: the scans ahead of
+    /// it run `workers` at a time, each taking about the recent average.
+    fn estimated_wait(&self) -> Duration {
+        let state = lock(&self.state);
+        let waiting = state.waiting_interactive.len() + state.waiting_batch.len();
+        let rounds = waiting.div_ceil(state.workers) as f64;
+        let partial = if state.running >= state.workers { 0.5 } else { 0.0 };
+        Duration::from_secs_f64((rounds + partial) * state.average_secs.unwrap_or(DEFAULT_SCAN_SECS))
+    }
+}
+
+/// Scheduling priority of a scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lane {
+    /// A person waiting on `/check-sui-developer`; always served first.
+    Interactive,
+    /// Cohort scans, background refreshes, preloads and webhook re-scans.
+    Batch,
+}
+
+/// How a scan left the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// One scan's passage through the queue. Times are Unix seconds.
+#[derive(Debug)]
+struct Entry {
+    serial: u64,
+    scan_id: String,
+    lane: Lane,
+    queued_at: u64,
+    started_at: Option<u64>,
+    finished_at: Option<u64>,
+    outcome: Option<Outcome>,
+}
+
+struct Waiter {
+    sender: oneshot::Sender<ScanTicket>,
+    entry: Entry,
+}
+
+#[derive(Default)]
+struct QueueState {
+    workers: usize,
+    /// Of `workers`, the slots batch scans may take.
+    batch_workers: usize,
+    running: usize,
+    running_interactive: usize,
+    waiting_interactive: VecDeque<Waiter>,
+    waiting_batch: VecDeque<Waiter>,
+    average_secs: Option<f64>,
+    next_serial: u64,
+    running_scans: Vec<Entry>,
+    /// Finished scans, newest last.
+    recent: VecDeque<Entry>,
+}
+
+impl QueueState {
+    fn can_start(&self, lane: Lane) -> bool {
+        match lane {
+            Lane::Interactive => self.running < self.workers,
+            Lane::Batch => self.waiting_interactive.is_empty() && self.running < self.batch_workers,
+        }
+    }
+
+    fn entry(&mut self, scan_id: &str, lane: Lane) -> Entry {
+        self.next_serial += 1;
+        Entry {
+            serial: self.next_serial,
+            scan_id: scan_id.to_string(),
+            lane,
+            queued_at: crate::storage::now_secs(),
+            started_at: None,
+            finished_at: None,
+            outcome: None,
+        }
+    }
+
+    /// Gives `entry` a worker slot of `queue`, the queue this state belongs to.
+    fn start(&mut self, mut entry: Entry, queue: &Arc<Mutex<QueueState>>) -> ScanTicket {
+        self.running += 1;
+        if entry.lane == Lane::Interactive {
+            self.running_interactive += 1;
+        }
+        let ticket = ScanTicket {
+            queue: queue.clone(),
+            lane: entry.lane,
+            serial: entry.serial,
+            started: Instant::now(),
+            outcome: Outcome::Cancelled,
+        };
+        entry.started_at = Some(crate::storage::now_secs());
+        self.running_scans.push(entry);
+        ticket
+    }
+
+    fn finish(&mut self, mut entry: Entry, outcome: Outcome) {
+        entry.finished_at = Some(crate::storage::now_secs());
+        entry.outcome = Some(outcome);
+        if self.recent.len() == RECENT_SCANS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(entry);
+    }
+
+    /// Takes a cancelled scan out of the waiting lines. One already handed
+    /// a ticket is recorded when the ticket drops instead.
+    fn abandon(&mut self, serial: u64) {
+        let waiter = [&mut self.waiting_interactive, &mut self.waiting_batch].into_iter().find_map(|waiting| {
+            let index = waiting.iter().position(|w| w.entry.serial == serial)?;
+            waiting.remove(index)
+        });
+        if let Some(waiter) = waiter {
+            self.finish(waiter.entry, Outcome::Cancelled);
+        }
+    }
+
+    /// Tickets for the waiters that fit into the free slots, interactive first.
+    fn dispatch(&mut self, queue: &Arc<Mutex<QueueState>>) -> Vec<(oneshot::Sender<ScanTicket>, ScanTicket)> {
+        let mut woken = Vec::new();
+        loop {
+            let lane = if self.can_start(Lane::Interactive) && !self.waiting_interactive.is_empty() {
+                Lane::Interactive
+            } else if self.can_start(Lane::Batch) && !self.waiting_batch.is_empty() {
+                Lane::Batch
+            } else {
+                return woken;
+            };
+            let waiter = match lane {
+                Lane::Interactive => self.waiting_interactive.pop_front(),
+                Lane::Batch => self.waiting_batch.pop_front(),
+            };
+            if let Some(waiter) = waiter.filter(|w| !w.sender.is_closed()) {
+                woken.push((waiter.sender, self.start(waiter.entry, queue)));
+            }
+        }
+    }
+}
+
+/// Hands out tickets outside the lock. A waiter that gave up in the meantime
+/// drops its ticket, which frees the slot again.
+fn wake(woken: Vec<(oneshot::Sender<ScanTicket>, ScanTicket)>) {
+    for (waiter, ticket) in woken {
+        let _ = waiter.send(ticket);
+    }
+}
+
+/// Held for the duration of one scan; frees the worker slot and records how
+/// long the scan took and how it ended on drop.
+pub struct ScanTicket {
+    queue: Arc<Mutex<QueueState>>,
+    lane: Lane,
+    serial: u64,
+    started: Instant,
+    outcome: Outcome,
+}
+
+impl Drop for ScanTicket {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let woken = {
+            let mut state = lock(&self.queue);
+            state.running -= 1;
+            if self.lane == Lane::Interactive {
+                state.running_interactive -= 1;
+            }
+            if let Some(index) = state.running_scans.iter().position(|e| e.serial == self.serial) {
+                let entry = state.running_scans.swap_remove(index);
+                state.finish(entry, self.outcome);
+            }
+            let average = state.average_secs.unwrap_or(elapsed);
+            state.average_secs = Some(average + DURATION_SMOOTHING * (elapsed - average));
+            state.dispatch(&self.queue)
+        };
+        wake(woken);
+    }
+}
+
+tokio::task_local! {
+    static LANE: Lane;
+}
+
+/// Waits for a worker slot in `lane`, then runs `scan` in it. Every scan that
+/// calls GitHub goes through here so the queue depth is accurate. A scan
+/// outside a [`cancellable`] request registers its own ID; once cancelled it
+/// gives up its place or slot and fails with [`Cancelled`].
+pub async fn run<T, F>(lane: Lane, scan: F) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+where
+    F: Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let runtime = crate::state::runtime();
+    let queue = &runtime.queue;
+    let (scan_id, cancel, _registration) = match CANCEL.try_with(|registration| (registration.id.clone(), registration.cancel.clone())) {
+        Ok((scan_id, cancel)) => (scan_id, cancel, None),
+        Err(_) => {
+            let registration = Registration::new(&queue.registry);
+            tracing::info!("Scan {} queued in the {lane:?} lane", registration.id);
+            (registration.id.clone(), registration.cancel.clone(), Some(registration))
+        }
+    };
+
+    let ticket = {
+        let mut state = lock(&queue.state);
+        let entry = state.entry(&scan_id, lane);
+        if state.can_start(lane) {
+            Ok(state.start(entry, &queue.state))
+        } else {
+            let (sender, ticket) = oneshot::channel();
+            let serial = entry.serial;
+            let waiter = Waiter { sender, entry };
+            match lane {
+                Lane::Interactive => state.waiting_interactive.push_back(waiter),
+                Lane::Batch => state.waiting_batch.push_back(waiter),
+            }
+            Err((ticket, serial))
+        }
+    };
+    let mut ticket = match ticket {
+        Ok(ticket) => ticket,
+        Err((ticket, serial)) => tokio::select! {
+            ticket = ticket => ticket.expect("queued scans are always woken"),
+            _ = cancel.cancelled() => {
+                lock(&queue.state).abandon(serial);
+                return Err(Cancelled.into());
+            }
+        },
+    };
+    let result = tokio::select! {
+        result = LANE.scope(lane, scan) => result,
+        _ = cancel.cancelled() => Err(Cancelled.into()),
+    };
+    ticket.outcome = match &result {
+        Ok(_) => Outcome::Completed,
+        Err(e) if e.is::<Cancelled>() => Outcome::Cancelled,
+        Err(_) => Outcome::Failed,
+    };
+    result
+}
+
+/// Called before every GitHub request: batch scans back off while an
+/// interactive scan is running or waiting.
+pub async fn yield_to_interactive() {
+    if LANE.try_with(|lane| *lane) != Ok(Lane::Batch) {
+        return;
+    }
+    let runtime = crate::state::runtime();
+    let contended = {
+        let state = lock(&runtime.queue.state);
+        state.running_interactive > 0 || !state.waiting_interactive.is_empty()
+    };
+    if contended {
+        tokio::time::sleep(BATCH_YIELD).await;
+    }
+}
+
+// ------------------- Queue Status -------------------
+
+/// A scan in `GET /queue`. Times are Unix seconds.
+#[derive(Debug, Serialize)]
+pub struct ScanStatus {
+    /// Only shown to admins and to the client that passed it as `?id=`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub lane: Lane,
+    /// 1-based place in line, for waiting scans.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+    pub queued_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    /// When a waiting scan should get a worker slot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_start_at: Option<u64>,
+    /// When a running scan should be done.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_finish_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<Outcome>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueueSnapshot {
+    pub workers: usize,
+    /// Of `workers`, the slots batch scans may take.
+    pub batch_workers: usize,
+    pub average_scan_secs: f64,
+    /// Rate limits on the latest GitHub responses.
+    pub rate_limits: Vec<crate::github::RateBudget>,
+    /// Set while GitHub's budget is spent: no scan starts calling it before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_resets_at: Option<u64>,
+    pub running: Vec<ScanStatus>,
+    /// In the order they will start: interactive scans ahead of batch ones.
+    pub queued: Vec<ScanStatus>,
+    /// Up to `RECENT_SCANS` finished scans, newest first.
+    pub recent: Vec<ScanStatus>,
+}
+
+impl ScanStatus {
+    fn of(entry: &Entry, show_id: bool) -> Self {
+        ScanStatus {
+            id: show_id.then(|| entry.scan_id.clone()),
+            lane: entry.lane,
+            position: None,
+            queued_at: entry.queued_at,
+            started_at: entry.started_at,
+            estimated_start_at: None,
+            estimated_finish_at: None,
+            finished_at: entry.finished_at,
+            outcome: entry.outcome,
+        }
+    }
+}
+
+/// Replays the dispatch order over the worker slots: running scans free
+/// their slot about the average duration after starting, interactive scans
+/// take the first free slot, batch scans the first free one of their own
+/// `batch_workers` once no interactive scan is waiting, and nothing starts
+/// before an exhausted GitHub budget resets.
+fn snapshot(state: &QueueState, budgets: &RateBudgets, show_id: impl Fn(&str) -> bool) -> QueueSnapshot {
+    let now = crate::storage::now_secs() as f64;
+    let average = state.average_secs.unwrap_or(DEFAULT_SCAN_SECS);
+    let budget_resets_at = budgets.exhausted_until();
+    let gate = budget_resets_at.map_or(now, |reset| reset as f64);
+
+    let finish_of = |entry: &Entry| (entry.started_at.unwrap_or(entry.queued_at) as f64 + average).max(now);
+    let mut slots: Vec<f64> = state.running_scans.iter().map(finish_of).collect();
+    slots.resize(state.workers.max(slots.len()), now);
+    let mut batch_slots: Vec<f64> = state.running_scans.iter().filter(|e| e.lane == Lane::Batch).map(finish_of).collect();
+    batch_slots.resize(state.batch_workers.max(batch_slots.len()), now);
+    let earliest = |slots: &[f64]| slots.iter().copied().enumerate().min_by(|a, b| a.1.total_cmp(&b.1)).unwrap_or((0, now));
+
+    let running = state
+        .running_scans
+        .iter()
+        .map(|entry| ScanStatus { estimated_finish_at: Some(finish_of(entry).round() as u64), ..ScanStatus::of(entry, show_id(&entry.scan_id)) })
+        .collect();
+
+    let waiting = state.waiting_interactive.iter().chain(&state.waiting_batch).filter(|w| !w.sender.is_closed());
+    let mut last_interactive_start = now;
+    let mut queued = Vec::new();
+    for (position, waiter) in waiting.enumerate() {
+        let (slot, free_at) = earliest(&slots);
+        let start = match waiter.entry.lane {
+            Lane::Interactive => {
+                let start = free_at.max(gate);
+                last_interactive_start = start;
+                start
+            }
+            Lane::Batch => {
+                let (batch_slot, batch_free_at) = earliest(&batch_slots);
+                let start = free_at.max(batch_free_at).max(gate).max(last_interactive_start);
+                batch_slots[batch_slot] = start + average;
+                start
+            }
+        };
+        slots[slot] = start + average;
+        queued.push(ScanStatus {
+            position: Some(position + 1),
+            estimated_start_at: Some(start.round() as u64),
+            ..ScanStatus::of(&waiter.entry, show_id(&waiter.entry.scan_id))
+        });
+    }
+
+    QueueSnapshot {
+        workers: state.workers,
+        batch_workers: state.batch_workers,
+        average_scan_secs: average,
+        rate_limits: budgets.observed(),
+        budget_resets_at,
+        running,
+        queued,
+        recent: state.recent.iter().rev().map(|entry| ScanStatus::of(entry, show_id(&entry.scan_id))).collect(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueueQuery {
+    /// The client's own scan ID (the `X-Scan-Id` of its response), shown on
+    /// its entries.
+    id: Option<String>,
+}
+
+/// `GET /queue?id=<scan id>`: running, waiting and recently finished scans,
+/// with estimated start and finish times. Scan IDs are only listed for the
+/// admin token; anyone else sees their own by passing it as `id`.
+pub async fn queue_status(
+    State(runtime): State<Arc<Runtime>>,
+    headers: HeaderMap,
+    Query(params): Query<QueueQuery>,
+) -> Json<QueueSnapshot> {
+    let admin = crate::admin::require_admin(&headers).is_ok();
+    let own = params.id.as_deref().map(str::trim).filter(|id| !id.is_empty());
+    let state = lock(&runtime.queue.state);
+    Json(snapshot(&state, &runtime.budgets, |scan_id| admin || own == Some(scan_id)))
+}
+
+// ------------------- Load Shedding -------------------
+
+#[derive(Debug, Serialize)]
+struct Overloaded {
+    error: &'static str,
+    queue_length: usize,
+    estimated_wait_secs: u64,
+}
+
+/// Answers scan requests with 503 while the queue is at `SCAN_QUEUE_MAX_DEPTH`,
+/// so a spike gets a fast rejection with a `Retry-After` hint instead of a
+/// request that would time out waiting.
+pub async fn shed_load(request: Request, next: Next) -> Response {
+    let runtime = crate::state::runtime();
+    let queue_length = runtime.queue.depth();
+    if queue_length < runtime.queue.max_depth {
+        return next.run(request).await;
+    }
+
+    let estimated_wait_secs = runtime.queue.estimated_wait().as_secs().max(1);
+    tracing::warn!("Shedding {} with {queue_length} scans queued", request.uri().path());
+    let body = Overloaded { error: "scan queue is full", queue_length, estimated_wait_secs };
+    let mut response = (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response();
+    response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(estimated_wait_secs));
+    response
+}
+
+// ------------------- Cancellation -------------------
+
+/// Response header carrying a scan's ID, generated by the server.
+pub const SCAN_ID_HEADER: &str = "x-scan-id";
+
+/// Response header carrying the secret that cancels the scan, sent back the
+/// same way on `POST /scans/{id}/cancel`.
+pub const SCAN_CANCEL_TOKEN_HEADER: &str = "x-scan-cancel-token";
+
+/// The scan was cancelled by `POST /scans/{id}/cancel` or its client left.
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("scan cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+tokio::task_local! {
+    static CANCEL: Arc<Registration>;
+}
+
+/// A scan ID in the registry. Dropping it, which also happens when a client
+/// disconnects and its handler or response stream is dropped, cancels the
+/// token and frees the ID.
+struct Registration {
+    id: String,
+    secret: String,
+    cancel: CancellationToken,
+    registry: Registry,
+}
+
+impl Registration {
+    /// Registers a new random scan ID with a random cancel secret.
+    fn new(registry: &Registry) -> Self {
+        let id = uuid::Uuid::new_v4().to_string();
+        let secret = uuid::Uuid::new_v4().simple().to_string();
+        let cancel = CancellationToken::new();
+        lock(registry).insert(id.clone(), Cancellation { token: cancel.clone(), secret: secret.clone() });
+        Registration { id, secret, cancel, registry: registry.clone() }
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+        lock(&self.registry).remove(&self.id);
+    }
+}
+
+/// Whether the scan the current task belongs to has been cancelled, for
+/// loops that should stop between scans.
+pub fn is_cancelled() -> bool {
+    CANCEL.try_with(|registration| registration.cancel.is_cancelled()).unwrap_or(false)
+}
+
+/// The current request's scan ID, kept registered for as long as a clone is
+/// alive so work that outlives the handler, like a streamed response body,
+/// stays cancellable.
+#[derive(Clone)]
+pub struct ScanScope(Arc<Registration>);
+
+/// The scope of the [`cancellable`] request the current task is serving.
+pub fn current_scope() -> Option<ScanScope> {
+    CANCEL.try_with(|registration| ScanScope(registration.clone())).ok()
+}
+
+impl ScanScope {
+    /// Runs `future` as part of this scope, as if inside the request.
+    pub async fn run<F: Future>(&self, future: F) -> F::Output {
+        CANCEL.scope(self.0.clone(), future).await
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancel.is_cancelled()
+    }
+}
+
+/// Registers a scan request under a new scan ID and runs it with that
+/// cancellation token, returning the ID and its cancel secret in the
+/// response headers.
+pub async fn cancellable(request: Request, next: Next) -> Response {
+    let registration = Registration::new(&crate::state::runtime().queue.registry);
+    let id = HeaderValue::from_str(&registration.id).expect("scan IDs are valid header values");
+    let secret = HeaderValue::from_str(&registration.secret).expect("cancel secrets are valid header values");
+    let mut response = CANCEL.scope(Arc::new(registration), next.run(request)).await;
+    response.headers_mut().insert(HeaderName::from_static(SCAN_ID_HEADER), id);
+    response.headers_mut().insert(HeaderName::from_static(SCAN_CANCEL_TOKEN_HEADER), secret);
+    response
+}
+
+/// `POST /scans/{id}/cancel`: stops a running or queued scan. Its GitHub
+/// requests are dropped and its worker slot freed right away. Needs the
+/// scan's `X-Scan-Cancel-Token` or the admin token.
+pub async fn cancel_scan(
+    State(runtime): State<Arc<Runtime>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> (StatusCode, String) {
+    let Some(cancellation) = lock(&runtime.queue.registry).get(&id).cloned() else {
+        return (StatusCode::NOT_FOUND, format!("no running scan {id}"));
+    };
+
+    let provided = headers.get(SCAN_CANCEL_TOKEN_HEADER).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let owner = crate::admin::constant_time_eq(provided.as_bytes(), cancellation.secret.as_bytes());
+    if !owner && crate::admin::require_admin(&headers).is_err() {
+        return (StatusCode::UNAUTHORIZED, format!("cancelling scan {id} needs its X-Scan-Cancel-Token or the admin token"));
+    }
+
+    cancellation.token.cancel();
+    tracing::info!("Scan {id} cancelled");
+    (StatusCode::ACCEPTED, format!("scan {id} cancelled"))
+}
@@ -0,0 +1,54 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{scan::UserMoveFilesResponse, storage::Storage};
+
+// ------------------- Serve-Only Mode -------------------
+
+static SERVE_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Switches the process to serve-only mode (`serve --serve-only` or
+/// `SERVE_ONLY=true`): GitHub is never called and scans are answered from
+/// the stored results alone, so the deployment needs no GitHub token.
+pub fn enable() {
+    SERVE_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    SERVE_ONLY.load(Ordering::Relaxed)
+}
+
+/// Whether `SERVE_ONLY` asks for serve-only mode.
+pub fn requested_by_env() -> bool {
+    std::env::var("SERVE_ONLY").is_ok_and(|v| matches!(v.trim(), "true" | "1" | "yes"))
+}
+
+/// Returned instead of calling GitHub in serve-only mode.
+#[derive(Debug)]
+pub struct ServeOnly;
+
+impl std::fmt::Display for ServeOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("this deployment serves stored results only and does not call GitHub")
+    }
+}
+
+impl std::error::Error for ServeOnly {}
+
+/// The latest stored scan of the same accounts, whatever its mode, limits
+/// or age, since it will never be refreshed; `None` when there is none.
+pub fn stored_scan(
+    storage: &Storage,
+    usernames: &[String],
+) -> Result<Option<UserMoveFilesResponse>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some(stored) = storage.latest_scan(&usernames[0])? else {
+        return Ok(None);
+    };
+    let result = stored.result;
+    let same_accounts = result.aliases.len() == usernames.len() - 1
+        && result.aliases.iter().zip(&usernames[1..]).all(|(a, b)| a.eq_ignore_ascii_case(b));
+    if !same_accounts {
+        return Ok(None);
+    }
+
+    Ok(Some(UserMoveFilesResponse { cached_at: Some(stored.scanned_at), stale: false, ..result }))
+}
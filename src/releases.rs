@@ -0,0 +1,131 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::github;
+
+// ------------------- Releases -------------------
+
+/// Releases kept per repository, newest first.
+const MAX_RELEASES: usize = 20;
+
+/// Tags read per repository.
+const MAX_TAGS: usize = 50;
+
+/// Commit message phrases that mark a package publish or deployment.
+const PUBLISH_PATTERNS: &[&str] = &[
+    "publish",
+    "mainnet deploy",
+    "deploy to mainnet",
+    "deployed to mainnet",
+    "testnet deploy",
+    "deploy to testnet",
+    "upgrade package",
+    "package upgrade",
+];
+
+/// A shipped version of a repository's Move code: a version tag or a commit
+/// whose message records a publish.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub kind: ReleaseKind,
+    /// Tag name, or the first line of the commit message.
+    pub name: String,
+    /// Tagger or commit date (ISO 8601).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseKind {
+    Tag,
+    Commit,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Whether `tag` names a version: `v1.2.0`, `1.2`, or a package-prefixed
+/// form such as `my_pkg-v1.0.0` or `my_pkg@1.0.0`.
+pub fn is_version_tag(tag: &str) -> bool {
+    let version = tag.rsplit(['-', '@', '/']).next().unwrap_or(tag);
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    let mut parts = version.split('.');
+    let numeric = |p: Option<&str>| p.is_some_and(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    numeric(parts.next()) && numeric(parts.next())
+}
+
+/// Commits (commits API objects) whose message matches a publish pattern.
+pub fn from_commits(commits: &[serde_json::Value]) -> Vec<Release> {
+    commits
+        .iter()
+        .filter_map(|c| {
+            let subject = c["commit"]["message"].as_str()?.lines().next()?.trim();
+            let lower = subject.to_lowercase();
+            PUBLISH_PATTERNS.iter().any(|p| lower.contains(p)).then(|| Release {
+                kind: ReleaseKind::Commit,
+                name: subject.to_string(),
+                date: c["commit"]["author"]["date"].as_str().map(String::from),
+                sha: c["sha"].as_str().map(String::from),
+            })
+        })
+        .collect()
+}
+
+/// Version tags of `repo`, dated by the tagger or the tagged commit.
+pub async fn fetch_tags(client: &Client, token: &str, repo: &str) -> Result<Vec<Release>, Box<dyn std::error::Error + Send + Sync>> {
+    let Some((owner, name)) = repo.split_once('/') else {
+        return Ok(Vec::new());
+    };
+    let query = r#"
+    query($owner:String!, $name:String!, $first:Int!) {
+      repository(owner:$owner, name:$name) {
+        refs(refPrefix:"refs/tags/", first:$first, orderBy:{field:TAG_COMMIT_DATE, direction:DESC}) {
+          nodes {
+            name
+            target {
+              oid
+              ... on Commit { committedDate }
+              ... on Tag { tagger { date } target { ... on Commit { committedDate } } }
+            }
+          }
+        }
+      }
+    }
+    "#;
+    let vars = serde_json::json!({ "owner": owner, "name": name, "first": MAX_TAGS });
+    let data = github::graphql_request(client, token, query, Some(vars)).await?;
+
+    Ok(data["repository"]["refs"]["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| {
+            let name = tag["name"].as_str().filter(|n| is_version_tag(n))?;
+            let target = &tag["target"];
+            let date = target["tagger"]["date"]
+                .as_str()
+                .or(target["committedDate"].as_str())
+                .or(target["target"]["committedDate"].as_str());
+            Some(Release {
+                kind: ReleaseKind::Tag,
+                name: name.to_string(),
+                date: date.map(String::from),
+                sha: target["oid"].as_str().map(String::from),
+            })
+        })
+        .collect())
+}
+
+/// Merges release lists, dropping duplicates, newest first and capped.
+pub fn merge(mut releases: Vec<Release>, more: Vec<Release>) -> Vec<Release> {
+    for release in more {
+        if !releases.iter().any(|r| r.kind == release.kind && r.name == release.name && r.sha == release.sha) {
+            releases.push(release);
+        }
+    }
+    releases.sort_by(|a, b| b.date.cmp(&a.date));
+    releases.truncate(MAX_RELEASES);
+    releases
+}
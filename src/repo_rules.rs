@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::{authorship::glob_match, scan::OwnedRepository};
+
+// ------------------- Repository Rules -------------------
+
+/// Which repositories a deployment scans: `REPO_ALLOWLIST` and
+/// `REPO_DENYLIST`, comma-separated `owner/repo` patterns matched
+/// case-insensitively with `*` as wildcard (`*/sui-move-intro-course`,
+/// `some-org/*`). A bare owner stands for all of its repositories.
+#[derive(Debug, Default)]
+pub struct RepoRules {
+    /// When non-empty, only matching repositories are scanned.
+    allow: Vec<String>,
+    /// Matching repositories are never scanned, even when allowlisted.
+    deny: Vec<String>,
+}
+
+/// A repository left out of a scan, and the rule that did it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedRepository {
+    pub repo: String,
+    pub reason: ExclusionReason,
+    /// The `REPO_DENYLIST` pattern matched (`denylisted` only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    Denylisted,
+    NotAllowlisted,
+    #[serde(other)]
+    Unknown,
+}
+
+/// The process-wide rules, from the environment; invalid lists are ignored
+/// with a warning.
+pub fn rules() -> &'static RepoRules {
+    static RULES: OnceLock<RepoRules> = OnceLock::new();
+    RULES.get_or_init(|| {
+        RepoRules::from_env().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring repository rules: {e}");
+            RepoRules::default()
+        })
+    })
+}
+
+impl RepoRules {
+    pub fn from_env() -> Result<Self, String> {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        Ok(RepoRules {
+            allow: patterns("REPO_ALLOWLIST", &var("REPO_ALLOWLIST"))?,
+            deny: patterns("REPO_DENYLIST", &var("REPO_DENYLIST"))?,
+        })
+    }
+
+    /// Why `repo` (`owner/repo`) is excluded, if it is.
+    pub fn exclusion(&self, repo: &str) -> Option<ExcludedRepository> {
+        let name = repo.to_lowercase();
+        if let Some(pattern) = self.deny.iter().find(|p| glob_match(p, &name)) {
+            return Some(ExcludedRepository {
+                repo: repo.to_string(),
+                reason: ExclusionReason::Denylisted,
+                pattern: Some(pattern.clone()),
+            });
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|p| glob_match(p, &name)) {
+            return Some(ExcludedRepository { repo: repo.to_string(), reason: ExclusionReason::NotAllowlisted, pattern: None });
+        }
+        None
+    }
+
+    /// Removes the excluded repositories from `repositories` and returns them.
+    pub fn apply(&self, repositories: &mut Vec<OwnedRepository>) -> Vec<ExcludedRepository> {
+        let mut excluded = Vec::new();
+        repositories.retain(|repo| match self.exclusion(&repo.name) {
+            Some(exclusion) => {
+                excluded.push(exclusion);
+                false
+            }
+            None => true,
+        });
+        excluded
+    }
+}
+
+fn patterns(name: &str, list: &str) -> Result<Vec<String>, String> {
+    list.split(',')
+        .map(|p| p.trim().to_lowercase())
+        .filter(|p| !p.is_empty())
+        .map(|p| match p.matches('/').count() {
+            0 => Ok(format!("{p}/*")),
+            1 => Ok(p),
+            _ => Err(format!("{name}: `{p}` is not an owner or owner/repo pattern")),
+        })
+        .collect()
+}
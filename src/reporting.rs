@@ -0,0 +1,148 @@
+use sentry::{Breadcrumb, ClientInitGuard, Level, protocol::Value};
+use serde::{Deserialize, Serialize};
+
+// ------------------- Sentry Setup -------------------
+
+/// Initialises Sentry when `SENTRY_DSN` is set. Panics are captured by the
+/// default integrations; the returned guard flushes queued events on drop.
+///
+/// `SENTRY_ENVIRONMENT` optionally tags events with the deployment name.
+pub fn init() -> Option<ClientInitGuard> {
+    let dsn = std::env::var("SENTRY_DSN").ok().filter(|d| !d.is_empty())?;
+
+    let mut options = sentry::ClientOptions::new();
+    options.release = sentry::release_name!();
+    options.environment = std::env::var("SENTRY_ENVIRONMENT").ok().map(Into::into);
+
+    let guard = sentry::init((dsn, options));
+
+    guard.is_enabled().then_some(guard)
+}
+
+// ------------------- Context Helpers -------------------
+
+/// Records a non-success GitHub response as a breadcrumb so it is attached to
+/// any event captured later in the same request. Inside [`private`], the URL
+/// is left out since it names the repository.
+pub fn github_response(url: &str, status: reqwest::StatusCode) {
+    let url = if in_private_scan() { PRIVATE_LABEL } else { url };
+    sentry::add_breadcrumb(Breadcrumb {
+        category: Some("github".into()),
+        message: Some(format!("{status} {url}")),
+        level: if status.is_server_error() { Level::Error } else { Level::Warning },
+        data: [
+            ("url".to_string(), Value::from(url)),
+            ("status_code".to_string(), Value::from(status.as_u16())),
+        ]
+        .into_iter()
+        .collect(),
+        ..Default::default()
+    });
+}
+
+/// Captures a failed scan, tagged with the scanned username and, when the
+/// failure came from an HTTP call, the GitHub status code.
+/// Also published as a `scan_failed` notification.
+pub fn scan_failure(username: &str, err: &(dyn std::error::Error + 'static)) {
+    let status = err
+        .downcast_ref::<reqwest::Error>()
+        .and_then(|e| e.status())
+        .map(|s| s.as_u16().to_string());
+
+    sentry::with_scope(
+        |scope| {
+            scope.set_tag("username", username);
+            if let Some(status) = &status {
+                scope.set_tag("github.status_code", status);
+            }
+        },
+        || sentry::capture_error(err),
+    );
+
+    let summary = format!("Scan of {username} failed: {err}");
+    let details = serde_json::json!({ "error": err.to_string(), "github_status": status });
+    crate::notify::publish(crate::notify::Event::new(crate::notify::EventKind::ScanFailed, summary, details).username(username));
+}
+
+// ------------------- Private Repositories -------------------
+
+/// Stands in for repository names and URLs inside [`private`].
+const PRIVATE_LABEL: &str = "<private>";
+
+tokio::task_local! {
+    static PRIVATE: ();
+}
+
+/// Runs `future`, a scan of private repositories, so that spans, logs and
+/// breadcrumbs do not record their names.
+pub async fn private<F: Future>(future: F) -> F::Output {
+    PRIVATE.scope((), future).await
+}
+
+fn in_private_scan() -> bool {
+    PRIVATE.try_with(|_| ()).is_ok()
+}
+
+/// `repo` as spans and logs may record it: `<private>` inside [`private`].
+/// Requests still need the real name.
+pub fn repo_label(repo: &str) -> &str {
+    if in_private_scan() { PRIVATE_LABEL } else { repo }
+}
+
+// ------------------- Error Kinds -------------------
+
+/// Machine-readable cause of a failed scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    RateLimited,
+    NotFound,
+    Unauthorized,
+    /// The token lacks an OAuth scope the call needs.
+    InsufficientScopes,
+    Timeout,
+    /// Stopped by `POST /scans/{id}/cancel`.
+    Cancelled,
+    /// GitHub or a fullnode could not be reached.
+    Unreachable,
+    /// Any other upstream failure.
+    Upstream,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Classifies `err` from the HTTP error when there is one, else from the
+/// GitHub error text.
+pub fn error_kind(err: &(dyn std::error::Error + 'static)) -> ErrorKind {
+    if err.is::<crate::github::ScopeError>() {
+        return ErrorKind::InsufficientScopes;
+    }
+    if err.is::<crate::queue::Cancelled>() {
+        return ErrorKind::Cancelled;
+    }
+    if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+        if e.is_timeout() {
+            return ErrorKind::Timeout;
+        }
+        if e.is_connect() {
+            return ErrorKind::Unreachable;
+        }
+        match e.status().map(|s| s.as_u16()) {
+            Some(401) => return ErrorKind::Unauthorized,
+            Some(403 | 429) => return ErrorKind::RateLimited,
+            Some(404) => return ErrorKind::NotFound,
+            _ => {}
+        }
+    }
+
+    let message = err.to_string().to_lowercase();
+    if message.contains("rate limit") || message.contains("rate_limited") {
+        ErrorKind::RateLimited
+    } else if message.contains("not_found") || message.contains("not found") || message.contains("could not resolve") {
+        ErrorKind::NotFound
+    } else if message.contains("bad credentials") || message.contains("401") {
+        ErrorKind::Unauthorized
+    } else {
+        ErrorKind::Upstream
+    }
+}
@@ -0,0 +1,129 @@
+use reqwest::Client;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::Instrument;
+
+use crate::{
+    detect, github, pacing, reporting,
+    scan::{self, OwnedRepository, RepositoryWithCommits, ScanLimits},
+};
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Serialize)]
+pub struct ResolvedAccount {
+    pub login: String,
+    pub commit_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailResolution {
+    pub email: String,
+    /// Total matches reported by the search API; only the first page is inspected.
+    pub total_commits_found: u64,
+    pub commits_inspected: usize,
+    pub accounts: Vec<ResolvedAccount>,
+    pub move_repositories: Vec<RepositoryWithCommits>,
+}
+
+/// Commits inspected per lookup (a single search page).
+const SEARCH_PAGE_SIZE: u32 = 100;
+
+/// Distinct repositories whose trees are run through the detector pipeline.
+const MAX_REPOS_CHECKED: usize = 20;
+
+// ------------------- Core Logic -------------------
+
+/// Finds the GitHub accounts and Move repositories associated with a commit
+/// email using the commit search API.
+#[tracing::instrument(name = "resolve_email", skip(client, token))]
+pub async fn resolve_email(
+    client: &Client,
+    token: &str,
+    email: &str,
+) -> Result<EmailResolution, Box<dyn std::error::Error + Send + Sync>> {
+    let search_url = format!(
+        "https://api.github.com/search/commits?q={}&per_page={}",
+        urlencoding::encode(&format!("author-email:{email}")),
+        SEARCH_PAGE_SIZE
+    );
+    let resp = github::send(
+        github::EndpointClass::Search,
+        github::get(client, token, &search_url)
+            .header("Accept", "application/vnd.github+json"),
+    )
+    .instrument(tracing::info_span!("github.search_commits"))
+    .await?;
+
+    if !resp.status().is_success() {
+        reporting::github_response(&search_url, resp.status());
+        return Err(format!("GitHub commit search failed with {}", resp.status()).into());
+    }
+
+    let results: serde_json::Value = resp.json().await?;
+    let items = results["items"].as_array().cloned().unwrap_or_default();
+
+    let mut accounts: BTreeMap<String, u32> = BTreeMap::new();
+    // repo full name -> (html url, matching commits)
+    let mut repos: BTreeMap<String, (String, u32)> = BTreeMap::new();
+
+    for item in &items {
+        if let Some(login) = item["author"]["login"].as_str() {
+            *accounts.entry(login.to_string()).or_default() += 1;
+        }
+        if let Some(name) = item["repository"]["full_name"].as_str() {
+            let url = item["repository"]["html_url"].as_str().unwrap_or_default().to_string();
+            repos.entry(name.to_string()).or_insert((url, 0)).1 += 1;
+        }
+    }
+
+    let max_tree_entries = ScanLimits::ceiling().max_tree_entries;
+    let runtime = crate::state::runtime();
+    let pipeline = &runtime.pipeline;
+    let mut move_repositories = Vec::new();
+    let mut candidates: Vec<_> = repos.into_iter().collect();
+    candidates.sort_by_key(|(_, (_, count))| std::cmp::Reverse(*count));
+
+    for (name, (url, commit_count)) in candidates.into_iter().take(MAX_REPOS_CHECKED) {
+        let entries = scan::fetch_tree(client, token, &name, "HEAD", max_tree_entries).await?;
+        let repo = OwnedRepository {
+            name,
+            url,
+            default_branch: "HEAD".to_string(),
+            description: None,
+            topics: Vec::new(),
+            pushed_at: None,
+            disk_usage_kb: None,
+        };
+        let detections = pipeline.run(&detect::RepoContext { client, token, repo: &repo, entries: &entries }).await?;
+        if !detections.is_empty() {
+            let manifests = entries.iter().filter(|e| scan::is_manifest(&e.path)).cloned().collect();
+            let move_files = entries.into_iter().filter(|e| e.path.ends_with(".move")).collect();
+            move_repositories.push(RepositoryWithCommits {
+                repo_name: repo.name,
+                repo_url: repo.url,
+                commit_count,
+                detections,
+                move_files,
+                manifests,
+                ..Default::default()
+            });
+        }
+
+        pacing::pause().await;
+    }
+
+    let mut accounts: Vec<_> = accounts
+        .into_iter()
+        .map(|(login, commit_count)| ResolvedAccount { login, commit_count })
+        .collect();
+    accounts.sort_by_key(|a| std::cmp::Reverse(a.commit_count));
+
+    Ok(EmailResolution {
+        email: email.to_string(),
+        total_commits_found: results["total_count"].as_u64().unwrap_or(0),
+        commits_inspected: items.len(),
+        accounts,
+        move_repositories,
+    })
+}
@@ -0,0 +1,211 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    admin, classify,
+    scan::{self, OwnedRepository, TreeEntry, UserMoveFilesResponse},
+    storage::{ScanWithInputs, Storage},
+    templates,
+};
+
+// ------------------- Changelog -------------------
+
+/// One version of the built-in detection and analysis rules.
+#[derive(Debug, Clone, Serialize)]
+pub struct RulesetChange {
+    pub version: u32,
+    pub summary: &'static str,
+}
+
+/// Every built-in ruleset version, oldest first. Append an entry whenever a
+/// detector, the built-in category keywords or template matching change
+/// what a scan reports for the same GitHub data.
+pub const CHANGELOG: &[RulesetChange] = &[RulesetChange {
+    version: 1,
+    summary: "Detectors move_files, move_toml, sdk and onchain; keyword categories defi, nft, gaming, infra and tutorial \
+              over topics, description and module names; template copies flagged at TEMPLATE_MATCH_THRESHOLD of verbatim Move files",
+}];
+
+/// The rules a result was produced under: the built-in version plus the
+/// deployment's own detector and category configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RulesetStamp {
+    pub version: u32,
+    /// Enabled detectors (`SCAN_DETECTORS`).
+    pub detectors: Vec<String>,
+    /// Digest of the category keywords in use, so `CATEGORY_RULES_PATH`
+    /// edits show up as a change.
+    pub category_rules: String,
+}
+
+/// The stamp of the rules this process applies.
+pub fn current() -> RulesetStamp {
+    let keywords = serde_json::to_vec(classify::ruleset()).unwrap_or_default();
+    let digest = ring::digest::digest(&ring::digest::SHA256, &keywords);
+    RulesetStamp {
+        version: CHANGELOG.last().map_or(0, |change| change.version),
+        detectors: crate::state::runtime().pipeline.names().into_iter().map(String::from).collect(),
+        category_rules: hex::encode(&digest.as_ref()[..6]),
+    }
+}
+
+// ------------------- Inputs -------------------
+
+/// What the analysis phase read from GitHub for one repository, stored
+/// beside each scan so the analysis can be re-run without fetching again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryInputs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<String>,
+    /// `(path, blob sha)` of every `.move` file.
+    pub move_files: Vec<(String, String)>,
+}
+
+/// Repository name -> inputs, for the repositories of one scan.
+pub type ScanInputs = BTreeMap<String, RepositoryInputs>;
+
+/// The analysis inputs of `result`. Repositories carried over unchanged from
+/// an earlier scan have none.
+pub fn inputs(result: &UserMoveFilesResponse) -> ScanInputs {
+    result
+        .repositories
+        .iter()
+        .filter(|r| !r.move_files.is_empty())
+        .map(|r| {
+            let inputs = RepositoryInputs {
+                description: r.description.clone(),
+                topics: r.topics.clone(),
+                move_files: r.move_files.iter().map(|f| (f.path.clone(), f.sha.clone())).collect(),
+            };
+            (r.repo_name.clone(), inputs)
+        })
+        .collect()
+}
+
+// ------------------- Reanalysis -------------------
+
+/// Re-runs the analysis phase of a stored result from its `inputs` under the
+/// current rules: categories, template matches, category counts and owner
+/// subtotals, then stamps the current ruleset. Which repositories count and
+/// their commits come from GitHub and stay as stored. Returns how many
+/// repositories had no inputs and kept their previous analysis.
+pub fn reanalyze(
+    storage: &Storage,
+    result: &mut UserMoveFilesResponse,
+    inputs: &ScanInputs,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let rules = classify::ruleset();
+    let mut without_inputs = 0;
+    for repo in &mut result.repositories {
+        let Some(stored) = inputs.get(&repo.repo_name) else {
+            without_inputs += 1;
+            continue;
+        };
+        repo.move_files = stored.move_files.iter().map(|(path, sha)| TreeEntry { path: path.clone(), sha: sha.clone() }).collect();
+        let owned = OwnedRepository {
+            name: repo.repo_name.clone(),
+            url: repo.repo_url.clone(),
+            default_branch: String::new(),
+            description: stored.description.clone(),
+            topics: stored.topics.clone(),
+            pushed_at: None,
+            disk_usage_kb: None,
+        };
+        repo.categories = classify::classify(rules, &owned, &repo.move_files);
+        repo.template_match = None;
+    }
+    templates::flag_template_copies(storage, result)?;
+
+    result.category_counts = scan::category_counts(&result.repositories);
+    result.owners = scan::owner_rollup(&result.repositories);
+    result.ruleset = Some(current());
+    Ok(without_inputs)
+}
+
+// ------------------- Handlers -------------------
+
+#[derive(Debug, Serialize)]
+pub struct RulesetInfo {
+    pub current: RulesetStamp,
+    pub changelog: &'static [RulesetChange],
+}
+
+/// `GET /ruleset`: the rules scans are produced under now, and every
+/// built-in version.
+pub async fn ruleset() -> Json<RulesetInfo> {
+    Json(RulesetInfo { current: current(), changelog: CHANGELOG })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReanalyzeQuery {
+    /// Also re-run scans already stamped with the current rules, e.g. after
+    /// templates were registered.
+    #[serde(default)]
+    all: bool,
+    username: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ReanalyzeReport {
+    pub ruleset: Option<RulesetStamp>,
+    pub scans_examined: usize,
+    pub scans_reanalyzed: usize,
+    /// Scans stored before inputs were kept, left as they are.
+    pub scans_without_inputs: usize,
+    /// Repositories carried over from an earlier scan, which keep their
+    /// previous analysis.
+    pub repositories_without_inputs: usize,
+}
+
+/// Stored scans read per page.
+const PAGE_SIZE: usize = 200;
+
+/// `POST /admin/reanalyze?all=<bool>&username=<github_user>`: recomputes
+/// the analysis of every stored scan, historical ones included, whose
+/// ruleset differs from the current one, without calling GitHub. Scan
+/// times are kept, since the data they describe is unchanged.
+pub async fn reanalyze_handler(
+    headers: HeaderMap,
+    Query(params): Query<ReanalyzeQuery>,
+    State(storage): State<Storage>,
+) -> Result<Json<ReanalyzeReport>, (StatusCode, String)> {
+    admin::require_admin(&headers)?;
+
+    let stamp = current();
+    let username = params.username.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    let mut report = ReanalyzeReport::default();
+    let mut after = 0;
+    loop {
+        let page = storage.scans_with_inputs(after, PAGE_SIZE, username).map_err(admin::internal)?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = last.id;
+        for ScanWithInputs { id, mut result, inputs } in page {
+            report.scans_examined += 1;
+            if !params.all && result.ruleset.as_ref() == Some(&stamp) {
+                continue;
+            }
+            let Some(inputs) = inputs else {
+                report.scans_without_inputs += 1;
+                continue;
+            };
+            report.repositories_without_inputs += reanalyze(&storage, &mut result, &inputs).map_err(admin::internal)?;
+            storage.update_scan_result(id, &result).map_err(admin::internal)?;
+            report.scans_reanalyzed += 1;
+        }
+    }
+    if report.scans_reanalyzed > 0 {
+        storage.reindex_search().map_err(admin::internal)?;
+    }
+    tracing::info!("Re-analysed {} of {} stored scans under ruleset v{}", report.scans_reanalyzed, report.scans_examined, stamp.version);
+    report.ruleset = Some(stamp);
+    Ok(Json(report))
+}
@@ -0,0 +1,1794 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tracing::Instrument;
+
+use crate::{activity, authorship, classify, detect, docs, github, governance, hygiene, i18n::Message, mirror, pacing, releases, repo_rules, reporting};
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Clone)]
+pub struct OwnedRepository {
+    pub name: String,
+    pub url: String,
+    pub default_branch: String,
+    pub description: Option<String>,
+    pub topics: Vec<String>,
+    /// Last push to any branch, used to skip unchanged repositories on re-scans.
+    pub pushed_at: Option<String>,
+    /// GitHub's `diskUsage`, used to cap local clones.
+    pub disk_usage_kb: Option<u64>,
+}
+
+/// One entry of a recursive tree listing; `sha` is the git blob SHA, which is
+/// a content hash of the file.
+#[derive(Debug, Clone, Default)]
+pub struct TreeEntry {
+    pub path: String,
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepositoryWithCommits {
+    pub repo_name: String,
+    pub repo_url: String,
+    pub commit_count: u32,
+    /// Lines of `.move` code blamed to the user (deep mode only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_lines_authored: Option<u32>,
+    /// Commits by bots and automation left out of `commit_count`.
+    #[serde(default, skip_serializing_if = "crate::authorship::is_zero")]
+    pub bot_commits_excluded: u32,
+    /// Date of the user's earliest commit touching Move code here (not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_since: Option<String>,
+    /// Date of the user's latest counted commit here (not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
+    /// Distinct days (`YYYY-MM-DD`, UTC) with a counted commit, kept for the
+    /// activity metrics of later re-scans (not in quick mode).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commit_days: Vec<String>,
+    /// Newest counted commit; re-scans only count commits after it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_cursor: Option<CommitCursor>,
+    /// Issues the user opened here (not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issues_opened: Option<u32>,
+    /// Pull requests by others that the user reviewed here (not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews_given: Option<u32>,
+    /// Merge commits left out of `commit_count` (`exclude_merges=true`).
+    #[serde(default, skip_serializing_if = "crate::authorship::is_zero")]
+    pub merge_commits_excluded: u32,
+    /// The user's merged pull requests into this repository (`count_merged_prs=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merged_pull_requests: Option<u32>,
+    /// Commit message quality of the counted commits (`commit_hygiene=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_hygiene: Option<crate::hygiene::CommitHygiene>,
+    /// Declared Move edition and Move 2024 features used (not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_edition: Option<crate::edition::MoveEdition>,
+    /// `name`s of the repository's `Move.toml` packages, read with the edition.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub package_names: Vec<String>,
+    /// Version tags and publish commits, newest first (not in quick mode).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub releases: Vec<crate::releases::Release>,
+    /// How certain the commit attribution is (not computed in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<crate::authorship::AuthorshipConfidence>,
+    /// Above `SCAN_LARGE_REPO_KB`: scanned through the API with a capped tree
+    /// and without blame, so counts may be incomplete.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub size_downgraded: bool,
+    /// Trees and history were read from a local clone (`strategy=clone`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cloned: bool,
+    /// Categories from the keyword ruleset (`defi`, `nft`, `gaming`, `infra`, `tutorial`, ...).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Set when the repository's Move files are copies of a registered template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_match: Option<crate::templates::TemplateMatch>,
+    /// The repository's `.move` files, used for template and similarity matching.
+    #[serde(skip)]
+    pub move_files: Vec<TreeEntry>,
+    /// GitHub description and topics, kept with `move_files` as the inputs
+    /// a later re-analysis classifies from.
+    #[serde(skip)]
+    pub description: Option<String>,
+    #[serde(skip)]
+    pub topics: Vec<String>,
+    /// `Move.toml` manifests, one per package.
+    #[serde(skip)]
+    pub manifests: Vec<TreeEntry>,
+    /// Detectors that flagged the repository, with their evidence.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub detections: Vec<crate::detect::Detection>,
+    /// Why the repository was classified as Sui Move: matched paths, and
+    /// outside quick mode `Move.toml` dependency lines and `Move.lock` addresses.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub evidence: Vec<crate::detect::Evidence>,
+    /// Sui CLI, localnet and suibase artifacts in the tree: client configs,
+    /// keystore files (path only), genesis and localnet configs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tooling_evidence: Vec<crate::detect::ToolingEvidence>,
+    /// Move ecosystems the evidence points to, when `MOVE_ECOSYSTEMS`
+    /// serves several. Computed per response, never stored.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ecosystems: Vec<crate::ecosystems::MoveEcosystem>,
+    /// Build results per package (only when requested with `verify_build=true`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<crate::verify::PackageBuild>,
+    /// Reviewer notes and overrides on this repository, merged per response.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<crate::annotations::Annotation>,
+}
+
+impl RepositoryWithCommits {
+    /// Original work unless it matched a template, with a reviewer's
+    /// `boilerplate` or `original` annotation taking precedence.
+    pub fn is_original(&self) -> bool {
+        crate::annotations::originality(&self.annotations).unwrap_or(self.template_match.is_none())
+    }
+}
+
+/// Version of the response contract, bumped on breaking changes to field
+/// names, types or enum values. Additive fields do not change it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+fn schema_version() -> u32 {
+    SCHEMA_VERSION
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserMoveFilesResponse {
+    /// Always the running release's version; stored results are re-serialised
+    /// in the current shape.
+    #[serde(skip_deserializing, default = "schema_version")]
+    pub schema_version: u32,
+    pub username: String,
+    /// Additional accounts merged into this result (`username=alice,alice-work`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    pub has_move_files: bool,
+    pub total_repositories: usize,
+    pub total_commits: u32,
+    #[serde(default, skip_serializing_if = "crate::authorship::is_zero")]
+    pub bot_commits_excluded: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issues_opened: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews_given: Option<u32>,
+    /// Earliest `move_since` across repositories: when the user started writing Move.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_since: Option<String>,
+    /// Latest `last_commit_at` across repositories.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
+    /// Commit streak and consistency across the Move repositories (not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<crate::activity::Activity>,
+    /// Merged pull requests across the Move repositories (`count_merged_prs=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_merged_pull_requests: Option<u32>,
+    /// Commit message quality across the Move repositories (`commit_hygiene=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_hygiene: Option<crate::hygiene::CommitHygiene>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_lines_authored: Option<u32>,
+    /// Number of Move repositories in each category.
+    #[serde(default)]
+    pub category_counts: std::collections::BTreeMap<String, usize>,
+    /// Version of the detection and analysis rules the result was produced
+    /// under, see `GET /ruleset`. Absent on scans stored before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ruleset: Option<crate::ruleset::RulesetStamp>,
+    /// Subtotals per repository owner, most commits first, when the Move
+    /// repositories span several owners (aliases or listed `repos`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<OwnerRollup>,
+    /// Cross-user Move file similarity (only when requested with `similarity=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity_matches: Option<Vec<crate::similarity::SimilarityMatch>>,
+    /// Sui-relevant organizations (`SUI_ORGS`) any scanned account publicly belongs to.
+    #[serde(default)]
+    pub sui_organizations: Vec<String>,
+    /// Sui Improvement Proposal activity (not checked in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub governance: Option<crate::governance::Governance>,
+    /// Commits to documentation and tutorial repositories (`DOC_RULES_PATH`; not in quick mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation_contributions: Option<crate::docs::DocumentationContributions>,
+    /// On-chain activity of the wallet addresses bound to the scanned accounts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_activity: Option<crate::chain::ChainActivity>,
+    /// Reviewer notes and overrides on the user, merged per response.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<crate::annotations::Annotation>,
+    /// A reviewer confirmed the accounts belong to the person.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub identity_confirmed: bool,
+    /// Whether the scan meets the selected policy (`policy=<name>`, from
+    /// `VERDICT_POLICY_PATH`), rule by rule. Evaluated per response, never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<crate::policy::Verdict>,
+    /// The ecosystem the response was narrowed to (`ecosystem=<name>`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecosystem: Option<crate::ecosystems::MoveEcosystem>,
+    /// The policy evaluated per Move ecosystem, when `MOVE_ECOSYSTEMS`
+    /// serves several.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ecosystems: Vec<crate::ecosystems::EcosystemVerdict>,
+    /// Certificate issued for a passing verdict, see `GET /certificates/<id>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub certificate_id: Option<String>,
+    /// Content ID of the archived canonical report (only when requested with `archive=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive: Option<crate::archive::ArchiveReceipt>,
+    /// Per-stage timing and GitHub request counts (only when requested with `debug=true`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Diagnostics>,
+    /// `pushedAt` of every repository whose tree was checked, keyed by name;
+    /// stored beside the scan for incremental re-scans.
+    #[serde(skip)]
+    pub repo_cursors: Vec<(String, String)>,
+    /// When served from the scan cache: the time the cached scan was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_at: Option<u64>,
+    /// True when the cached result is past its TTL and a refresh is running.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// For results without Move code: until when the negative result is cached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative_cached_until: Option<u64>,
+    /// Repositories left out by the deployment's `REPO_ALLOWLIST` and `REPO_DENYLIST`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_repositories: Vec<crate::repo_rules::ExcludedRepository>,
+    /// Repositories above `SCAN_MAX_REPO_KB`, not scanned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped_due_to_size: Vec<SizeSkip>,
+    pub repositories: Vec<RepositoryWithCommits>,
+    pub mode: ScanMode,
+    pub limits: ScanLimits,
+}
+
+/// Number of `repositories` in each category.
+pub fn category_counts(repositories: &[RepositoryWithCommits]) -> std::collections::BTreeMap<String, usize> {
+    let mut counts = std::collections::BTreeMap::new();
+    for category in repositories.iter().flat_map(|r| &r.categories) {
+        *counts.entry(category.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Recomputes the totals of `result` over its repositories after some were
+/// left out. Totals the scan did not measure stay unmeasured.
+pub fn retotal(result: &mut UserMoveFilesResponse) {
+    let repositories = &result.repositories;
+    let sum = |value: fn(&RepositoryWithCommits) -> Option<u32>| repositories.iter().filter_map(value).sum::<u32>();
+    result.has_move_files = !repositories.is_empty();
+    result.total_repositories = repositories.len();
+    result.total_commits = repositories.iter().map(|r| r.commit_count).sum();
+    result.bot_commits_excluded = repositories.iter().map(|r| r.bot_commits_excluded).sum();
+    result.issues_opened = result.issues_opened.map(|_| sum(|r| r.issues_opened));
+    result.reviews_given = result.reviews_given.map(|_| sum(|r| r.reviews_given));
+    result.total_merged_pull_requests = result.total_merged_pull_requests.map(|_| sum(|r| r.merged_pull_requests));
+    result.move_lines_authored = result.move_lines_authored.map(|_| sum(|r| r.move_lines_authored));
+    result.move_since = repositories.iter().filter_map(|r| r.move_since.clone()).min();
+    result.last_commit_at = repositories.iter().filter_map(|r| r.last_commit_at.clone()).max();
+    if result.mode != ScanMode::Quick {
+        result.activity = activity::activity(repositories.iter().flat_map(|r| &r.commit_days), crate::storage::now_secs());
+    }
+    if result.commit_hygiene.is_some() {
+        result.commit_hygiene = hygiene::total(repositories.iter().filter_map(|r| r.commit_hygiene.as_ref()));
+    }
+    result.category_counts = category_counts(repositories);
+    result.owners = owner_rollup(repositories);
+}
+
+/// The Move repositories, commits and blamed Move lines under one owner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnerRollup {
+    pub owner: String,
+    pub repositories: usize,
+    pub commits: u32,
+    /// Deep mode only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_lines_authored: Option<u32>,
+}
+
+/// Groups `repositories` by the owner in their `owner/name`; empty when
+/// they all share one owner, whose subtotals would repeat the totals.
+pub fn owner_rollup(repositories: &[RepositoryWithCommits]) -> Vec<OwnerRollup> {
+    let mut owners: Vec<OwnerRollup> = Vec::new();
+    for repo in repositories {
+        let owner = repo.repo_name.split_once('/').map_or(repo.repo_name.as_str(), |(owner, _)| owner);
+        let at = match owners.iter().position(|o| o.owner.eq_ignore_ascii_case(owner)) {
+            Some(at) => at,
+            None => {
+                owners.push(OwnerRollup { owner: owner.to_string(), repositories: 0, commits: 0, move_lines_authored: None });
+                owners.len() - 1
+            }
+        };
+        let rollup = &mut owners[at];
+        rollup.repositories += 1;
+        rollup.commits += repo.commit_count;
+        if let Some(lines) = repo.move_lines_authored {
+            rollup.move_lines_authored = Some(rollup.move_lines_authored.unwrap_or(0) + lines);
+        }
+    }
+    if owners.len() < 2 {
+        return Vec::new();
+    }
+    owners.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.owner.cmp(&b.owner)));
+    owners
+}
+
+/// The newest commit counted in a repository, by committer date (the date
+/// the commits API `since` filter uses).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitCursor {
+    pub sha: String,
+    pub committed_at: String,
+}
+
+/// A stored scan and its repository cursors, from which a re-scan only
+/// re-checks what changed.
+#[derive(Debug)]
+pub struct Snapshot {
+    pub result: UserMoveFilesResponse,
+    /// Repository name (lowercase) -> `pushedAt` when it was last checked.
+    pub cursors: HashMap<String, String>,
+}
+
+impl Snapshot {
+    fn unchanged(&self, repo: &OwnedRepository) -> bool {
+        repo.pushed_at.is_some() && self.cursors.get(&repo.name.to_lowercase()) == repo.pushed_at.as_ref()
+    }
+
+    fn take_repository(&mut self, name: &str) -> Option<RepositoryWithCommits> {
+        let at = self.result.repositories.iter().position(|r| r.repo_name.eq_ignore_ascii_case(name))?;
+        Some(self.result.repositories.swap_remove(at))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanMode {
+    /// Enumerate, check every tree and count commits.
+    #[default]
+    Full,
+    /// Stop at the first repository containing a `.move` file and skip commit
+    /// counting; `commit_count` is reported as 0.
+    Quick,
+    /// Full scan plus GraphQL blame of every `.move` file, reporting
+    /// `move_lines_authored`.
+    Deep,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScanOptions {
+    pub mode: ScanMode,
+    pub limits: ScanLimits,
+    /// Leave merge commits (more than one parent) out of `commit_count`.
+    pub exclude_merges: bool,
+    /// Also count the user's merged pull requests per repository, which
+    /// credits squash-merged work that raw commit counts miss.
+    pub count_merged_prs: bool,
+    /// Also measure commit message quality (`commit_hygiene`).
+    pub commit_hygiene: bool,
+    pub strategy: ScanStrategy,
+}
+
+/// Where trees and commit history are read from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanStrategy {
+    /// The REST Git Trees and commits APIs.
+    #[default]
+    Api,
+    /// Blobless local clones (`mirror`), falling back to the API for
+    /// repositories that are too large or fail to clone. Quick scans always
+    /// use the API.
+    Clone,
+}
+
+impl ScanOptions {
+    pub fn new(mode: ScanMode, limits: ScanLimits) -> Self {
+        ScanOptions {
+            mode,
+            limits,
+            exclude_merges: false,
+            count_merged_prs: false,
+            commit_hygiene: false,
+            strategy: ScanStrategy::Api,
+        }
+    }
+
+    /// No option changes the result beyond `mode` and `limits`, so a stored
+    /// scan can stand in for it.
+    pub fn is_plain(&self) -> bool {
+        !self.exclude_merges && !self.count_merged_prs && !self.commit_hygiene && self.strategy == ScanStrategy::Api
+    }
+}
+
+/// A repository whose `diskUsage` is above `SCAN_MAX_REPO_KB`, left
+/// unscanned so one monorepo cannot stall the whole scan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeSkip {
+    pub repo: String,
+    pub disk_usage_kb: u64,
+    pub limit_kb: u64,
+}
+
+/// Repositories above this many KB are skipped.
+const DEFAULT_MAX_REPO_KB: u64 = 5_000_000;
+
+/// Repositories above this many KB are scanned in a reduced way: through the
+/// API even with `strategy=clone`, with at most `LARGE_REPO_MAX_TREE_ENTRIES`
+/// tree entries and without blame.
+const DEFAULT_LARGE_REPO_KB: u64 = 1_000_000;
+const LARGE_REPO_MAX_TREE_ENTRIES: usize = 20_000;
+
+/// Blobs larger than this are never downloaded; Move sources and manifests
+/// are far smaller, so anything above is generated or binary.
+const MAX_BLOB_BYTES: u64 = 1_000_000;
+
+/// The environment variable `name` parsed as a `T`, or `default` when it is
+/// unset or does not parse.
+pub fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+/// Removes the repositories above `SCAN_MAX_REPO_KB` and returns them.
+fn skip_oversized(repositories: &mut Vec<OwnedRepository>) -> Vec<SizeSkip> {
+    let limit_kb = env_or("SCAN_MAX_REPO_KB", DEFAULT_MAX_REPO_KB);
+    let mut skipped = Vec::new();
+    repositories.retain(|repo| match repo.disk_usage_kb.filter(|kb| *kb > limit_kb) {
+        Some(disk_usage_kb) => {
+            tracing::info!("Skipping {} ({disk_usage_kb} KB, above {limit_kb} KB)", reporting::repo_label(&repo.name));
+            skipped.push(SizeSkip { repo: repo.name.clone(), disk_usage_kb, limit_kb });
+            false
+        }
+        None => true,
+    });
+    skipped
+}
+
+/// Whether `repo` is above `SCAN_LARGE_REPO_KB` and gets the reduced scan.
+fn is_large(repo: &OwnedRepository) -> bool {
+    repo.disk_usage_kb.is_some_and(|kb| kb > env_or("SCAN_LARGE_REPO_KB", DEFAULT_LARGE_REPO_KB))
+}
+
+/// How far a scan is allowed to go. Client-requested values are clamped to
+/// the server ceilings, which default to generous values and can be lowered
+/// with `MAX_REPOS_CEILING`, `MAX_TREE_ENTRIES_CEILING` and
+/// `MAX_COMMIT_PAGES_CEILING`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanLimits {
+    pub max_repos: usize,
+    pub max_tree_entries: usize,
+    pub max_commit_pages: u32,
+    /// Files blamed per repository in deep mode; ceiling only.
+    pub max_blame_files: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiCallEstimate {
+    pub graphql: u32,
+    pub trees: u32,
+    pub commits_min: u32,
+    pub total_min: u32,
+}
+
+/// Where a scan spent its time. GitHub requests are only counted when the
+/// scan runs inside [`github::counting_requests`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub total_ms: u64,
+    pub total_github_requests: u32,
+    /// Unchanged repositories carried over from the previous scan.
+    #[serde(default, skip_serializing_if = "crate::authorship::is_zero")]
+    pub repositories_reused: u32,
+    pub stages: Vec<StageDiagnostics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageDiagnostics {
+    pub stage: String,
+    pub elapsed_ms: u64,
+    pub github_requests: u32,
+}
+
+/// Start of a measured stage, see [`Diagnostics::record`].
+pub struct Checkpoint {
+    started: std::time::Instant,
+    requests: u32,
+}
+
+impl Checkpoint {
+    pub fn now() -> Self {
+        Checkpoint { started: std::time::Instant::now(), requests: github::requests_made() }
+    }
+}
+
+impl UserMoveFilesResponse {
+    /// Records a post-processing stage when diagnostics were requested.
+    pub fn record_stage(&mut self, stage: &str, since: &Checkpoint) {
+        if let Some(diagnostics) = self.diagnostics.as_mut() {
+            diagnostics.record(stage, since);
+        }
+    }
+}
+
+impl Diagnostics {
+    /// Adds the time and requests since `since` to `stage`, accumulating
+    /// across repeated runs of the same stage.
+    pub fn record(&mut self, stage: &str, since: &Checkpoint) {
+        let elapsed_ms = since.started.elapsed().as_millis() as u64;
+        let github_requests = github::requests_made().saturating_sub(since.requests);
+
+        self.total_ms += elapsed_ms;
+        self.total_github_requests += github_requests;
+        crate::deadline::note_stage(stage, elapsed_ms, github_requests);
+        match self.stages.iter_mut().find(|s| s.stage == stage) {
+            Some(existing) => {
+                existing.elapsed_ms += elapsed_ms;
+                existing.github_requests += github_requests;
+            }
+            None => self.stages.push(StageDiagnostics { stage: stage.to_string(), elapsed_ms, github_requests }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanEstimate {
+    pub schema_version: u32,
+    pub username: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    pub estimate: bool,
+    pub owned_repositories: usize,
+    pub api_calls: ApiCallEstimate,
+    pub estimated_seconds: f64,
+}
+
+impl ScanLimits {
+    pub fn ceiling() -> Self {
+        ScanLimits {
+            max_repos: env_or("MAX_REPOS_CEILING", 1000),
+            max_tree_entries: env_or("MAX_TREE_ENTRIES_CEILING", 100_000),
+            max_commit_pages: env_or("MAX_COMMIT_PAGES_CEILING", 100),
+            max_blame_files: env_or("MAX_BLAME_FILES_CEILING", 200),
+        }
+    }
+
+    pub fn requested(
+        max_repos: Option<usize>,
+        max_tree_entries: Option<usize>,
+        max_commit_pages: Option<u32>,
+    ) -> Self {
+        let ceiling = Self::ceiling();
+        ScanLimits {
+            max_repos: max_repos.map_or(ceiling.max_repos, |v| v.min(ceiling.max_repos)),
+            max_tree_entries: max_tree_entries.map_or(ceiling.max_tree_entries, |v| v.min(ceiling.max_tree_entries)),
+            max_commit_pages: max_commit_pages.map_or(ceiling.max_commit_pages, |v| v.min(ceiling.max_commit_pages)),
+            max_blame_files: ceiling.max_blame_files,
+        }
+    }
+}
+
+/// Rough round-trip time of a single GitHub call, used only for estimates.
+const ASSUMED_CALL_LATENCY_SECS: f64 = 0.4;
+
+/// Organizations treated as Sui-relevant when `SUI_ORGS` is not set.
+const DEFAULT_SUI_ORGS: &str = "MystenLabs,sui-foundation";
+
+/// Maximum number of accounts that can be merged into one scan.
+pub const MAX_ALIASES: usize = 5;
+
+/// Splits a `username` query value such as `alice,alice-work` into distinct
+/// accounts, the first being the primary one. Every account must be a
+/// valid GitHub login.
+pub fn parse_aliases(raw: &str) -> Result<Vec<String>, crate::i18n::Message> {
+    let mut accounts: Vec<String> = Vec::new();
+    for name in raw.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        if !github::is_valid_login(name) {
+            return Err(Message::new("username_invalid").arg("username", name));
+        }
+        if !accounts.iter().any(|a| a.eq_ignore_ascii_case(name)) {
+            accounts.push(name.to_string());
+        }
+    }
+
+    match accounts.len() {
+        0 => Err(Message::new("username_empty")),
+        n if n > MAX_ALIASES => Err(Message::new("too_many_aliases").arg("max", MAX_ALIASES).arg("count", n)),
+        _ => Ok(accounts),
+    }
+}
+
+// ------------------- Core Logic -------------------
+
+/// Repository fields every scan reads, see [`owned_repository`].
+pub const REPOSITORY_FIELDS: &str = r#"
+            nameWithOwner
+            url
+            description
+            defaultBranchRef { name }
+            pushedAt
+            diskUsage
+            repositoryTopics(first:20) { nodes { topic { name } } }
+"#;
+
+pub fn owned_repository(node: &serde_json::Value) -> OwnedRepository {
+    OwnedRepository {
+        name: node["nameWithOwner"].as_str().unwrap_or_default().to_string(),
+        url: node["url"].as_str().unwrap_or_default().to_string(),
+        default_branch: node["defaultBranchRef"]["name"].as_str().unwrap_or("main").to_string(),
+        description: node["description"].as_str().map(|d| d.to_string()),
+        pushed_at: node["pushedAt"].as_str().map(|d| d.to_string()),
+        disk_usage_kb: node["diskUsage"].as_u64(),
+        topics: node["repositoryTopics"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|t| t["topic"]["name"].as_str().map(|n| n.to_string()))
+            .collect(),
+    }
+}
+
+/// Looks up client-supplied `owner/name` repositories, whoever owns them.
+/// Returns the repositories found and the names GitHub does not know.
+#[tracing::instrument(name = "scan.listed_repositories", skip(client, token))]
+pub async fn fetch_listed_repositories(
+    client: &Client,
+    token: &str,
+    names: &[String],
+) -> Result<(Vec<OwnedRepository>, Vec<String>), Box<dyn std::error::Error + Send + Sync>> {
+    let query = format!(
+        r#"
+    query($owner:String!, $name:String!) {{
+      repository(owner:$owner, name:$name) {{ {REPOSITORY_FIELDS} }}
+    }}
+    "#
+    );
+
+    let mut repositories = Vec::new();
+    let mut not_found = Vec::new();
+    for name in names {
+        let Some((owner, repo)) = name.split_once('/') else {
+            not_found.push(name.clone());
+            continue;
+        };
+        let vars = serde_json::json!({ "owner": owner, "name": repo });
+        match github::graphql_request(client, token, &query, Some(vars)).await {
+            Ok(data) if !data["repository"].is_null() => repositories.push(owned_repository(&data["repository"])),
+            Ok(_) => not_found.push(name.clone()),
+            // GitHub reports unknown repositories as a NOT_FOUND GraphQL error.
+            Err(e) if e.to_string().contains("NOT_FOUND") => not_found.push(name.clone()),
+            Err(e) => return Err(e),
+        }
+        pacing::pause().await;
+    }
+
+    Ok((repositories, not_found))
+}
+
+/// Step 1 of a scan: enumerate up to `max_repos` of the user's public
+/// non-fork repositories via GraphQL. Private repositories are left out
+/// whichever token runs the scan, so stored results are safe to share.
+/// Returns the repositories and the number of GraphQL pages fetched.
+#[tracing::instrument(name = "scan.repositories", skip(client, token))]
+pub async fn fetch_repositories(
+    client: &Client,
+    token: &str,
+    username: &str,
+    max_repos: usize,
+) -> Result<(Vec<OwnedRepository>, u32), Box<dyn std::error::Error + Send + Sync>> {
+    let mut repositories = Vec::new();
+    let mut after: Option<String> = None;
+    let mut pages = 0u32;
+
+    let query = format!(
+        r#"
+    query($login:String!, $first:Int!, $after:String) {{
+      user(login:$login) {{
+        repositories(first:$first, after:$after, ownerAffiliations:OWNER, isFork:false, privacy:PUBLIC) {{
+          nodes {{ {REPOSITORY_FIELDS} }}
+          pageInfo {{ hasNextPage endCursor }}
+        }}
+      }}
+    }}
+    "#
+    );
+
+    loop {
+        let first = (max_repos - repositories.len()).clamp(1, 50);
+        let vars = serde_json::json!({ "login": username, "first": first, "after": after });
+        let data = github::graphql_request(client, token, &query, Some(vars)).await?;
+        pages += 1;
+
+        if let Some(nodes) = data["user"]["repositories"]["nodes"].as_array() {
+            repositories.extend(nodes.iter().map(owned_repository));
+        }
+
+        let page_info = &data["user"]["repositories"]["pageInfo"];
+        let has_next = page_info["hasNextPage"].as_bool().unwrap_or(false);
+        after = page_info["endCursor"].as_str().map(|s| s.to_string());
+
+        if !has_next || repositories.len() >= max_repos {
+            break;
+        }
+
+        pacing::pause().await;
+    }
+
+    repositories.truncate(max_repos);
+    Ok((repositories, pages))
+}
+
+/// Enumerates the repositories of every aliased account, deduplicated by name
+/// and capped at `max_repos` overall.
+async fn fetch_alias_repositories(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    max_repos: usize,
+) -> Result<(Vec<OwnedRepository>, u32), Box<dyn std::error::Error + Send + Sync>> {
+    let mut repositories: Vec<OwnedRepository> = Vec::new();
+    let mut pages = 0u32;
+
+    for username in usernames {
+        let remaining = max_repos.saturating_sub(repositories.len());
+        if remaining == 0 {
+            break;
+        }
+
+        let (repos, alias_pages) = fetch_repositories(client, token, username, remaining).await?;
+        pages += alias_pages;
+        for repo in repos {
+            if !repositories.iter().any(|r| r.name.eq_ignore_ascii_case(&repo.name)) {
+                repositories.push(repo);
+            }
+        }
+    }
+
+    Ok((repositories, pages))
+}
+
+/// Lists the organizations any of `usernames` is a public member of, using the
+/// GraphQL `organizations` connection (private memberships are not visible).
+#[tracing::instrument(name = "scan.organizations", skip(client, token))]
+pub async fn fetch_organizations(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let query = r#"
+    query($login:String!) {
+      user(login:$login) {
+        organizations(first:100) { nodes { login } }
+      }
+    }
+    "#;
+
+    let rest_only = github::TokenKind::of(token).rest_only();
+    let mut organizations: Vec<String> = Vec::new();
+    for username in usernames {
+        let orgs = if rest_only {
+            fetch_organizations_rest(client, token, username).await?
+        } else {
+            let data = github::graphql_request(client, token, query, Some(serde_json::json!({ "login": username }))).await?;
+            data["user"]["organizations"]["nodes"].as_array().cloned().unwrap_or_default()
+        };
+        for org in &orgs {
+            if let Some(login) = org["login"].as_str()
+                && !organizations.iter().any(|o| o.eq_ignore_ascii_case(login))
+            {
+                organizations.push(login.to_string());
+            }
+        }
+    }
+
+    Ok(organizations)
+}
+
+/// Public organization memberships of `username` through REST, for tokens
+/// whose GraphQL `organizations` only lists granted organizations.
+async fn fetch_organizations_rest(
+    client: &Client,
+    token: &str,
+    username: &str,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("https://api.github.com/users/{}/orgs?per_page=100", urlencoding::encode(username));
+    let resp = github::send(github::EndpointClass::Repos, github::get(client, token, &url)).await?;
+    if !resp.status().is_success() {
+        reporting::github_response(&url, resp.status());
+        return Ok(Vec::new());
+    }
+    Ok(resp.json().await?)
+}
+
+/// One page of REST issue search results for `query`.
+async fn search_issues_rest(
+    client: &Client,
+    token: &str,
+    query: &str,
+    per_page: u32,
+    page: u32,
+) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!(
+        "https://api.github.com/search/issues?q={}&per_page={per_page}&page={page}",
+        urlencoding::encode(query)
+    );
+    let resp = github::send(github::EndpointClass::Search, github::get(client, token, &url)).await?;
+    if !resp.status().is_success() {
+        reporting::github_response(&url, resp.status());
+        return Err(format!("GitHub issue search failed with {}", resp.status()).into());
+    }
+    let results = resp.json().await?;
+    pacing::pause_search().await;
+    Ok(results)
+}
+
+/// The organizations listed in `SUI_ORGS` (comma-separated); empty turns the
+/// membership signal off.
+fn configured_sui_organizations() -> Vec<String> {
+    let configured = std::env::var("SUI_ORGS").unwrap_or_else(|_| DEFAULT_SUI_ORGS.to_string());
+    configured.split(',').map(str::trim).filter(|o| !o.is_empty()).map(String::from).collect()
+}
+
+/// Keeps the organizations listed in `SUI_ORGS` (case-insensitive).
+pub fn sui_organizations(organizations: &[String]) -> Vec<String> {
+    let sui_orgs = configured_sui_organizations();
+
+    organizations
+        .iter()
+        .filter(|org| sui_orgs.iter().any(|s| s.eq_ignore_ascii_case(org)))
+        .cloned()
+        .collect()
+}
+
+/// Lists up to `max_entries` entries of `repo` (`owner/name`) at `tree_ref`
+/// via the recursive Git Trees API. Unreadable trees (empty repos, missing
+/// refs) yield no entries rather than failing the scan.
+pub async fn fetch_tree(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    tree_ref: &str,
+    max_entries: usize,
+) -> Result<Vec<TreeEntry>, Box<dyn std::error::Error + Send + Sync>> {
+    let tree_url = format!("https://api.github.com/repos/{}/git/trees/{}?recursive=1", repo, tree_ref);
+    let resp = github::send(
+        github::EndpointClass::Trees,
+        github::get(client, token, &tree_url),
+    )
+    .instrument(tracing::info_span!("github.tree", repo = %reporting::repo_label(repo)))
+    .await?;
+
+    if !resp.status().is_success() {
+        reporting::github_response(&tree_url, resp.status());
+        return Ok(Vec::new());
+    }
+
+    let tree: serde_json::Value = resp.json().await?;
+    Ok(parse_tree(&tree, max_entries))
+}
+
+/// The first `max_entries` entries of a Git Trees API response.
+pub fn parse_tree(tree: &serde_json::Value, max_entries: usize) -> Vec<TreeEntry> {
+    tree["tree"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .take(max_entries)
+        .filter_map(|f| {
+            Some(TreeEntry {
+                path: f["path"].as_str()?.to_string(),
+                sha: f["sha"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Downloads the raw content of a blob by SHA. Returns `None` for blobs that
+/// are unreadable, binary, larger than `MAX_BLOB_BYTES` or not valid UTF-8.
+pub async fn fetch_blob(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    sha: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let blob_url = format!("https://api.github.com/repos/{}/git/blobs/{}", repo, sha);
+    let resp = github::send(
+        github::EndpointClass::Blobs,
+        github::get(client, token, &blob_url)
+            .header("Accept", "application/vnd.github.raw"),
+    )
+    .instrument(tracing::info_span!("github.blob", repo = %reporting::repo_label(repo)))
+    .await?;
+
+    if !resp.status().is_success() {
+        reporting::github_response(&blob_url, resp.status());
+        return Ok(None);
+    }
+    if resp.content_length().is_some_and(|len| len > MAX_BLOB_BYTES) {
+        tracing::info!("Skipping blob {sha} of {}: above {MAX_BLOB_BYTES} bytes", reporting::repo_label(repo));
+        return Ok(None);
+    }
+
+    let bytes = resp.bytes().await?;
+    if bytes.len() as u64 > MAX_BLOB_BYTES || bytes.contains(&0) {
+        return Ok(None);
+    }
+    Ok(String::from_utf8(bytes.to_vec()).ok())
+}
+
+/// Enumerates repositories only and projects the cost of a full scan.
+///
+/// Every repository costs one tree call. Commit counting costs at least one
+/// call per Move repository, and which repositories contain Move code is only
+/// known after the tree calls, so `commits_min` assumes one page for every
+/// repository.
+#[tracing::instrument(name = "scan.estimate", skip(client, token))]
+pub async fn estimate_scan(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    limits: ScanLimits,
+) -> Result<ScanEstimate, Box<dyn std::error::Error + Send + Sync>> {
+    let (mut repositories, graphql_pages) = fetch_alias_repositories(client, token, usernames, limits.max_repos).await?;
+    repo_rules::rules().apply(&mut repositories);
+    skip_oversized(&mut repositories);
+
+    let repos = repositories.len() as u32;
+    let accounts = usernames.len() as u32;
+    let api_calls = ApiCallEstimate {
+        graphql: graphql_pages,
+        trees: repos,
+        commits_min: repos * accounts,
+        total_min: graphql_pages + repos + repos * accounts,
+    };
+
+    // Each call pays its latency; trees and per-repo commit counting are also paced.
+    let paced_calls = graphql_pages.saturating_sub(1) + repos + repos * accounts;
+    let estimated_seconds = api_calls.total_min as f64 * ASSUMED_CALL_LATENCY_SECS
+        + paced_calls as f64 * pacing::current().delay().as_secs_f64();
+
+    Ok(ScanEstimate {
+        schema_version: SCHEMA_VERSION,
+        username: usernames[0].clone(),
+        aliases: usernames[1..].to_vec(),
+        estimate: true,
+        owned_repositories: repositories.len(),
+        api_calls,
+        estimated_seconds: (estimated_seconds * 10.0).round() / 10.0,
+    })
+}
+
+/// Scans one account, or several aliases of the same person merged into a
+/// single result. `usernames[0]` is reported as the primary account; commits
+/// authored by more than one alias are counted once.
+pub async fn get_user_move_repos(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    options: ScanOptions,
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    rescan_user_move_repos(client, token, usernames, options, None).await
+}
+
+/// Like [`get_user_move_repos`], with the accounts' public organization
+/// memberships already fetched (e.g. to check `require_org`).
+pub async fn get_user_move_repos_with_organizations(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    options: ScanOptions,
+    organizations: Vec<String>,
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    scan(client, token, usernames, options, None, None, AccountSignals::Organizations(organizations)).await
+}
+
+/// Like [`get_user_move_repos`], starting from a previous scan of the same
+/// accounts: repositories whose `pushedAt` has not changed are carried over
+/// as they were, and changed ones only count commits after their
+/// `commit_cursor`. Quick scans ignore `previous`.
+#[tracing::instrument(name = "scan", skip(client, token, previous))]
+pub async fn rescan_user_move_repos(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    options: ScanOptions,
+    previous: Option<Snapshot>,
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    scan(client, token, usernames, options, previous, None, AccountSignals::Fetch).await
+}
+
+/// Scans `repositories` (e.g. from [`fetch_listed_repositories`]) instead of
+/// the accounts' own, attributing commits to `usernames`.
+#[tracing::instrument(name = "scan.listed", skip(client, token, repositories), fields(repos = repositories.len()))]
+pub async fn scan_listed_repos(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    options: ScanOptions,
+    repositories: Vec<OwnedRepository>,
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    scan(client, token, usernames, options, None, Some(repositories), AccountSignals::Fetch).await
+}
+
+/// Like [`scan_listed_repos`] for an org's private repositories: the
+/// accounts' organizations, governance and docs work are left out, and the
+/// repository names stay out of spans, logs and breadcrumbs.
+pub async fn scan_private_repos(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    options: ScanOptions,
+    repositories: Vec<OwnedRepository>,
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    reporting::private(scan(client, token, usernames, options, None, Some(repositories), AccountSignals::Skip)).await
+}
+
+/// What a scan gathers about the accounts besides their repositories.
+enum AccountSignals {
+    /// Organization memberships, governance and docs contributions.
+    Fetch,
+    /// The same, with the memberships already fetched.
+    Organizations(Vec<String>),
+    /// None of them.
+    Skip,
+}
+
+async fn scan(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    options: ScanOptions,
+    previous: Option<Snapshot>,
+    listed: Option<Vec<OwnedRepository>>,
+    signals: AccountSignals,
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let ScanOptions { mode, limits, exclude_merges, count_merged_prs, commit_hygiene, strategy } = options;
+
+    let mut diagnostics = Diagnostics::default();
+
+    // Step 0: Public organization memberships as an identity signal, unless
+    // the caller already has them or no Sui organization is configured
+    let account_signals = !matches!(signals, AccountSignals::Skip);
+    let sui_organizations = match signals {
+        AccountSignals::Organizations(organizations) => sui_organizations(&organizations),
+        AccountSignals::Skip => Vec::new(),
+        AccountSignals::Fetch if configured_sui_organizations().is_empty() => Vec::new(),
+        AccountSignals::Fetch => {
+            let stage = Checkpoint::now();
+            let organizations = sui_organizations(&fetch_organizations(client, token, usernames).await?);
+            diagnostics.record("organizations", &stage);
+            organizations
+        }
+    };
+
+    // Protocol-level contributors may write little Move code themselves.
+    let governance = if mode != ScanMode::Quick && account_signals {
+        let stage = Checkpoint::now();
+        let governance = governance::fetch_governance(client, token, usernames).await?;
+        diagnostics.record("governance", &stage);
+        governance
+    } else {
+        None
+    };
+
+    let documentation_contributions = if mode != ScanMode::Quick && account_signals {
+        let stage = Checkpoint::now();
+        let docs = docs::fetch_documentation_contributions(client, token, usernames, limits.max_commit_pages).await?;
+        diagnostics.record("documentation", &stage);
+        docs
+    } else {
+        None
+    };
+
+    // Step 1: Fetch repositories via GraphQL
+    let stage = Checkpoint::now();
+    let mut repositories = match listed {
+        Some(repositories) => repositories,
+        None => fetch_alias_repositories(client, token, usernames, limits.max_repos).await?.0,
+    };
+    let excluded_repositories = repo_rules::rules().apply(&mut repositories);
+    let skipped_due_to_size = skip_oversized(&mut repositories);
+    diagnostics.record("repo_enumeration", &stage);
+
+    let merged_prs = if count_merged_prs && mode != ScanMode::Quick {
+        let stage = Checkpoint::now();
+        let counts = fetch_merged_pr_counts(client, token, usernames, limits.max_commit_pages).await?;
+        diagnostics.record("merged_prs", &stage);
+        Some(counts)
+    } else {
+        None
+    };
+
+    // Step 2: Run the detector pipeline over each repo's tree (REST Git Trees API)
+    let stage = Checkpoint::now();
+    let runtime = crate::state::runtime();
+    let pipeline = &runtime.pipeline;
+    // Commit hygiene needs every message, so earlier scans without it are recounted.
+    let mut previous = previous
+        .filter(|_| mode != ScanMode::Quick)
+        .filter(|p| !commit_hygiene || p.result.repositories.iter().all(|r| r.commit_hygiene.is_some()));
+    let mut reused = Vec::new();
+    let mut repo_cursors = Vec::new();
+    let mut repos_with_move = Vec::new();
+    for repo in &repositories {
+        if let Some(previous) = previous.as_mut()
+            && previous.unchanged(repo)
+        {
+            reused.extend(previous.take_repository(&repo.name));
+            repo_cursors.extend(repo.pushed_at.clone().map(|p| (repo.name.to_lowercase(), p)));
+            continue;
+        }
+        if mode != ScanMode::Quick {
+            repo_cursors.extend(repo.pushed_at.clone().map(|p| (repo.name.to_lowercase(), p)));
+        }
+
+        let large = is_large(repo);
+        let max_tree_entries = if large { limits.max_tree_entries.min(LARGE_REPO_MAX_TREE_ENTRIES) } else { limits.max_tree_entries };
+        let mirror = if strategy == ScanStrategy::Clone && mode != ScanMode::Quick && !large {
+            let stage = Checkpoint::now();
+            let mirror = mirror::clone(repo).await;
+            diagnostics.record("clone", &stage);
+            mirror.inspect_err(|reason| tracing::info!("Scanning {} through the API: {reason}", reporting::repo_label(&repo.name))).ok()
+        } else {
+            None
+        };
+        let entries = match &mirror {
+            Some(mirror) => mirror.tree(max_tree_entries).await?,
+            None => fetch_tree(client, token, &repo.name, &repo.default_branch, max_tree_entries).await?,
+        };
+        let ctx = detect::RepoContext { client, token, repo, entries: &entries };
+        let detections = pipeline.run(&ctx).await?;
+
+        if !detections.is_empty() {
+            let evidence = detect::evidence(&ctx, mirror.as_ref(), mode == ScanMode::Quick).await?;
+            let tooling_evidence = detect::tooling_evidence(&ctx, mirror.as_ref(), mode == ScanMode::Quick).await?;
+            let manifests: Vec<TreeEntry> = entries.iter().filter(|e| is_manifest(&e.path)).cloned().collect();
+            let move_files: Vec<TreeEntry> = entries.into_iter().filter(|e| e.path.ends_with(".move")).collect();
+            repos_with_move.push((repo, move_files, manifests, detections, evidence, tooling_evidence, mirror));
+            if mode == ScanMode::Quick {
+                break;
+            }
+        }
+
+        pacing::pause().await;
+    }
+    diagnostics.record("tree_checks", &stage);
+
+    // Step 3: Count commits for each repo with .move files (skipped in quick mode)
+    let mut total_commits = 0u32;
+    let mut repositories_with_commits = Vec::new();
+
+    let rules = classify::ruleset();
+
+    for (repo, move_files, manifests, detections, evidence, tooling_evidence, mirror) in repos_with_move {
+        let categories = classify::classify(rules, repo, &move_files);
+
+        if mode == ScanMode::Quick {
+            repositories_with_commits.push(RepositoryWithCommits {
+                repo_name: repo.name.clone(),
+                repo_url: repo.url.clone(),
+                categories,
+                detections,
+                evidence,
+                tooling_evidence,
+                move_files,
+                manifests,
+                description: repo.description.clone(),
+                topics: repo.topics.clone(),
+                size_downgraded: is_large(repo),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        // A changed repository from the previous scan only needs its new
+        // commits; a local mirror recounts its whole history for free.
+        let prior = previous
+            .as_mut()
+            .and_then(|p| p.take_repository(&repo.name))
+            .filter(|r| r.commit_cursor.is_some() && mirror.is_none());
+        let cursor = prior.as_ref().and_then(|r| r.commit_cursor.as_ref());
+
+        let stage = Checkpoint::now();
+        let mut commits = match (&mirror, cursor) {
+            (Some(mirror), _) => mirror.commits(usernames, limits.max_commit_pages, &[]).await?,
+            (None, Some(cursor)) => {
+                let mut commits =
+                    fetch_commits_since(client, token, &repo.name, usernames, &cursor.committed_at, limits.max_commit_pages)
+                        .await?;
+                commits.retain(|c| c["sha"] != cursor.sha.as_str());
+                commits
+            }
+            (None, None) => fetch_commits(client, token, &repo.name, usernames, limits.max_commit_pages).await?,
+        };
+        let mut bot_commits_excluded = authorship::exclude_bots(&mut commits);
+        let mut merge_commits_excluded = if exclude_merges { exclude_merge_commits(&mut commits) } else { 0 };
+        let mut repo_commits = commits.len() as u32;
+        let mut confidence = authorship::confidence(&commits, usernames);
+        let mut releases = releases::from_commits(&commits);
+        let mut hygiene = commit_hygiene.then(|| hygiene::hygiene(&commits));
+        let mut commit_days = activity::commit_days(&commits);
+        let mut last_commit_at =
+            commits.iter().filter_map(|c| c["commit"]["author"]["date"].as_str()).max().map(String::from);
+        let mut commit_cursor = commits
+            .iter()
+            .filter_map(|c| Some((c["commit"]["committer"]["date"].as_str()?, c["sha"].as_str()?)))
+            .max()
+            .map(|(date, sha)| CommitCursor { sha: sha.to_string(), committed_at: date.to_string() });
+
+        if let Some(prior) = &prior {
+            repo_commits += prior.commit_count;
+            bot_commits_excluded += prior.bot_commits_excluded;
+            merge_commits_excluded += prior.merge_commits_excluded;
+            if let Some(previous) = &prior.confidence {
+                confidence = previous.combine(&confidence);
+            }
+            last_commit_at = last_commit_at.max(prior.last_commit_at.clone());
+            commit_cursor = commit_cursor.or_else(|| prior.commit_cursor.clone());
+            releases = releases::merge(releases, prior.releases.clone());
+            commit_days = activity::merge_days(commit_days, &prior.commit_days);
+            if let (Some(hygiene), Some(previous)) = (&mut hygiene, &prior.commit_hygiene) {
+                *hygiene = hygiene.combine(previous);
+            }
+        }
+        diagnostics.record("commit_counting", &stage);
+
+        let stage = Checkpoint::now();
+        releases = releases::merge(releases, releases::fetch_tags(client, token, &repo.name).await?);
+        diagnostics.record("releases", &stage);
+
+        // The first Move commit does not change once found.
+        let stage = Checkpoint::now();
+        let move_since = match (prior.as_ref().and_then(|r| r.move_since.clone()), &mirror) {
+            (Some(since), _) => Some(since),
+            (None, Some(mirror)) => mirror.move_since(usernames).await?,
+            (None, None) => fetch_move_since(client, token, &repo.name, usernames, &move_files).await?,
+        };
+        diagnostics.record("move_history", &stage);
+
+        // Step 4 (deep mode): attribute Move lines via blame
+        let move_lines_authored = if mode == ScanMode::Deep && !is_large(repo) {
+            let stage = Checkpoint::now();
+            let mut lines = 0u32;
+            for file in move_files.iter().take(limits.max_blame_files) {
+                match &mirror {
+                    Some(mirror) => lines += mirror.authored_lines(&file.path, usernames).await?,
+                    None => {
+                        lines += count_authored_lines(client, token, repo, &file.path, usernames).await?;
+                        pacing::pause().await;
+                    }
+                }
+            }
+            diagnostics.record("blame", &stage);
+            Some(lines)
+        } else {
+            None
+        };
+
+        repositories_with_commits.push(RepositoryWithCommits {
+            repo_name: repo.name.clone(),
+            repo_url: repo.url.clone(),
+            commit_count: repo_commits,
+            bot_commits_excluded,
+            move_since,
+            last_commit_at,
+            commit_days,
+            commit_cursor,
+            merge_commits_excluded,
+            commit_hygiene: hygiene,
+            merged_pull_requests: merged_prs.as_ref().map(|counts| {
+                counts.iter().find(|(r, _)| r.eq_ignore_ascii_case(&repo.name)).map_or(0, |(_, n)| *n)
+            }),
+            move_lines_authored,
+            releases,
+            size_downgraded: is_large(repo),
+            confidence: Some(confidence),
+            cloned: mirror.is_some(),
+            categories,
+            detections,
+            evidence,
+            tooling_evidence,
+            move_files,
+            manifests,
+            description: repo.description.clone(),
+            topics: repo.topics.clone(),
+            ..Default::default()
+        });
+
+        total_commits += repo_commits;
+        pacing::pause().await;
+    }
+
+    // Carried-over repositories keep their counts; only PR totals are re-read.
+    diagnostics.repositories_reused = reused.len() as u32;
+    for mut repo in reused {
+        if !commit_hygiene {
+            repo.commit_hygiene = None;
+        }
+        repo.merged_pull_requests = merged_prs.as_ref().map(|counts| {
+            counts.iter().find(|(r, _)| r.eq_ignore_ascii_case(&repo.repo_name)).map_or(0, |(_, n)| *n)
+        });
+        total_commits += repo.commit_count;
+        repositories_with_commits.push(repo);
+    }
+
+    // Step 5: Issues opened and reviews given on the Move repositories
+    if mode != ScanMode::Quick && !repositories_with_commits.is_empty() {
+        let stage = Checkpoint::now();
+        let names: Vec<String> = repositories_with_commits.iter().map(|r| r.repo_name.clone()).collect();
+        let activity = fetch_issue_review_counts(client, token, usernames, &names).await?;
+        for (repo, (issues, reviews)) in repositories_with_commits.iter_mut().zip(activity) {
+            repo.issues_opened = Some(issues);
+            repo.reviews_given = Some(reviews);
+        }
+        diagnostics.record("issues_and_reviews", &stage);
+    }
+
+    repositories_with_commits.sort_by_key(|r| std::cmp::Reverse(r.commit_count));
+
+    let move_lines_authored = (mode == ScanMode::Deep)
+        .then(|| repositories_with_commits.iter().filter_map(|r| r.move_lines_authored).sum());
+
+    Ok(UserMoveFilesResponse {
+        schema_version: SCHEMA_VERSION,
+        username: usernames[0].clone(),
+        aliases: usernames[1..].to_vec(),
+        has_move_files: !repositories_with_commits.is_empty(),
+        total_repositories: repositories_with_commits.len(),
+        total_commits,
+        bot_commits_excluded: repositories_with_commits.iter().map(|r| r.bot_commits_excluded).sum(),
+        issues_opened: (mode != ScanMode::Quick)
+            .then(|| repositories_with_commits.iter().filter_map(|r| r.issues_opened).sum()),
+        reviews_given: (mode != ScanMode::Quick)
+            .then(|| repositories_with_commits.iter().filter_map(|r| r.reviews_given).sum()),
+        move_since: repositories_with_commits.iter().filter_map(|r| r.move_since.clone()).min(),
+        last_commit_at: repositories_with_commits.iter().filter_map(|r| r.last_commit_at.clone()).max(),
+        activity: activity::activity(repositories_with_commits.iter().flat_map(|r| &r.commit_days), crate::storage::now_secs()),
+        total_merged_pull_requests: merged_prs
+            .is_some()
+            .then(|| repositories_with_commits.iter().filter_map(|r| r.merged_pull_requests).sum()),
+        commit_hygiene: commit_hygiene
+            .then(|| hygiene::total(repositories_with_commits.iter().filter_map(|r| r.commit_hygiene.as_ref())))
+            .flatten(),
+        move_lines_authored,
+        category_counts: category_counts(&repositories_with_commits),
+        ruleset: Some(crate::ruleset::current()),
+        owners: owner_rollup(&repositories_with_commits),
+        similarity_matches: None,
+        sui_organizations,
+        governance,
+        documentation_contributions,
+        chain_activity: None,
+        annotations: Vec::new(),
+        identity_confirmed: false,
+        verdict: None,
+        ecosystem: None,
+        ecosystems: Vec::new(),
+        certificate_id: None,
+        archive: None,
+        diagnostics: Some(diagnostics),
+        repo_cursors,
+        cached_at: None,
+        stale: false,
+        negative_cached_until: None,
+        excluded_repositories,
+        skipped_due_to_size,
+        repositories: repositories_with_commits,
+        mode,
+        limits,
+    })
+}
+
+/// Directories the repository's Move code lives in, outermost only, for
+/// path-filtered history queries. Root-level files are used as-is.
+fn move_paths(move_files: &[TreeEntry]) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for file in move_files {
+        let path = file.path.rsplit_once('/').map_or(file.path.as_str(), |(dir, _)| dir);
+        if !paths.iter().any(|p| p == path) {
+            paths.push(path.to_string());
+        }
+    }
+    paths.sort_by_key(|p| p.len());
+
+    let mut outermost: Vec<String> = Vec::new();
+    for path in paths {
+        if !outermost.iter().any(|o| path.starts_with(&format!("{o}/"))) {
+            outermost.push(path);
+        }
+    }
+    outermost.truncate(MAX_MOVE_SINCE_PATHS);
+    outermost
+}
+
+/// Move directories whose history is checked per repository for `move_since`.
+const MAX_MOVE_SINCE_PATHS: usize = 3;
+
+/// Date of the earliest commit by any of `usernames` touching the
+/// repository's Move code. Uses `per_page=1` and the `last` page link, so
+/// each path costs at most two requests per account.
+pub async fn fetch_move_since(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    usernames: &[String],
+    move_files: &[TreeEntry],
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut earliest: Option<String> = None;
+
+    for username in usernames {
+        for path in move_paths(move_files) {
+            let base = format!(
+                "https://api.github.com/repos/{}/commits?author={}&path={}&per_page=1",
+                repo,
+                urlencoding::encode(username),
+                urlencoding::encode(&path)
+            );
+            let mut url = base.clone();
+
+            // The first page is the newest commit; follow the `last` link for the oldest.
+            for _ in 0..2 {
+                let resp = github::send(
+                    github::EndpointClass::Commits,
+                    github::get(client, token, &url),
+                )
+                .instrument(tracing::info_span!("github.commits", repo = %reporting::repo_label(repo), path = %path))
+                .await?;
+
+                if !resp.status().is_success() {
+                    reporting::github_response(&url, resp.status());
+                    break;
+                }
+
+                let last_page = resp
+                    .headers()
+                    .get("link")
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(last_page_number);
+                let commits: Vec<serde_json::Value> = resp.json().await.unwrap_or_default();
+
+                match last_page {
+                    Some(page) if url == base && page > 1 => url = format!("{base}&page={page}"),
+                    _ => {
+                        if let Some(date) = commits.first().and_then(|c| c["commit"]["author"]["date"].as_str())
+                            && earliest.as_deref().is_none_or(|e| date < e)
+                        {
+                            earliest = Some(date.to_string());
+                        }
+                        break;
+                    }
+                }
+            }
+
+            pacing::pause().await;
+        }
+    }
+
+    Ok(earliest)
+}
+
+/// Extracts the page number of `rel="last"` from a `Link` header.
+fn last_page_number(link: &str) -> Option<u32> {
+    link.split(',').find(|part| part.contains("rel=\"last\"")).and_then(|part| {
+        let url = part.split(['<', '>']).nth(1)?;
+        url.split(['?', '&']).find_map(|param| param.strip_prefix("page=")).and_then(|p| p.parse().ok())
+    })
+}
+
+/// Repositories covered per GraphQL request by [`fetch_issue_review_counts`].
+const ACTIVITY_REPOS_PER_QUERY: usize = 20;
+
+/// `(issues opened, reviews given)` by any of `usernames` in each of
+/// `repos`, in order. Uses aliased search counts, batching many repositories
+/// into one GraphQL request.
+pub async fn fetch_issue_review_counts(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    repos: &[String],
+) -> Result<Vec<(u32, u32)>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut counts = vec![(0u32, 0u32); repos.len()];
+    let rest_only = github::TokenKind::of(token).rest_only();
+
+    for username in usernames {
+        if rest_only {
+            for (repo, entry) in repos.iter().zip(counts.iter_mut()) {
+                let issues = search_issues_rest(client, token, &format!("repo:{repo} is:issue author:{username}"), 1, 1).await?;
+                let reviews =
+                    search_issues_rest(client, token, &format!("repo:{repo} is:pr reviewed-by:{username} -author:{username}"), 1, 1)
+                        .await?;
+                entry.0 += issues["total_count"].as_u64().unwrap_or(0) as u32;
+                entry.1 += reviews["total_count"].as_u64().unwrap_or(0) as u32;
+            }
+            continue;
+        }
+        for (chunk_index, chunk) in repos.chunks(ACTIVITY_REPOS_PER_QUERY).enumerate() {
+            let mut fields = String::new();
+            for (i, repo) in chunk.iter().enumerate() {
+                // JSON string literals are valid GraphQL string literals.
+                let issues = serde_json::Value::from(format!("repo:{repo} is:issue author:{username}"));
+                let reviews = serde_json::Value::from(format!("repo:{repo} is:pr reviewed-by:{username} -author:{username}"));
+                fields.push_str(&format!("i{i}: search(query:{issues}, type:ISSUE, first:1) {{ issueCount }}\n"));
+                fields.push_str(&format!("r{i}: search(query:{reviews}, type:ISSUE, first:1) {{ issueCount }}\n"));
+            }
+
+            let data = github::graphql_request(client, token, &format!("query {{\n{fields}}}"), None).await?;
+            for i in 0..chunk.len() {
+                let entry = &mut counts[chunk_index * ACTIVITY_REPOS_PER_QUERY + i];
+                entry.0 += data[format!("i{i}")]["issueCount"].as_u64().unwrap_or(0) as u32;
+                entry.1 += data[format!("r{i}")]["issueCount"].as_u64().unwrap_or(0) as u32;
+            }
+            pacing::pause().await;
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Removes commits with more than one parent and returns how many were dropped.
+fn exclude_merge_commits(commits: &mut Vec<serde_json::Value>) -> u32 {
+    let before = commits.len();
+    commits.retain(|c| c["parents"].as_array().is_none_or(|p| p.len() <= 1));
+    (before - commits.len()) as u32
+}
+
+/// Merged pull requests authored by any of `usernames`, per repository
+/// (`owner/repo`), from the GraphQL search API; at most `max_pages` pages of
+/// 100 per account.
+pub async fn fetch_merged_pr_counts(
+    client: &Client,
+    token: &str,
+    usernames: &[String],
+    max_pages: u32,
+) -> Result<Vec<(String, u32)>, Box<dyn std::error::Error + Send + Sync>> {
+    let query = r#"
+    query($q:String!, $after:String) {
+      search(query:$q, type:ISSUE, first:100, after:$after) {
+        nodes { ... on PullRequest { repository { nameWithOwner } } }
+        pageInfo { hasNextPage endCursor }
+      }
+    }
+    "#;
+
+    let mut counts: Vec<(String, u32)> = Vec::new();
+    let mut count = |repo: &str| match counts.iter_mut().find(|(r, _)| r.eq_ignore_ascii_case(repo)) {
+        Some((_, n)) => *n += 1,
+        None => counts.push((repo.to_string(), 1)),
+    };
+    let rest_only = github::TokenKind::of(token).rest_only();
+    for username in usernames {
+        if rest_only {
+            for page in 1..=max_pages {
+                let results = search_issues_rest(client, token, &format!("is:pr is:merged author:{username}"), 100, page).await?;
+                let items = results["items"].as_array().cloned().unwrap_or_default();
+                for item in &items {
+                    if let Some(repo) = item["repository_url"].as_str().and_then(|u| u.strip_prefix("https://api.github.com/repos/")) {
+                        count(repo);
+                    }
+                }
+                if items.len() < 100 {
+                    break;
+                }
+            }
+            continue;
+        }
+        let mut after: Option<String> = None;
+        for _ in 0..max_pages {
+            let variables = serde_json::json!({ "q": format!("is:pr is:merged author:{username}"), "after": after });
+            let data = github::graphql_request(client, token, query, Some(variables)).await?;
+
+            for node in data["search"]["nodes"].as_array().into_iter().flatten() {
+                if let Some(repo) = node["repository"]["nameWithOwner"].as_str() {
+                    count(repo);
+                }
+            }
+
+            let page_info = &data["search"]["pageInfo"];
+            if !page_info["hasNextPage"].as_bool().unwrap_or(false) {
+                break;
+            }
+            after = page_info["endCursor"].as_str().map(|c| c.to_string());
+            pacing::pause().await;
+        }
+    }
+
+    Ok(counts)
+}
+
+/// Whether a tree path is a Move package manifest.
+pub fn is_manifest(path: &str) -> bool {
+    path == "Move.toml" || path.ends_with("/Move.toml")
+}
+
+/// Lists the commits authored by any of `usernames` in `repo`, following at
+/// most `max_pages` pages per account and deduplicated by SHA.
+pub async fn fetch_commits(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    usernames: &[String],
+    max_pages: u32,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    fetch_commits_in(client, token, repo, None, usernames, max_pages).await
+}
+
+/// Like [`fetch_commits`], restricted to commits touching `path` when given.
+pub async fn fetch_commits_in(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    path: Option<&str>,
+    usernames: &[String],
+    max_pages: u32,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let filter = path.map(|p| format!("&path={}", urlencoding::encode(p))).unwrap_or_default();
+    fetch_commits_filtered(client, token, repo, &filter, usernames, max_pages).await
+}
+
+/// Like [`fetch_commits`], limited to commits committed at or after `since`.
+pub async fn fetch_commits_since(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    usernames: &[String],
+    since: &str,
+    max_pages: u32,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let filter = format!("&since={}", urlencoding::encode(since));
+    fetch_commits_filtered(client, token, repo, &filter, usernames, max_pages).await
+}
+
+/// Like [`fetch_commits`], limited to commits committed from `since` to
+/// `until` (ISO 8601, inclusive).
+pub async fn fetch_commits_between(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    usernames: &[String],
+    since: &str,
+    until: &str,
+    max_pages: u32,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let filter = format!("&since={}&until={}", urlencoding::encode(since), urlencoding::encode(until));
+    fetch_commits_filtered(client, token, repo, &filter, usernames, max_pages).await
+}
+
+async fn fetch_commits_filtered(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    path_filter: &str,
+    usernames: &[String],
+    max_pages: u32,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut seen_shas = HashSet::new();
+    let mut all_commits = Vec::new();
+
+    for username in usernames {
+        let mut page = 1;
+
+        while page <= max_pages {
+            let commits_url = format!(
+                "https://api.github.com/repos/{}/commits?author={}&per_page=100&page={}{}",
+                repo,
+                urlencoding::encode(username),
+                page,
+                path_filter
+            );
+            let resp = github::send(
+                github::EndpointClass::Commits,
+                github::get(client, token, &commits_url),
+            )
+            .instrument(tracing::info_span!("github.commits", repo = %reporting::repo_label(repo), page))
+            .await?;
+
+            if !resp.status().is_success() {
+                reporting::github_response(&commits_url, resp.status());
+                break;
+            }
+
+            let commits: Vec<serde_json::Value> = resp.json().await.unwrap_or_default();
+            if commits.is_empty() { break; }
+
+            for commit in commits {
+                if let Some(sha) = commit["sha"].as_str()
+                    && seen_shas.insert(sha.to_string())
+                {
+                    all_commits.push(commit);
+                }
+            }
+            page += 1;
+        }
+    }
+
+    Ok(all_commits)
+}
+
+/// Blames `path` on the repository's default branch and counts the lines whose
+/// commit author is linked to one of `usernames`.
+#[tracing::instrument(name = "github.blame", skip(client, token, repo), fields(repo = %reporting::repo_label(&repo.name)))]
+async fn count_authored_lines(
+    client: &Client,
+    token: &str,
+    repo: &OwnedRepository,
+    path: &str,
+    usernames: &[String],
+) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+    let query = r#"
+    query($owner:String!, $name:String!, $ref:String!, $path:String!) {
+      repository(owner:$owner, name:$name) {
+        object(expression:$ref) {
+          ... on Commit {
+            blame(path:$path) {
+              ranges {
+                startingLine
+                endingLine
+                commit { author { user { login } } }
+              }
+            }
+          }
+        }
+      }
+    }
+    "#;
+
+    let (owner, name) = repo.name.split_once('/').unwrap_or((&repo.name, ""));
+    let vars = serde_json::json!({ "owner": owner, "name": name, "ref": repo.default_branch, "path": path });
+    let data = github::graphql_request(client, token, query, Some(vars)).await?;
+
+    let lines = data["repository"]["object"]["blame"]["ranges"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|range| {
+            range["commit"]["author"]["user"]["login"]
+                .as_str()
+                .is_some_and(|login| usernames.iter().any(|u| login.eq_ignore_ascii_case(u)))
+        })
+        .map(|range| {
+            let start = range["startingLine"].as_u64().unwrap_or(0);
+            let end = range["endingLine"].as_u64().unwrap_or(start);
+            (end + 1).saturating_sub(start) as u32
+        })
+        .sum();
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_aliases_splits_and_dedupes() {
+        assert_eq!(parse_aliases(" alice, Alice ,alice-work,,").unwrap(), ["alice", "alice-work"]);
+    }
+
+    #[test]
+    fn parse_aliases_rejects_non_logins() {
+        let too_long = "a".repeat(40);
+        for raw in ["alice&path=x", "alice&sha=1", "alice repo:other/private", "alice:x", "../alice", "alice/orgs", "a_b", &too_long] {
+            assert_eq!(parse_aliases(raw).unwrap_err().key, "username_invalid", "{raw}");
+        }
+        assert!(parse_aliases(&"a".repeat(39)).is_ok());
+    }
+
+    #[test]
+    fn parse_aliases_limits_accounts() {
+        assert_eq!(parse_aliases(" , ").unwrap_err().key, "username_empty");
+        assert_eq!(parse_aliases("a,b,c,d,e,f").unwrap_err().key, "too_many_aliases");
+    }
+}
@@ -0,0 +1,166 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    admin, certificates,
+    storage::{Storage, StoredScan},
+};
+
+// ------------------- Structs -------------------
+
+/// Results per page.
+const PAGE_SIZE: usize = 20;
+
+/// Search terms used from `q`; the rest are ignored.
+const MAX_TERMS: usize = 8;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    /// Words matched as prefixes against usernames, repository names and
+    /// Move package names; every word must match. Empty lists everyone.
+    #[serde(default)]
+    q: String,
+    min_score: Option<f64>,
+    /// Only `sui` is indexed.
+    framework: Option<String>,
+    /// 1-based.
+    page: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResults {
+    pub query: String,
+    pub page: usize,
+    pub per_page: usize,
+    /// Developers matching across all pages.
+    pub total: usize,
+    pub results: Vec<DeveloperHit>,
+}
+
+/// A stored Move developer, as of their latest scan.
+#[derive(Debug, Serialize)]
+pub struct DeveloperHit {
+    pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    pub commits: u32,
+    pub repositories: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_at: Option<String>,
+    pub scanned_at: u64,
+}
+
+impl DeveloperHit {
+    fn from_scan(scan: &StoredScan) -> Self {
+        let result = &scan.result;
+        DeveloperHit {
+            username: scan.username.clone(),
+            score: certificates::score(result),
+            commits: result.total_commits,
+            repositories: result.repositories.iter().map(|r| r.repo_name.clone()).collect(),
+            packages: result.repositories.iter().flat_map(|r| r.package_names.iter().cloned()).collect(),
+            last_commit_at: result.last_commit_at.clone(),
+            scanned_at: scan.scanned_at,
+        }
+    }
+}
+
+// ------------------- Matching -------------------
+
+/// The FTS5 query for `q`: each word a quoted prefix term, so partial input
+/// matches as the user types. `None` when `q` has no words.
+fn match_expression(q: &str) -> Option<String> {
+    let terms: Vec<String> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(MAX_TERMS)
+        .map(|word| format!("\"{word}\"*"))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+// ------------------- Handler -------------------
+
+/// `GET /search-developers?q=<text>&min_score=<0..1>&framework=sui&page=<n>`:
+/// a page of stored Move developers who have not opted out, best match
+/// first, or highest score first without `q`.
+pub async fn search_developers(
+    Query(params): Query<SearchQuery>,
+    State(storage): State<Storage>,
+) -> Result<Json<SearchResults>, (StatusCode, String)> {
+    if let Some(framework) = params.framework.as_deref().map(str::trim)
+        && !framework.eq_ignore_ascii_case("sui")
+    {
+        return Err((StatusCode::BAD_REQUEST, format!("framework `{framework}` is not indexed; use sui")));
+    }
+    let page = params.page.unwrap_or(1).max(1);
+    let developers = |scans: Vec<StoredScan>| -> HashMap<String, StoredScan> {
+        scans.into_iter().filter(|s| s.result.has_move_files).map(|s| (s.username.to_lowercase(), s)).collect()
+    };
+
+    let mut hits: Vec<DeveloperHit> = match match_expression(&params.q) {
+        Some(expression) => {
+            let (matched, scans) = storage
+                .blocking(move |storage| {
+                    let matched = storage.search_developers(&expression)?;
+                    let scans = storage.discoverable_scans_of(&matched)?;
+                    Ok((matched, scans))
+                })
+                .await
+                .map_err(admin::internal)?;
+            let scans = developers(scans);
+            matched.iter().filter_map(|username| scans.get(&username.to_lowercase())).map(DeveloperHit::from_scan).collect()
+        }
+        None if params.q.trim().is_empty() => {
+            let scans = developers(storage.blocking(|storage| storage.discoverable_developers(0)).await.map_err(admin::internal)?);
+            let mut hits: Vec<DeveloperHit> = scans.values().map(DeveloperHit::from_scan).collect();
+            hits.sort_by(|a, b| {
+                b.score.unwrap_or(f64::MIN).total_cmp(&a.score.unwrap_or(f64::MIN)).then_with(|| a.username.cmp(&b.username))
+            });
+            hits
+        }
+        None => Vec::new(),
+    };
+    if let Some(min_score) = params.min_score {
+        hits.retain(|h| h.score.is_some_and(|s| s >= min_score));
+    }
+
+    let total = hits.len();
+    let results = hits.into_iter().skip((page - 1).saturating_mul(PAGE_SIZE)).take(PAGE_SIZE).collect();
+    Ok(Json(SearchResults { query: params.q, page, per_page: PAGE_SIZE, total, results }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn each_word_becomes_a_quoted_prefix_term() {
+        assert_eq!(match_expression("alice").as_deref(), Some("\"alice\"*"));
+        assert_eq!(match_expression("  defi   dex ").as_deref(), Some("\"defi\"* \"dex\"*"));
+    }
+
+    #[test]
+    fn fts_syntax_is_treated_as_word_breaks() {
+        assert_eq!(match_expression("sui-move\"; OR* (nft)").as_deref(), Some("\"sui\"* \"move\"* \"OR\"* \"nft\"*"));
+    }
+
+    #[test]
+    fn terms_are_capped() {
+        let expression = match_expression("a b c d e f g h i j").unwrap();
+        assert_eq!(expression.split(' ').count(), MAX_TERMS);
+    }
+
+    #[test]
+    fn no_words_no_expression() {
+        assert_eq!(match_expression(""), None);
+        assert_eq!(match_expression(" -*\"() "), None);
+    }
+}
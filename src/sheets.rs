@@ -0,0 +1,280 @@
+use axum::{
+    Json,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{admin, i18n, jwt, policy, state::Runtime, storage::{self, Storage, StoredScan}};
+
+// ------------------- Google Sheets -------------------
+
+const SHEETS_API: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const SHEETS_SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+
+/// Tab written to when `GOOGLE_SHEET_TAB` is unset.
+const DEFAULT_TAB: &str = "Scans";
+
+/// Seconds before expiry at which a cached access token is replaced.
+const TOKEN_REFRESH_MARGIN_SECS: u64 = 60;
+
+/// Columns of the exported rows; row 1 of the tab holds these names and
+/// column A is the username rows are matched by.
+const HEADER: [&str; 10] = [
+    "username",
+    "has_move_files",
+    "is_sui_developer",
+    "policy",
+    "total_repositories",
+    "total_commits",
+    "move_since",
+    "last_commit_at",
+    "labels",
+    "scanned_at",
+];
+
+/// The fields of a Google service-account key file that are used.
+#[derive(Debug, Deserialize)]
+struct ServiceAccount {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+/// Where results are exported: `GOOGLE_SHEETS_CREDENTIALS` (path to a
+/// service-account key file), `GOOGLE_SHEET_ID` and `GOOGLE_SHEET_TAB`.
+/// `None` when the first two are not both set.
+pub fn config() -> Result<Option<SheetConfig>, Box<dyn std::error::Error + Send + Sync>> {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    let (Some(credentials), Some(spreadsheet_id)) = (var("GOOGLE_SHEETS_CREDENTIALS"), var("GOOGLE_SHEET_ID")) else {
+        return Ok(None);
+    };
+    let raw = std::fs::read(&credentials).map_err(|e| format!("cannot read GOOGLE_SHEETS_CREDENTIALS {credentials}: {e}"))?;
+    let account: ServiceAccount =
+        serde_json::from_slice(&raw).map_err(|e| format!("GOOGLE_SHEETS_CREDENTIALS is not a service-account key: {e}"))?;
+    Ok(Some(SheetConfig {
+        account,
+        spreadsheet_id,
+        tab: var("GOOGLE_SHEET_TAB").unwrap_or_else(|| DEFAULT_TAB.to_string()),
+    }))
+}
+
+pub struct SheetConfig {
+    account: ServiceAccount,
+    spreadsheet_id: String,
+    tab: String,
+}
+
+// ------------------- Auth -------------------
+
+/// The current access token and when it expires, shared by all exports.
+static ACCESS_TOKEN: Mutex<Option<(String, u64)>> = Mutex::new(None);
+
+/// A JWT assertion for the OAuth JWT-bearer grant, signed with the service
+/// account's PKCS#8 key.
+fn assertion(account: &ServiceAccount, now: u64) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let key = jwt::signing_key(&account.private_key).map_err(|e| format!("service account: {e}"))?;
+    let claims = serde_json::json!({
+        "iss": account.client_email,
+        "scope": SHEETS_SCOPE,
+        "aud": account.token_uri,
+        "iat": now,
+        "exp": now + 3600,
+    });
+    jwt::sign_rs256(&key, &claims)
+}
+
+/// A valid access token, exchanged for a fresh assertion when the cached one
+/// is about to expire.
+async fn access_token(client: &Client, account: &ServiceAccount) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let now = storage::now_secs();
+    if let Some((token, expires_at)) = ACCESS_TOKEN.lock().unwrap_or_else(|p| p.into_inner()).as_ref()
+        && *expires_at > now + TOKEN_REFRESH_MARGIN_SECS
+    {
+        return Ok(token.clone());
+    }
+
+    let resp = client
+        .post(&account.token_uri)
+        .form(&[("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"), ("assertion", &assertion(account, now)?)])
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        return Err(format!("Google token exchange failed with {}: {}", resp.status(), resp.text().await.unwrap_or_default()).into());
+    }
+    let body: serde_json::Value = resp.json().await?;
+    let token = body["access_token"].as_str().ok_or("Google token response has no access_token")?.to_string();
+    let expires_at = now + body["expires_in"].as_u64().unwrap_or(3600);
+    *ACCESS_TOKEN.lock().unwrap_or_else(|p| p.into_inner()) = Some((token.clone(), expires_at));
+    Ok(token)
+}
+
+// ------------------- Export -------------------
+
+/// Outcome of one export.
+#[derive(Debug, Default, Serialize)]
+pub struct SheetExport {
+    /// Rows rewritten in place because the username was already listed.
+    pub updated: usize,
+    /// Rows added below the existing ones.
+    pub appended: usize,
+    /// Requested users without a stored scan; nothing is written for them.
+    pub missing: Vec<String>,
+}
+
+/// The exported row of one stored scan.
+fn row(scan: &StoredScan, policy: &policy::Policy, labels: &[String]) -> Vec<serde_json::Value> {
+    let result = &scan.result;
+    let verdict = policy.evaluate(result, storage::now_secs());
+    vec![
+        scan.username.clone().into(),
+        result.has_move_files.into(),
+        verdict.is_sui_developer.into(),
+        verdict.policy.into(),
+        result.total_repositories.into(),
+        result.total_commits.into(),
+        result.move_since.clone().unwrap_or_default().into(),
+        result.last_commit_at.clone().unwrap_or_default().into(),
+        labels.join(", ").into(),
+        scan.scanned_at.into(),
+    ]
+}
+
+/// `'<tab>'!<cells>`, quoted so tab names with spaces work.
+fn range(tab: &str, cells: &str) -> String {
+    format!("'{}'!{cells}", tab.replace('\'', "''"))
+}
+
+/// Writes one row per scan, rewriting the row already holding the username
+/// and appending the rest, so exporting the same cohort twice leaves one
+/// row per user.
+pub async fn export(
+    client: &Client,
+    config: &SheetConfig,
+    scans: &[StoredScan],
+    policy: &policy::Policy,
+    labels: &HashMap<String, Vec<String>>,
+) -> Result<SheetExport, Box<dyn std::error::Error + Send + Sync>> {
+    let token = access_token(client, &config.account).await?;
+    let sheet = format!("{SHEETS_API}/{}", config.spreadsheet_id);
+
+    let column_url = format!("{sheet}/values/{}", urlencoding::encode(&range(&config.tab, "A:A")));
+    let resp = client.get(&column_url).bearer_auth(&token).send().await?;
+    if !resp.status().is_success() {
+        return Err(format!("reading the sheet failed with {}: {}", resp.status(), resp.text().await.unwrap_or_default()).into());
+    }
+    let column: serde_json::Value = resp.json().await?;
+    let listed: Vec<String> = column["values"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|cells| cells[0].as_str().unwrap_or_default().to_lowercase())
+        .collect();
+
+    let mut export = SheetExport::default();
+    let mut updates = Vec::new();
+    let mut appends = Vec::new();
+    if listed.is_empty() {
+        appends.push(HEADER.iter().map(|h| serde_json::Value::from(*h)).collect::<Vec<_>>());
+    }
+    for scan in scans {
+        let key = scan.username.to_lowercase();
+        let row = row(scan, policy, labels.get(&key).map(Vec::as_slice).unwrap_or_default());
+        // Row 1 is the header; sheet rows are 1-based.
+        match listed.iter().skip(1).position(|u| *u == key) {
+            Some(index) => {
+                let at = index + 2;
+                updates.push(serde_json::json!({ "range": range(&config.tab, &format!("A{at}:J{at}")), "values": [row] }));
+                export.updated += 1;
+            }
+            None => {
+                appends.push(row);
+                export.appended += 1;
+            }
+        }
+    }
+
+    if !updates.is_empty() {
+        let body = serde_json::json!({ "valueInputOption": "RAW", "data": updates });
+        let resp = client.post(format!("{sheet}/values:batchUpdate")).bearer_auth(&token).json(&body).send().await?;
+        if !resp.status().is_success() {
+            return Err(format!("updating sheet rows failed with {}: {}", resp.status(), resp.text().await.unwrap_or_default()).into());
+        }
+    }
+    if !appends.is_empty() {
+        let url = format!(
+            "{sheet}/values/{}:append?valueInputOption=RAW&insertDataOption=INSERT_ROWS",
+            urlencoding::encode(&range(&config.tab, "A:J"))
+        );
+        let resp = client.post(url).bearer_auth(&token).json(&serde_json::json!({ "values": appends })).send().await?;
+        if !resp.status().is_success() {
+            return Err(format!("appending sheet rows failed with {}: {}", resp.status(), resp.text().await.unwrap_or_default()).into());
+        }
+    }
+    Ok(export)
+}
+
+// ------------------- Handler -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct SheetExportRequest {
+    #[serde(default)]
+    usernames: Vec<String>,
+    /// Also export every user carrying this label.
+    label: Option<String>,
+    /// Verdict policy for the `is_sui_developer` column.
+    policy: Option<String>,
+}
+
+/// `POST /admin/sheets/export`: pushes the latest stored scans of a batch or
+/// cohort to the configured sheet (admin).
+pub async fn export_handler(
+    locale: i18n::Locale,
+    headers: HeaderMap,
+    State(client): State<Client>,
+    State(storage): State<Storage>,
+    State(runtime): State<Arc<Runtime>>,
+    Json(body): Json<SheetExportRequest>,
+) -> Result<Json<SheetExport>, (StatusCode, String)> {
+    admin::require_admin(&headers)?;
+    let config = config()
+        .map_err(admin::internal)?
+        .ok_or((StatusCode::SERVICE_UNAVAILABLE, "GOOGLE_SHEETS_CREDENTIALS and GOOGLE_SHEET_ID are not set".to_string()))?;
+    let policy = runtime
+        .policies
+        .select(body.policy.as_deref())
+        .map_err(|m| (StatusCode::BAD_REQUEST, locale.render(&m)))?;
+
+    let mut usernames: Vec<String> = Vec::new();
+    let labelled = match body.label.as_deref().map(str::trim) {
+        Some(label) => storage.labelled_users(label).map_err(admin::internal)?,
+        None => Vec::new(),
+    };
+    for username in body.usernames.iter().chain(&labelled).map(|u| u.trim()) {
+        if !username.is_empty() && !usernames.iter().any(|u| u.eq_ignore_ascii_case(username)) {
+            usernames.push(username.to_string());
+        }
+    }
+
+    let mut scans = Vec::new();
+    let mut missing = Vec::new();
+    for username in usernames {
+        match storage.latest_scan(&username).map_err(admin::internal)? {
+            Some(scan) => scans.push(scan),
+            None => missing.push(username),
+        }
+    }
+    let labels = storage.all_labels().map_err(admin::internal)?;
+
+    let mut export = export(&client, &config, &scans, policy, &labels)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+    export.missing = missing;
+    tracing::info!("Exported {} rows to Google Sheets ({} updated)", export.updated + export.appended, export.updated);
+    Ok(Json(export))
+}
@@ -0,0 +1,183 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{
+    blobs,
+    scan::{UserMoveFilesResponse, env_or},
+    storage::{Storage, StoredMoveFile},
+};
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityMatch {
+    pub repo: String,
+    pub path: String,
+    pub other_username: String,
+    pub other_repo: String,
+    pub other_path: String,
+    /// Shared fingerprints over the smaller file's fingerprint count.
+    pub similarity: f64,
+}
+
+/// Tokens per k-gram.
+const K: usize = 5;
+/// Winnowing window size, in k-grams.
+const WINDOW: usize = 4;
+/// Files with fewer fingerprints than this are too small to compare meaningfully.
+const MIN_FINGERPRINTS: usize = 10;
+/// Matches reported per scan.
+const MAX_MATCHES: usize = 20;
+
+const DEFAULT_THRESHOLD: f64 = 0.7;
+const DEFAULT_MAX_FILES: usize = 50;
+
+const KEYWORDS: &[&str] = &[
+    "abort", "acquires", "as", "break", "const", "continue", "copy", "drop", "else", "entry", "enum",
+    "false", "friend", "fun", "has", "if", "key", "let", "loop", "macro", "match", "module", "move",
+    "mut", "native", "phantom", "public", "return", "script", "spec", "store", "struct", "true",
+    "type", "use", "while",
+];
+
+// ------------------- Fingerprinting -------------------
+
+/// Splits Move source into a normalised token stream: comments and
+/// whitespace are dropped, keywords and punctuation kept, and identifiers and
+/// literals collapsed so renaming variables does not hide a copy.
+pub fn tokenize(source: &str) -> Vec<String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(if KEYWORDS.contains(&word.as_str()) { word } else { "ID".to_string() });
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push("NUM".to_string());
+        } else if c == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            i += 1;
+            tokens.push("STR".to_string());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+fn fnv1a(parts: &[String]) -> i64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash as i64
+}
+
+/// Winnowing (Schleimer et al.): hash every k-gram and keep the minimum of
+/// each window, giving a position-independent fingerprint set.
+pub fn fingerprints(source: &str) -> Vec<i64> {
+    let tokens = tokenize(source);
+    if tokens.len() < K {
+        return Vec::new();
+    }
+
+    let hashes: Vec<i64> = tokens.windows(K).map(fnv1a).collect();
+    let selected: HashSet<i64> = hashes
+        .windows(WINDOW.min(hashes.len()))
+        .filter_map(|w| w.iter().min().copied())
+        .collect();
+
+    let mut selected: Vec<i64> = selected.into_iter().collect();
+    selected.sort_unstable();
+    selected
+}
+
+// ------------------- Analysis -------------------
+
+/// Fingerprints the scanned user's Move files (up to `MAX_SIMILARITY_FILES`,
+/// downloading only blobs not analysed before), stores them for future comparisons and compares
+/// them against every other user's stored files. Matches at or above
+/// `SIMILARITY_THRESHOLD` are recorded on `result`.
+#[tracing::instrument(name = "similarity", skip_all, fields(username = %result.username))]
+pub async fn analyze(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    result: &mut UserMoveFilesResponse,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let threshold = env_or("SIMILARITY_THRESHOLD", DEFAULT_THRESHOLD);
+    let max_files = env_or("MAX_SIMILARITY_FILES", DEFAULT_MAX_FILES);
+
+    let mut files = Vec::new();
+    'repos: for repo in &result.repositories {
+        for file in &repo.move_files {
+            if files.len() >= max_files {
+                break 'repos;
+            }
+
+            if let Some(analysis) = blobs::analyze_move_blob(client, token, storage, &repo.repo_name, &file.sha).await? {
+                files.push(StoredMoveFile {
+                    repo: repo.repo_name.clone(),
+                    path: file.path.clone(),
+                    hashes: analysis.fingerprints,
+                });
+            }
+        }
+    }
+
+    let mut matches = Vec::new();
+    for file in files.iter().filter(|f| f.hashes.len() >= MIN_FINGERPRINTS) {
+        for other in storage.similar_move_files(&file.hashes, &result.username)? {
+            let smaller = file.hashes.len().min(other.hash_count).max(MIN_FINGERPRINTS);
+            let similarity = other.shared as f64 / smaller as f64;
+            // The same repository stored under another user (e.g. a shared org repo) is not a copy.
+            if similarity >= threshold && !other.repo.eq_ignore_ascii_case(&file.repo) {
+                matches.push(SimilarityMatch {
+                    repo: file.repo.clone(),
+                    path: file.path.clone(),
+                    other_username: other.username,
+                    other_repo: other.repo,
+                    other_path: other.path,
+                    similarity: (similarity * 100.0).round() / 100.0,
+                });
+            }
+        }
+    }
+
+    matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    matches.truncate(MAX_MATCHES);
+
+    storage.replace_move_files(&result.username, &files)?;
+    result.similarity_matches = Some(matches);
+    Ok(())
+}
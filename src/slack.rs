@@ -0,0 +1,177 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use reqwest::Client;
+use serde::Deserialize;
+
+use crate::{
+    Analyses, cache, i18n,
+    policy::PolicySet,
+    queue,
+    scan::{self, ScanLimits, ScanMode, ScanOptions, UserMoveFilesResponse},
+    state::{self, AppState},
+    storage::{self, Storage},
+};
+
+// ------------------- Signature -------------------
+
+/// Requests older than this are rejected as possible replays.
+const MAX_REQUEST_AGE_SECS: u64 = 5 * 60;
+
+/// Checks `X-Slack-Signature` against an HMAC-SHA256 of `v0:<timestamp>:<body>`.
+fn verify_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let Some(timestamp) = header("X-Slack-Request-Timestamp") else {
+        return false;
+    };
+    let fresh = timestamp.parse::<u64>().is_ok_and(|t| storage::now_secs().abs_diff(t) <= MAX_REQUEST_AGE_SECS);
+    let Some(signature) = header("X-Slack-Signature")
+        .and_then(|v| v.strip_prefix("v0="))
+        .and_then(|v| hex::decode(v).ok())
+    else {
+        return false;
+    };
+
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let mut signed = format!("v0:{timestamp}:").into_bytes();
+    signed.extend_from_slice(body);
+    fresh && ring::hmac::verify(&key, &signed, &signature).is_ok()
+}
+
+// ------------------- Command -------------------
+
+/// The slash-command form fields we use.
+#[derive(Debug, Deserialize)]
+struct SlashCommand {
+    #[serde(default)]
+    command: String,
+    #[serde(default)]
+    text: String,
+    response_url: String,
+}
+
+/// `POST /integrations/slack/command`: verifies the request with
+/// `SLACK_SIGNING_SECRET`, acknowledges within Slack's three seconds and
+/// posts the summary card to `response_url` once the check finishes.
+pub async fn command(
+    headers: HeaderMap,
+    State(AppState { client, github_token: token, storage, runtime, .. }): State<AppState>,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let secret = std::env::var("SLACK_SIGNING_SECRET").ok().filter(|s| !s.is_empty()).ok_or((
+        StatusCode::NOT_FOUND,
+        "Slack integration is disabled".to_string(),
+    ))?;
+    if !verify_signature(&secret, &headers, &body) {
+        return Err((StatusCode::UNAUTHORIZED, "invalid Slack signature".to_string()));
+    }
+
+    let command: SlashCommand = serde_urlencoded::from_bytes(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("invalid slash command: {e}")))?;
+    let locale = i18n::Locale { lang: "en".to_string() };
+    let usernames = match scan::parse_aliases(&command.text) {
+        Ok(usernames) => usernames,
+        Err(message) => {
+            let usage = format!("{}. Usage: `{} <github_user>[,<alias>]`", locale.render(&message), command.command);
+            return Ok(Json(ephemeral(&usage)));
+        }
+    };
+
+    let ack = ephemeral(&format!("Checking `{}`…", usernames.join(", ")));
+    state::spawn(async move {
+        let card = match check(&client, &token, &storage, &runtime.policies, &usernames).await {
+            Ok(result) => summary_card(&result),
+            Err(e) => ephemeral(&format!("Could not check `{}`: {e}", usernames[0])),
+        };
+        let delivered = client.post(&command.response_url).json(&card).send().await.and_then(|r| r.error_for_status());
+        if let Err(e) = delivered {
+            tracing::warn!("Slack response for {} was not delivered: {e}", usernames[0]);
+        }
+    });
+
+    Ok(Json(ack))
+}
+
+/// A plain full scan with the default policy, answered from the cache when fresh.
+async fn check(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    policies: &PolicySet,
+    usernames: &[String],
+) -> Result<UserMoveFilesResponse, Box<dyn std::error::Error + Send + Sync>> {
+    let policy = policies.select(None).map_err(|m| m.key)?;
+    let options = ScanOptions::new(ScanMode::Full, ScanLimits::ceiling());
+
+    if let Ok(Some(mut cached)) = cache::lookup(storage, usernames, options.mode, options.limits, 0) {
+        crate::attach_verdict(storage, &mut cached, policy);
+        return Ok(cached);
+    }
+
+    let scan = async {
+        let mut result = scan::get_user_move_repos(client, token, usernames, options).await?;
+        result.diagnostics = None;
+        crate::post_process_scan(client, token, storage, &mut result, Analyses::default()).await;
+        Ok(result)
+    };
+    let mut result = queue::run(queue::Lane::Interactive, scan).await?;
+    crate::attach_verdict(storage, &mut result, policy);
+    Ok(result)
+}
+
+// ------------------- Messages -------------------
+
+/// Top repositories listed on the card.
+const CARD_REPOSITORIES: usize = 5;
+
+fn ephemeral(text: &str) -> serde_json::Value {
+    serde_json::json!({ "response_type": "ephemeral", "text": text })
+}
+
+/// Block Kit card with the verdict, headline numbers and top repositories.
+fn summary_card(result: &UserMoveFilesResponse) -> serde_json::Value {
+    let passed = result.verdict.as_ref().is_some_and(|v| v.is_sui_developer);
+    let (icon, verdict) = if passed { ("✅", "Sui developer") } else { ("❌", "not a Sui developer") };
+    let title = format!("{icon} {} — {verdict}", result.username);
+
+    let mut fields = vec![
+        format!("*Repositories*\n{}", result.total_repositories),
+        format!("*Commits*\n{}", result.total_commits),
+    ];
+    if let Some(last) = &result.last_commit_at {
+        fields.push(format!("*Last Move commit*\n{}", last.get(..10).unwrap_or(last)));
+    }
+    if let Some(verdict) = &result.verdict {
+        fields.push(format!("*Policy*\n{}", verdict.policy));
+    }
+
+    let repositories = result
+        .repositories
+        .iter()
+        .take(CARD_REPOSITORIES)
+        .map(|r| format!("• <{}|{}> — {} commits", r.repo_url, r.repo_name, r.commit_count))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut blocks = vec![
+        serde_json::json!({ "type": "header", "text": { "type": "plain_text", "text": title } }),
+        serde_json::json!({
+            "type": "section",
+            "fields": fields.iter().map(|f| serde_json::json!({ "type": "mrkdwn", "text": f })).collect::<Vec<_>>(),
+        }),
+    ];
+    if !repositories.is_empty() {
+        blocks.push(serde_json::json!({ "type": "section", "text": { "type": "mrkdwn", "text": repositories } }));
+    }
+    if let Some(id) = &result.certificate_id {
+        blocks.push(serde_json::json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": format!("Certificate `{id}`") }],
+        }));
+    }
+
+    serde_json::json!({ "response_type": "in_channel", "text": title, "blocks": blocks })
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
+
+use crate::storage::{self, Row, Storage};
+
+// ------------------- Archive -------------------
+
+/// Identifies snapshot files.
+const FORMAT: &str = "sui_contributors_snapshot";
+
+/// Layout version written by this build. Imports accept this version and
+/// older ones; a newer snapshot needs a newer build.
+const VERSION: u32 = 1;
+
+/// The full storage state: every table as column -> value rows, so it does
+/// not depend on the database it came from or is restored into.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: u64,
+    pub tables: BTreeMap<String, Vec<Row>>,
+}
+
+impl Snapshot {
+    pub fn capture(storage: &Storage) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let tables = storage::TABLES
+            .iter()
+            .map(|table| Ok((table.to_string(), storage.export_table(table)?)))
+            .collect::<Result<_, Box<dyn std::error::Error + Send + Sync>>>()?;
+        Ok(Snapshot { format: FORMAT.to_string(), version: VERSION, exported_at: storage::now_secs(), tables })
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.format != FORMAT {
+            return Err(format!("not a snapshot (format `{}`)", self.format));
+        }
+        if self.version > VERSION {
+            return Err(format!("snapshot version {} is newer than this build reads ({VERSION})", self.version));
+        }
+        Ok(())
+    }
+}
+
+// ------------------- Commands -------------------
+
+/// `export-data <path>`: writes the snapshot of `DATABASE_PATH` to `path`
+/// (`-` for stdout). Returns the process exit code.
+pub fn export(path: &str) -> i32 {
+    let written = storage::open_from_env().and_then(|storage| {
+        let snapshot = Snapshot::capture(&storage)?;
+        let json = serde_json::to_vec(&snapshot)?;
+        match path {
+            "-" => std::io::stdout().write_all(&json)?,
+            _ => std::fs::write(path, &json)?,
+        }
+        Ok(snapshot)
+    });
+    match written {
+        Ok(snapshot) => {
+            eprintln!("Exported snapshot v{VERSION} to {path}");
+            summarize(&snapshot);
+            0
+        }
+        Err(e) => {
+            eprintln!("Export failed: {e}");
+            1
+        }
+    }
+}
+
+/// `import-data <path>`: restores a snapshot (`-` for stdin) into
+/// `DATABASE_PATH`, which must hold no data yet. Returns the process exit code.
+pub fn import(path: &str) -> i32 {
+    let restored = (|| -> Result<(Snapshot, usize), Box<dyn std::error::Error + Send + Sync>> {
+        let raw = match path {
+            "-" => {
+                let mut raw = Vec::new();
+                std::io::stdin().read_to_end(&mut raw)?;
+                raw
+            }
+            _ => std::fs::read(path)?,
+        };
+        let snapshot: Snapshot = serde_json::from_slice(&raw)?;
+        snapshot.check()?;
+        let inserted = storage::open_from_env()?.import_tables(&snapshot.tables)?;
+        Ok((snapshot, inserted))
+    })();
+    match restored {
+        Ok((snapshot, inserted)) => {
+            eprintln!("Imported {inserted} rows from snapshot v{} taken at {}", snapshot.version, snapshot.exported_at);
+            summarize(&snapshot);
+            0
+        }
+        Err(e) => {
+            eprintln!("Import failed: {e}");
+            1
+        }
+    }
+}
+
+fn summarize(snapshot: &Snapshot) {
+    for (table, rows) in snapshot.tables.iter().filter(|(_, rows)| !rows.is_empty()) {
+        eprintln!("  {table:<22} {:>8} rows", rows.len());
+    }
+}
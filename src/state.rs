@@ -0,0 +1,137 @@
+use axum::{
+    extract::{FromRef, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use reqwest::Client;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use crate::{
+    detect::Pipeline,
+    github::{self, RateBudgets},
+    installations::AppConfig,
+    notify::Dispatcher,
+    pacing::Profiles,
+    policy::PolicySet,
+    queue::Queue,
+    storage::Storage,
+};
+
+// ------------------- App State -------------------
+
+/// Resources shared by every handler, passed as axum `State`. Handlers take
+/// the whole state or, through [`FromRef`], just the client, storage or
+/// runtime. Cloning is cheap: everything in it is a handle to shared data.
+#[derive(Clone)]
+pub struct AppState {
+    pub client: Client,
+    /// The server's GitHub token, used unless a request brings its own.
+    pub github_token: String,
+    pub storage: Storage,
+    /// What `github_token` can do, worked out once at startup.
+    pub capabilities: github::TokenCapabilities,
+    /// The GitHub App for org-internal repositories, when configured.
+    pub github_app: Option<Arc<AppConfig>>,
+    pub runtime: Arc<Runtime>,
+}
+
+impl AppState {
+    pub fn new(client: Client, github_token: String, storage: Storage, github_app: Option<AppConfig>, runtime: Arc<Runtime>) -> Self {
+        let capabilities = github::capabilities(&github_token);
+        AppState { client, github_token, storage, capabilities, github_app: github_app.map(Arc::new), runtime }
+    }
+}
+
+impl FromRef<AppState> for Client {
+    fn from_ref(state: &AppState) -> Self {
+        state.client.clone()
+    }
+}
+
+impl FromRef<AppState> for Storage {
+    fn from_ref(state: &AppState) -> Self {
+        state.storage.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Runtime> {
+    fn from_ref(state: &AppState) -> Self {
+        state.runtime.clone()
+    }
+}
+
+// ------------------- Runtime -------------------
+
+/// The configuration and caches of one server or CLI run: verdict policies,
+/// detectors, pacing profiles, the scan queue, the rate limits GitHub last
+/// reported and the notification routes. Handlers take it from
+/// [`AppState`]; scans and background jobs, which run far from any
+/// handler, read it with [`runtime`] inside a [`scope`].
+pub struct Runtime {
+    pub policies: PolicySet,
+    pub pipeline: Pipeline,
+    pub pacing: Profiles,
+    pub queue: Queue,
+    pub budgets: RateBudgets,
+    pub notifications: Dispatcher,
+}
+
+impl Runtime {
+    /// A runtime with the given policies and detectors, and every other
+    /// setting read from the environment.
+    pub fn new(policies: PolicySet, pipeline: Pipeline) -> Self {
+        Runtime {
+            policies,
+            pipeline,
+            pacing: Profiles::from_env(),
+            queue: Queue::from_env(),
+            budgets: RateBudgets::default(),
+            notifications: Dispatcher::from_env(),
+        }
+    }
+
+    /// A runtime read entirely from the environment, for CLI commands:
+    /// invalid settings fall back to the built-in ones with a warning.
+    pub fn from_env() -> Self {
+        let policies = PolicySet::from_env().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring {e}");
+            crate::policy::builtin()
+        });
+        let pipeline = Pipeline::from_env().unwrap_or_else(|e| {
+            tracing::warn!("Ignoring SCAN_DETECTORS: {e}");
+            Pipeline::builtin()
+        });
+        Runtime::new(policies, pipeline)
+    }
+}
+
+tokio::task_local! {
+    static RUNTIME: Arc<Runtime>;
+}
+
+/// Runs `future` with `runtime` as the one [`runtime`] returns.
+pub async fn scope<F: Future>(runtime: Arc<Runtime>, future: F) -> F::Output {
+    RUNTIME.scope(runtime, future).await
+}
+
+/// The runtime of the request, job or command the current task serves.
+/// Panics outside a [`scope`]: every entry point sets one.
+pub fn runtime() -> Arc<Runtime> {
+    RUNTIME.try_with(Arc::clone).expect("called outside a runtime scope")
+}
+
+/// `tokio::spawn` for work that needs the runtime: the task keeps the
+/// spawning task's runtime.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(scope(runtime(), future))
+}
+
+/// Runs every request inside the scope of the server's runtime.
+pub async fn enter(State(runtime): State<Arc<Runtime>>, request: Request, next: Next) -> Response {
+    scope(runtime, next.run(request)).await
+}
@@ -0,0 +1,143 @@
+use axum::{
+    Json,
+    extract::{Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::{
+    notify,
+    policy::{PolicySet, days_since_epoch},
+    profile::iso_date,
+    state,
+    storage::{self, Storage, StoredScan},
+};
+
+// ------------------- Rollups -------------------
+
+/// How often the job checks whether yesterday's rollup is missing.
+const ROLLUP_CHECK_SECS: u64 = 60 * 60;
+
+/// Days returned by `GET /stats/ecosystem` without `from`, and the widest range served.
+const DEFAULT_RANGE_DAYS: u64 = 30;
+const MAX_RANGE_DAYS: u64 = 3660;
+
+/// Aggregates over the latest stored scan of every user, taken once per UTC day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyRollup {
+    /// The UTC day (`YYYY-MM-DD`) the rollup covers.
+    pub date: String,
+    /// Users with a stored scan.
+    pub users_scanned: u32,
+    /// Users whose latest scan passes the default verdict policy.
+    pub verified_developers: u32,
+    /// Verified developers whose first stored scan to pass the policy was
+    /// taken on `date`.
+    pub new_developers: u32,
+    /// Move repositories across all latest scans.
+    pub move_repositories: u32,
+    pub total_commits: u64,
+    pub computed_at: u64,
+}
+
+/// The rollup for `date` over the latest stored scans, as of `now_secs`.
+/// A developer is new on the day of their first stored scan that passed the
+/// policy, judged as of when it was taken.
+pub fn rollup(
+    storage: &Storage,
+    policies: &PolicySet,
+    date: &str,
+    now_secs: u64,
+) -> Result<DailyRollup, Box<dyn std::error::Error + Send + Sync>> {
+    let policy = policies.select(None).expect("the default policy exists");
+    let scans = storage.latest_scans()?;
+    let verified: Vec<&StoredScan> =
+        scans.iter().filter(|s| policy.evaluate(&s.result, now_secs).is_sui_developer).collect();
+    let mut new_developers = 0;
+    for scan in &verified {
+        let history = storage.scan_history(&scan.username)?;
+        let first_verified = history.iter().find(|s| policy.evaluate(&s.result, s.scanned_at).is_sui_developer);
+        if first_verified.is_some_and(|s| iso_date(s.scanned_at) == date) {
+            new_developers += 1;
+        }
+    }
+    Ok(DailyRollup {
+        date: date.to_string(),
+        users_scanned: scans.len() as u32,
+        verified_developers: verified.len() as u32,
+        new_developers,
+        move_repositories: scans.iter().map(|s| s.result.total_repositories as u32).sum(),
+        total_commits: scans.iter().map(|s| s.result.total_commits as u64).sum(),
+        computed_at: now_secs,
+    })
+}
+
+/// Stores the rollup of the previous UTC day unless it already exists;
+/// true when one was computed.
+fn roll_up_yesterday(storage: &Storage) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let now = storage::now_secs();
+    let yesterday = iso_date(now.saturating_sub(86_400));
+    if storage.has_rollup(&yesterday)? {
+        return Ok(false);
+    }
+    let rollup = rollup(storage, &state::runtime().policies, &yesterday, now)?;
+    storage.save_rollup(&rollup)?;
+    tracing::info!("Ecosystem rollup for {yesterday}: {} verified developers", rollup.verified_developers);
+    let summary = format!(
+        "Sui ecosystem on {yesterday}: {} verified developers ({} new), {} Move repositories",
+        rollup.verified_developers, rollup.new_developers, rollup.move_repositories
+    );
+    notify::publish(notify::Event::new(notify::EventKind::EcosystemRollup, summary, serde_json::to_value(&rollup)?));
+    Ok(true)
+}
+
+/// Spawns the job that rolls up each finished day shortly after midnight UTC.
+pub fn spawn_rollup_job(storage: Storage) {
+    state::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(ROLLUP_CHECK_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = roll_up_yesterday(&storage) {
+                tracing::warn!("Ecosystem rollup failed: {e}");
+            }
+        }
+    });
+}
+
+// ------------------- Handler -------------------
+
+#[derive(Debug, Deserialize)]
+pub struct EcosystemStatsQuery {
+    /// First day (`YYYY-MM-DD`), default 30 days before `to`.
+    from: Option<String>,
+    /// Last day, inclusive (default today).
+    to: Option<String>,
+}
+
+/// `GET /stats/ecosystem?from=<YYYY-MM-DD>&to=<YYYY-MM-DD>`: the stored daily
+/// rollups in the range, oldest first.
+pub async fn ecosystem_stats(
+    Query(params): Query<EcosystemStatsQuery>,
+    State(storage): State<Storage>,
+) -> Result<Json<Vec<DailyRollup>>, (StatusCode, String)> {
+    let day = |value: &Option<String>, default: u64| match value.as_deref().map(str::trim) {
+        None => Ok(default),
+        Some(date) => days_since_epoch(date)
+            .filter(|_| date.len() == 10)
+            .ok_or((StatusCode::BAD_REQUEST, format!("{date} is not a YYYY-MM-DD date"))),
+    };
+    let to = day(&params.to, storage::now_secs() / 86_400)?;
+    let from = day(&params.from, to.saturating_sub(DEFAULT_RANGE_DAYS))?;
+    if from > to {
+        return Err((StatusCode::BAD_REQUEST, "from is after to".to_string()));
+    }
+    if to - from > MAX_RANGE_DAYS {
+        return Err((StatusCode::BAD_REQUEST, format!("the range is longer than {MAX_RANGE_DAYS} days")));
+    }
+
+    storage
+        .rollups(&iso_date(from * 86_400), &iso_date(to * 86_400))
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
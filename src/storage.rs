@@ -0,0 +1,1081 @@
+use rusqlite::{Connection, OptionalExtension, params};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use crate::{
+    annotations::Annotation,
+    avatars::Avatar,
+    stats::DailyRollup,
+    blobs::BlobAnalysis,
+    certificates::Certificate,
+    privacy::OptOut,
+    ruleset::{self, ScanInputs},
+    scan::{TreeEntry, UserMoveFilesResponse},
+};
+
+// ------------------- Storage -------------------
+
+/// SQLite-backed store of completed scans. Cheap to clone; all clones share
+/// one connection, and every operation holds the lock only for a single
+/// short statement.
+#[derive(Clone)]
+pub struct Storage {
+    conn: Arc<Mutex<Connection>>,
+}
+
+/// Rows removed by [`Storage::delete_user_data`].
+#[derive(Debug, Default, Serialize)]
+pub struct DeletedUserData {
+    pub scans: usize,
+    /// Other users' scans that merged `username` in as an alias.
+    pub alias_scans: usize,
+    pub move_files: usize,
+    pub wallets: usize,
+    pub certificates: usize,
+    pub repo_cursors: usize,
+    pub labels: usize,
+    pub annotations: usize,
+    pub avatars: usize,
+    pub non_developer: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateRecord {
+    pub id: i64,
+    pub repo: String,
+    pub added_at: u64,
+    pub fingerprinted_at: Option<u64>,
+    pub file_count: u32,
+}
+
+/// Winnowing fingerprints of one Move file, see `similarity`.
+#[derive(Debug)]
+pub struct StoredMoveFile {
+    pub repo: String,
+    pub path: String,
+    pub hashes: Vec<i64>,
+}
+
+#[derive(Debug)]
+pub struct SimilarFile {
+    pub username: String,
+    pub repo: String,
+    pub path: String,
+    pub hash_count: usize,
+    /// Fingerprints shared with the queried file.
+    pub shared: usize,
+}
+
+/// A stored scan with the analysis inputs kept beside it, if any.
+#[derive(Debug)]
+pub struct ScanWithInputs {
+    pub id: i64,
+    pub result: UserMoveFilesResponse,
+    pub inputs: Option<ScanInputs>,
+}
+
+#[derive(Debug)]
+pub struct StoredScan {
+    pub username: String,
+    pub scanned_at: u64,
+    pub result: UserMoveFilesResponse,
+}
+
+/// Opens the database named by `DATABASE_PATH` (default `sui_contributors.db`).
+pub fn open_from_env() -> Result<Storage, Box<dyn std::error::Error + Send + Sync>> {
+    let path = std::env::var("DATABASE_PATH").unwrap_or_else(|_| "sui_contributors.db".to_string());
+    Storage::open(path)
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl Storage {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode = WAL;
+            PRAGMA foreign_keys = ON;
+            CREATE TABLE IF NOT EXISTS scans (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                username    TEXT NOT NULL COLLATE NOCASE,
+                scanned_at  INTEGER NOT NULL,
+                result      TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS scans_username ON scans (username, id);
+            CREATE TABLE IF NOT EXISTS scan_inputs (
+                scan_id  INTEGER PRIMARY KEY REFERENCES scans (id) ON DELETE CASCADE,
+                inputs   TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS templates (
+                id                INTEGER PRIMARY KEY AUTOINCREMENT,
+                repo              TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                added_at          INTEGER NOT NULL,
+                fingerprinted_at  INTEGER,
+                file_count        INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS template_fingerprints (
+                template_id  INTEGER NOT NULL REFERENCES templates (id) ON DELETE CASCADE,
+                blob_sha     TEXT NOT NULL,
+                path         TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS template_fingerprints_sha ON template_fingerprints (blob_sha);
+
+            CREATE TABLE IF NOT EXISTS move_files (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                username    TEXT NOT NULL COLLATE NOCASE,
+                repo        TEXT NOT NULL,
+                path        TEXT NOT NULL,
+                hash_count  INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS move_files_username ON move_files (username);
+            CREATE TABLE IF NOT EXISTS move_file_hashes (
+                file_id  INTEGER NOT NULL REFERENCES move_files (id) ON DELETE CASCADE,
+                hash     INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS move_file_hashes_hash ON move_file_hashes (hash);
+            CREATE INDEX IF NOT EXISTS move_file_hashes_file ON move_file_hashes (file_id);
+            CREATE TABLE IF NOT EXISTS wallets (
+                username  TEXT NOT NULL COLLATE NOCASE,
+                address   TEXT NOT NULL COLLATE NOCASE,
+                bound_at  INTEGER NOT NULL,
+                PRIMARY KEY (username, address)
+            );
+            CREATE TABLE IF NOT EXISTS non_developers (
+                username      TEXT PRIMARY KEY COLLATE NOCASE,
+                confirmed_at  INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS blob_analyses (
+                sha          TEXT PRIMARY KEY,
+                version      INTEGER NOT NULL,
+                analyzed_at  INTEGER NOT NULL,
+                analysis     TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS repo_cursors (
+                username   TEXT NOT NULL COLLATE NOCASE,
+                repo       TEXT NOT NULL COLLATE NOCASE,
+                pushed_at  TEXT NOT NULL,
+                PRIMARY KEY (username, repo)
+            );
+            CREATE TABLE IF NOT EXISTS certificates (
+                id          TEXT PRIMARY KEY,
+                username    TEXT NOT NULL COLLATE NOCASE,
+                policy      TEXT NOT NULL,
+                score       REAL,
+                issued_at   INTEGER NOT NULL,
+                expires_at  INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS certificates_username ON certificates (username, issued_at);
+            CREATE TABLE IF NOT EXISTS labels (
+                username  TEXT NOT NULL COLLATE NOCASE,
+                label     TEXT NOT NULL COLLATE NOCASE,
+                added_at  INTEGER NOT NULL,
+                PRIMARY KEY (username, label)
+            );
+            CREATE INDEX IF NOT EXISTS labels_label ON labels (label);
+            CREATE TABLE IF NOT EXISTS annotations (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                username    TEXT NOT NULL COLLATE NOCASE,
+                annotation  TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS annotations_username ON annotations (username, id);
+            CREATE TABLE IF NOT EXISTS ecosystem_rollups (
+                date    TEXT PRIMARY KEY,
+                rollup  TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS opt_outs (
+                username      TEXT PRIMARY KEY COLLATE NOCASE,
+                method        TEXT NOT NULL,
+                opted_out_at  INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS avatars (
+                username      TEXT NOT NULL COLLATE NOCASE,
+                size          INTEGER NOT NULL,
+                avatar_url    TEXT NOT NULL,
+                content_type  TEXT NOT NULL,
+                image         BLOB NOT NULL,
+                fetched_at    INTEGER NOT NULL,
+                PRIMARY KEY (username, size)
+            );
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                delivery_id  TEXT PRIMARY KEY,
+                received_at  INTEGER NOT NULL
+            );
+            CREATE VIRTUAL TABLE IF NOT EXISTS developer_search USING fts5 (username, repositories, packages);
+            "#,
+        )?;
+
+        let storage = Storage { conn: Arc::new(Mutex::new(conn)) };
+        let indexed: i64 = storage.conn().query_row("SELECT COUNT(*) FROM developer_search", [], |row| row.get(0))?;
+        if indexed == 0 {
+            storage.reindex_search()?;
+        }
+        Ok(storage)
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Runs `f` on tokio's blocking pool, so waiting for the connection lock
+    /// and reading or decoding many rows never stalls an async worker.
+    pub async fn blocking<T, F>(&self, f: F) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Storage) -> Result<T, Box<dyn std::error::Error + Send + Sync>> + Send + 'static,
+    {
+        let storage = self.clone();
+        tokio::task::spawn_blocking(move || f(&storage)).await?
+    }
+
+    /// Cheap liveness check used by `doctor`.
+    pub fn ping(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conn().query_row("SELECT 1", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    pub fn save_scan(&self, result: &UserMoveFilesResponse) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(result)?;
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO scans (username, scanned_at, result) VALUES (?1, ?2, ?3)",
+            params![result.username, now_secs() as i64, json],
+        )?;
+        let inputs = ruleset::inputs(result);
+        if !inputs.is_empty() {
+            conn.execute(
+                "INSERT INTO scan_inputs (scan_id, inputs) VALUES (?1, ?2)",
+                params![conn.last_insert_rowid(), serde_json::to_string(&inputs)?],
+            )?;
+        }
+        index_for_search(&conn, result)?;
+        Ok(())
+    }
+
+    /// Up to `limit` stored scans with an id above `after_id`, oldest first,
+    /// with their analysis inputs when kept; only `username`'s if given.
+    pub fn scans_with_inputs(
+        &self,
+        after_id: i64,
+        limit: usize,
+        username: Option<&str>,
+    ) -> Result<Vec<ScanWithInputs>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(i64, String, Option<String>)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT s.id, s.result, i.inputs
+                FROM scans s LEFT JOIN scan_inputs i ON i.scan_id = s.id
+                WHERE s.id > ?1 AND (?2 IS NULL OR s.username = ?2)
+                ORDER BY s.id
+                LIMIT ?3
+                "#,
+            )?;
+            stmt.query_map(params![after_id, username, limit as i64], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<_, _>>()?
+        };
+        rows.into_iter()
+            .map(|(id, result, inputs)| {
+                let inputs = inputs.map(|raw| serde_json::from_str(&raw)).transpose()?;
+                Ok(ScanWithInputs { id, result: serde_json::from_str(&result)?, inputs })
+            })
+            .collect()
+    }
+
+    /// Replaces the result of stored scan `id`, keeping when it was taken.
+    pub fn update_scan_result(&self, id: i64, result: &UserMoveFilesResponse) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_string(result)?;
+        self.conn().execute("UPDATE scans SET result = ?1 WHERE id = ?2", params![json, id])?;
+        Ok(())
+    }
+
+    /// Rebuilds the developer search index from the latest scan of every user.
+    pub fn reindex_search(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let scans = self.latest_scans()?;
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM developer_search", [])?;
+        for scan in &scans {
+            index_for_search(&tx, &scan.result)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Usernames whose name, repository names or Move package names match
+    /// the FTS5 `query`, best match first.
+    pub fn search_developers(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT username FROM developer_search WHERE developer_search MATCH ?1 ORDER BY rank")?;
+        let users = stmt.query_map(params![query], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(users)
+    }
+
+    pub fn latest_scan(&self, username: &str) -> Result<Option<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let row = self
+            .conn()
+            .query_row(
+                "SELECT username, scanned_at, result FROM scans WHERE username = ?1 ORDER BY id DESC LIMIT 1",
+                params![username],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)),
+            )
+            .optional()?;
+
+        row.map(decode_scan).transpose()
+    }
+
+    /// Every stored scan of `username`, oldest first.
+    pub fn scan_history(&self, username: &str) -> Result<Vec<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(String, i64, String)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare("SELECT username, scanned_at, result FROM scans WHERE username = ?1 ORDER BY id")?;
+            stmt.query_map(params![username], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<_, _>>()?
+        };
+
+        rows.into_iter().map(decode_scan).collect()
+    }
+
+    /// The most recent scan of every stored user.
+    pub fn latest_scans(&self) -> Result<Vec<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        self.latest_scans_where("1", [])
+    }
+
+    /// The latest scans of users who have not opted out and have Move code
+    /// in a repository with at least `min_commits` commits. Filtered in SQL,
+    /// so only the scans returned are decoded.
+    pub fn discoverable_developers(&self, min_commits: u32) -> Result<Vec<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        self.latest_scans_where(
+            r#"
+            json_extract(s.result, '$.has_move_files') = 1
+            AND EXISTS (SELECT 1 FROM json_each(s.result, '$.repositories') WHERE json_extract(value, '$.commit_count') >= ?1)
+            AND lower(s.username) NOT IN (SELECT lower(username) FROM opt_outs)
+            "#,
+            params![min_commits],
+        )
+    }
+
+    fn latest_scans_where(
+        &self,
+        condition: &str,
+        args: impl rusqlite::Params,
+    ) -> Result<Vec<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(String, i64, String)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(&format!(
+                r#"
+                SELECT s.username, s.scanned_at, s.result
+                FROM scans s
+                JOIN (SELECT MAX(id) AS id FROM scans GROUP BY username) latest ON latest.id = s.id
+                WHERE {condition}
+                ORDER BY s.username
+                "#
+            ))?;
+            stmt.query_map(args, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<_, _>>()?
+        };
+
+        rows.into_iter().map(decode_scan).collect()
+    }
+
+    /// The most recent scan of each of `usernames` that has one, in no
+    /// particular order.
+    pub fn latest_scans_of(&self, usernames: &[String]) -> Result<Vec<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(String, i64, String)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare(
+                r#"
+                SELECT s.username, s.scanned_at, s.result
+                FROM scans s
+                JOIN (
+                    SELECT MAX(id) AS id FROM scans
+                    WHERE username IN (SELECT value FROM json_each(?1))
+                    GROUP BY username
+                ) latest ON latest.id = s.id
+                "#,
+            )?;
+            stmt.query_map(params![serde_json::to_string(usernames)?], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+                .collect::<Result<_, _>>()?
+        };
+
+        rows.into_iter().map(decode_scan).collect()
+    }
+
+    /// The latest stored scans of those of `usernames` who have not opted
+    /// out, for public rankings and discovery.
+    pub fn discoverable_scans_of(&self, usernames: &[String]) -> Result<Vec<StoredScan>, Box<dyn std::error::Error + Send + Sync>> {
+        let opted_out = self.opted_out()?;
+        let mut scans = self.latest_scans_of(usernames)?;
+        scans.retain(|s| !opted_out.contains(&s.username.to_lowercase()));
+        Ok(scans)
+    }
+
+    /// Registers a template repository (`owner/repo`); re-adding is a no-op.
+    /// Returns the template id.
+    pub fn add_template(&self, repo: &str) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT OR IGNORE INTO templates (repo, added_at) VALUES (?1, ?2)",
+            params![repo, now_secs() as i64],
+        )?;
+        Ok(conn.query_row("SELECT id FROM templates WHERE repo = ?1", params![repo], |row| row.get(0))?)
+    }
+
+    /// Removes a template and its fingerprints. Returns whether it existed.
+    pub fn remove_template(&self, id: i64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.conn().execute("DELETE FROM templates WHERE id = ?1", params![id])? > 0)
+    }
+
+    pub fn list_templates(&self) -> Result<Vec<TemplateRecord>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, repo, added_at, fingerprinted_at, file_count FROM templates ORDER BY id",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(TemplateRecord {
+                    id: row.get(0)?,
+                    repo: row.get(1)?,
+                    added_at: row.get::<_, i64>(2)?.max(0) as u64,
+                    fingerprinted_at: row.get::<_, Option<i64>>(3)?.map(|t| t.max(0) as u64),
+                    file_count: row.get(4)?,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Replaces the stored fingerprints of a template with `files`.
+    pub fn set_template_fingerprints(&self, id: i64, files: &[TreeEntry]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM template_fingerprints WHERE template_id = ?1", params![id])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO template_fingerprints (template_id, blob_sha, path) VALUES (?1, ?2, ?3)",
+            )?;
+            for file in files {
+                insert.execute(params![id, file.sha, file.path])?;
+            }
+        }
+        tx.execute(
+            "UPDATE templates SET fingerprinted_at = ?2, file_count = ?3 WHERE id = ?1",
+            params![id, now_secs() as i64, files.len() as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Every template fingerprint as `(blob_sha, template repo)`.
+    pub fn template_fingerprints(&self) -> Result<Vec<(String, String)>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT f.blob_sha, t.repo FROM template_fingerprints f JOIN templates t ON t.id = f.template_id",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Replaces every stored Move file fingerprint of `username` with `files`.
+    pub fn replace_move_files(&self, username: &str, files: &[StoredMoveFile]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM move_files WHERE username = ?1", params![username])?;
+        {
+            let mut insert_file = tx.prepare(
+                "INSERT INTO move_files (username, repo, path, hash_count) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut insert_hash = tx.prepare("INSERT INTO move_file_hashes (file_id, hash) VALUES (?1, ?2)")?;
+            for file in files {
+                insert_file.execute(params![username, file.repo, file.path, file.hashes.len() as i64])?;
+                let file_id = tx.last_insert_rowid();
+                for hash in &file.hashes {
+                    insert_hash.execute(params![file_id, hash])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Files of users other than `exclude_username` sharing at least one of
+    /// `hashes`, with the number of shared fingerprints.
+    pub fn similar_move_files(&self, hashes: &[i64], exclude_username: &str) -> Result<Vec<SimilarFile>, Box<dyn std::error::Error + Send + Sync>> {
+        if hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Keep well inside SQLite's bound-parameter limit.
+        let hashes = &hashes[..hashes.len().min(2000)];
+        let placeholders = vec!["?"; hashes.len()].join(",");
+        let sql = format!(
+            r#"
+            SELECT f.username, f.repo, f.path, f.hash_count, COUNT(DISTINCT h.hash) AS shared
+            FROM move_file_hashes h
+            JOIN move_files f ON f.id = h.file_id
+            WHERE h.hash IN ({placeholders}) AND f.username <> ?
+            GROUP BY f.id
+            ORDER BY shared DESC
+            LIMIT 50
+            "#
+        );
+
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&sql)?;
+        let mut values: Vec<rusqlite::types::Value> = hashes.iter().map(|h| (*h).into()).collect();
+        values.push(exclude_username.to_string().into());
+
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(values), |row| {
+                Ok(SimilarFile {
+                    username: row.get(0)?,
+                    repo: row.get(1)?,
+                    path: row.get(2)?,
+                    hash_count: row.get::<_, i64>(3)?.max(0) as usize,
+                    shared: row.get::<_, i64>(4)?.max(0) as usize,
+                })
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(rows)
+    }
+
+    /// Replaces the Sui wallet addresses bound to `username`.
+    pub fn set_wallets(&self, username: &str, addresses: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM wallets WHERE username = ?1", params![username])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO wallets (username, address, bound_at) VALUES (?1, ?2, ?3)",
+            )?;
+            for address in addresses {
+                insert.execute(params![username, address, now_secs() as i64])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replaces the labels attached to `username`.
+    pub fn set_labels(&self, username: &str, labels: &[String]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM labels WHERE username = ?1", params![username])?;
+        {
+            let mut insert = tx.prepare("INSERT OR IGNORE INTO labels (username, label, added_at) VALUES (?1, ?2, ?3)")?;
+            for label in labels {
+                insert.execute(params![username, label, now_secs() as i64])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Labels attached to `username`, in the order they were added.
+    pub fn labels(&self, username: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT label FROM labels WHERE username = ?1 ORDER BY added_at, label")?;
+        let labels = stmt.query_map(params![username], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(labels)
+    }
+
+    /// Every labelled user with their labels, keyed by lowercased username.
+    pub fn all_labels(&self) -> Result<HashMap<String, Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT username, label FROM labels ORDER BY added_at, label")?;
+        let mut labels: HashMap<String, Vec<String>> = HashMap::new();
+        for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))? {
+            let (username, label) = row?;
+            labels.entry(username.to_lowercase()).or_default().push(label);
+        }
+        Ok(labels)
+    }
+
+    /// Users carrying `label`, by username.
+    pub fn labelled_users(&self, label: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT username FROM labels WHERE label = ?1 ORDER BY username")?;
+        let users = stmt.query_map(params![label], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        Ok(users)
+    }
+
+    /// Stores a reviewer annotation on `username` and returns its id.
+    pub fn add_annotation(&self, username: &str, annotation: &Annotation) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO annotations (username, annotation) VALUES (?1, ?2)",
+            params![username, serde_json::to_string(annotation)?],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Annotations on `username`, oldest first.
+    pub fn annotations(&self, username: &str) -> Result<Vec<Annotation>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<(i64, String)> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare("SELECT id, annotation FROM annotations WHERE username = ?1 ORDER BY id")?;
+            stmt.query_map(params![username], |row| Ok((row.get(0)?, row.get(1)?)))?.collect::<Result<_, _>>()?
+        };
+        rows.into_iter()
+            .map(|(id, json)| Ok(Annotation { id, ..serde_json::from_str(&json)? }))
+            .collect()
+    }
+
+    /// Deletes annotation `id` of `username`; false when there is none.
+    pub fn remove_annotation(&self, username: &str, id: i64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.conn().execute("DELETE FROM annotations WHERE id = ?1 AND username = ?2", params![id, username])? > 0)
+    }
+
+    /// Stores the ecosystem rollup of `rollup.date`, replacing an earlier one.
+    pub fn save_rollup(&self, rollup: &DailyRollup) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO ecosystem_rollups (date, rollup) VALUES (?1, ?2)",
+            params![rollup.date, serde_json::to_string(rollup)?],
+        )?;
+        Ok(())
+    }
+
+    pub fn has_rollup(&self, date: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let found: Option<i64> = self
+            .conn()
+            .query_row("SELECT 1 FROM ecosystem_rollups WHERE date = ?1", params![date], |row| row.get(0))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Records webhook delivery `delivery_id`; false when it was already
+    /// applied, as with GitHub's automatic and manual redeliveries. IDs older
+    /// than `keep_secs` are forgotten.
+    pub fn record_delivery(&self, delivery_id: &str, keep_secs: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let now = now_secs();
+        let conn = self.conn();
+        conn.execute("DELETE FROM webhook_deliveries WHERE received_at < ?1", params![now.saturating_sub(keep_secs) as i64])?;
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO webhook_deliveries (delivery_id, received_at) VALUES (?1, ?2)",
+            params![delivery_id, now as i64],
+        )?;
+        Ok(inserted == 1)
+    }
+
+    /// Rollups dated `from` to `to` (`YYYY-MM-DD`, inclusive), oldest first.
+    pub fn rollups(&self, from: &str, to: &str) -> Result<Vec<DailyRollup>, Box<dyn std::error::Error + Send + Sync>> {
+        let rows: Vec<String> = {
+            let conn = self.conn();
+            let mut stmt = conn.prepare("SELECT rollup FROM ecosystem_rollups WHERE date BETWEEN ?1 AND ?2 ORDER BY date")?;
+            stmt.query_map(params![from, to], |row| row.get(0))?.collect::<Result<_, _>>()?
+        };
+        rows.iter().map(|json| Ok(serde_json::from_str(json)?)).collect()
+    }
+
+    /// Records that a developer opted out of public listings, replacing any
+    /// earlier record.
+    pub fn save_opt_out(&self, opt_out: &OptOut) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO opt_outs (username, method, opted_out_at) VALUES (?1, ?2, ?3)",
+            params![opt_out.username, serde_json::to_value(opt_out.method)?.as_str(), opt_out.opted_out_at as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn avatar(&self, username: &str, size: u32) -> Result<Option<Avatar>, Box<dyn std::error::Error + Send + Sync>> {
+        let avatar = self
+            .conn()
+            .query_row(
+                "SELECT avatar_url, content_type, image, fetched_at FROM avatars WHERE username = ?1 AND size = ?2",
+                params![username, size],
+                |row| {
+                    Ok(Avatar {
+                        avatar_url: row.get(0)?,
+                        content_type: row.get(1)?,
+                        image: row.get(2)?,
+                        fetched_at: row.get::<_, i64>(3)?.max(0) as u64,
+                    })
+                },
+            )
+            .optional()?;
+        Ok(avatar)
+    }
+
+    pub fn save_avatar(&self, username: &str, size: u32, avatar: &Avatar) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO avatars (username, size, avatar_url, content_type, image, fetched_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![username, size, avatar.avatar_url, avatar.content_type, avatar.image, avatar.fetched_at as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes `username`'s cached avatars fetched from another URL than
+    /// `avatar_url`. Returns the number removed.
+    pub fn invalidate_avatars(&self, username: &str, avatar_url: &str) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let removed = self
+            .conn()
+            .execute("DELETE FROM avatars WHERE username = ?1 AND avatar_url != ?2", params![username, avatar_url])?;
+        Ok(removed)
+    }
+
+    /// Lowercased usernames of every developer who opted out.
+    pub fn opted_out(&self) -> Result<HashSet<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT username FROM opt_outs")?;
+        let users = stmt.query_map([], |row| row.get::<_, String>(0))?.map(|u| u.map(|u| u.to_lowercase())).collect::<Result<_, _>>()?;
+        Ok(users)
+    }
+
+    /// Stored analysis of the blob `sha`, if it was made by analysis `version`.
+    pub fn blob_analysis(&self, sha: &str, version: u32) -> Result<Option<BlobAnalysis>, Box<dyn std::error::Error + Send + Sync>> {
+        let json: Option<String> = self
+            .conn()
+            .query_row(
+                "SELECT analysis FROM blob_analyses WHERE sha = ?1 AND version = ?2",
+                params![sha, version],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+    }
+
+    pub fn save_blob_analysis(&self, sha: &str, version: u32, analysis: &BlobAnalysis) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO blob_analyses (sha, version, analyzed_at, analysis) VALUES (?1, ?2, ?3, ?4)",
+            params![sha, version, now_secs() as i64, serde_json::to_string(analysis)?],
+        )?;
+        Ok(())
+    }
+
+    /// Replaces the `pushedAt` cursors recorded for `username`'s repositories.
+    pub fn set_repo_cursors(&self, username: &str, cursors: &[(String, String)]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM repo_cursors WHERE username = ?1", params![username])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR REPLACE INTO repo_cursors (username, repo, pushed_at) VALUES (?1, ?2, ?3)",
+            )?;
+            for (repo, pushed_at) in cursors {
+                insert.execute(params![username, repo, pushed_at])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Repository (lowercase) -> `pushedAt` as of `username`'s last stored scan.
+    pub fn repo_cursors(&self, username: &str) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT repo, pushed_at FROM repo_cursors WHERE username = ?1")?;
+        let cursors = stmt
+            .query_map(params![username], |row| Ok((row.get::<_, String>(0)?.to_lowercase(), row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(cursors)
+    }
+
+    pub fn save_certificate(&self, certificate: &Certificate) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.conn().execute(
+            "INSERT INTO certificates (id, username, policy, score, issued_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                certificate.id,
+                certificate.username,
+                certificate.policy,
+                certificate.score,
+                certificate.issued_at as i64,
+                certificate.expires_at as i64
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn certificate(&self, id: &str) -> Result<Option<Certificate>, Box<dyn std::error::Error + Send + Sync>> {
+        let certificate = self
+            .conn()
+            .query_row(
+                "SELECT id, username, policy, score, issued_at, expires_at FROM certificates WHERE id = ?1",
+                params![id],
+                decode_certificate,
+            )
+            .optional()?;
+        Ok(certificate)
+    }
+
+    /// Certificates issued to `username`, newest first.
+    pub fn certificates(&self, username: &str) -> Result<Vec<Certificate>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare(
+            "SELECT id, username, policy, score, issued_at, expires_at FROM certificates
+             WHERE username = ?1 ORDER BY issued_at DESC, id",
+        )?;
+        let certificates = stmt.query_map(params![username], decode_certificate)?.collect::<Result<_, _>>()?;
+        Ok(certificates)
+    }
+
+    /// Deletes every stored scan and the non-developer list. Returns the
+    /// number of scans removed.
+    pub fn flush_scans(&self) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let removed = tx.execute("DELETE FROM scans", [])?;
+        tx.execute("DELETE FROM non_developers", [])?;
+        tx.execute("DELETE FROM repo_cursors", [])?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Deletes everything stored about `username`: scans, Move file
+    /// fingerprints, wallet bindings, certificates, repository cursors,
+    /// labels, reviewer annotations and the non-developer mark. Scans that
+    /// merged `username` in as an alias go too, along with the fingerprints
+    /// and cursors they recorded for its repositories.
+    pub fn delete_user_data(&self, username: &str) -> Result<DeletedUserData, Box<dyn std::error::Error + Send + Sync>> {
+        const LISTS_ALIAS: &str =
+            "EXISTS (SELECT 1 FROM json_each(scans.result, '$.aliases') WHERE value = ?1 COLLATE NOCASE)";
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let merging: Vec<String> = {
+            let mut stmt = tx.prepare(&format!("SELECT DISTINCT username FROM scans WHERE {LISTS_ALIAS}"))?;
+            stmt.query_map(params![username], |row| row.get(0))?.collect::<Result<_, _>>()?
+        };
+        let alias_scans = tx.execute(&format!("DELETE FROM scans WHERE {LISTS_ALIAS}"), params![username])?;
+        // A prefix comparison rather than LIKE, where `%` or `_` in the name
+        // would be a wildcard; case-insensitive, as logins are.
+        let alias_repos = format!("{username}/");
+        for owner in &merging {
+            for table in ["move_files", "repo_cursors"] {
+                tx.execute(
+                    &format!("DELETE FROM {table} WHERE username = ?1 AND lower(substr(repo, 1, length(?2))) = lower(?2)"),
+                    params![owner, alias_repos],
+                )?;
+            }
+            let latest: Option<String> = tx
+                .query_row("SELECT result FROM scans WHERE username = ?1 ORDER BY id DESC LIMIT 1", params![owner], |row| row.get(0))
+                .optional()?;
+            tx.execute("DELETE FROM developer_search WHERE lower(username) = lower(?1)", params![owner])?;
+            if let Some(latest) = latest {
+                index_for_search(&tx, &serde_json::from_str(&latest)?)?;
+            }
+        }
+        let deleted = DeletedUserData {
+            scans: tx.execute("DELETE FROM scans WHERE username = ?1", params![username])?,
+            alias_scans,
+            move_files: tx.execute("DELETE FROM move_files WHERE username = ?1", params![username])?,
+            wallets: tx.execute("DELETE FROM wallets WHERE username = ?1", params![username])?,
+            certificates: tx.execute("DELETE FROM certificates WHERE username = ?1", params![username])?,
+            repo_cursors: tx.execute("DELETE FROM repo_cursors WHERE username = ?1", params![username])?,
+            labels: tx.execute("DELETE FROM labels WHERE username = ?1", params![username])?,
+            annotations: tx.execute("DELETE FROM annotations WHERE username = ?1", params![username])?,
+            avatars: tx.execute("DELETE FROM avatars WHERE username = ?1", params![username])?,
+            non_developer: tx.execute("DELETE FROM non_developers WHERE username = ?1", params![username])? > 0,
+        };
+        tx.execute("DELETE FROM developer_search WHERE lower(username) = lower(?1)", params![username])?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Deletes scans, non-developer marks, avatars and certificates recorded
+    /// before `cutoff_secs`, then the Move file fingerprints and repository
+    /// cursors of users left without a scan. Returns the number of scans
+    /// removed.
+    pub fn prune_before(&self, cutoff_secs: u64) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let removed = tx.execute("DELETE FROM scans WHERE scanned_at < ?1", params![cutoff_secs as i64])?;
+        tx.execute("DELETE FROM non_developers WHERE confirmed_at < ?1", params![cutoff_secs as i64])?;
+        tx.execute("DELETE FROM avatars WHERE fetched_at < ?1", params![cutoff_secs as i64])?;
+        tx.execute("DELETE FROM certificates WHERE issued_at < ?1", params![cutoff_secs as i64])?;
+        for table in ["move_files", "repo_cursors"] {
+            tx.execute(&format!("DELETE FROM {table} WHERE username NOT IN (SELECT username FROM scans)"), [])?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Records whether the latest scan of `username` found Move code; users
+    /// without any are remembered so batch jobs can skip them.
+    pub fn set_non_developer(&self, username: &str, non_developer: bool) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if non_developer {
+            self.conn().execute(
+                "INSERT OR REPLACE INTO non_developers (username, confirmed_at) VALUES (?1, ?2)",
+                params![username, now_secs() as i64],
+            )?;
+        } else {
+            self.conn().execute("DELETE FROM non_developers WHERE username = ?1", params![username])?;
+        }
+        Ok(())
+    }
+
+    /// Whether `username` was confirmed to have no Move code within `max_age_secs`.
+    pub fn is_known_non_developer(&self, username: &str, max_age_secs: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let cutoff = now_secs().saturating_sub(max_age_secs) as i64;
+        let found = self
+            .conn()
+            .query_row(
+                "SELECT 1 FROM non_developers WHERE username = ?1 AND confirmed_at >= ?2",
+                params![username, cutoff],
+                |_| Ok(()),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// Wallet addresses bound to any of `usernames`, deduplicated.
+    pub fn wallets(&self, usernames: &[String]) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        let conn = self.conn();
+        let mut stmt = conn.prepare("SELECT address FROM wallets WHERE username = ?1 ORDER BY bound_at, address")?;
+        let mut addresses: Vec<String> = Vec::new();
+        for username in usernames {
+            for address in stmt.query_map(params![username], |row| row.get::<_, String>(0))? {
+                let address = address?;
+                if !addresses.iter().any(|a| a.eq_ignore_ascii_case(&address)) {
+                    addresses.push(address);
+                }
+            }
+        }
+        Ok(addresses)
+    }
+}
+
+// ------------------- Snapshots -------------------
+
+/// Every table, each after the tables it references, as snapshots name them.
+/// Cached avatars and the search index are left out; both rebuild on demand.
+pub const TABLES: &[&str] = &[
+    "scans",
+    "scan_inputs",
+    "templates",
+    "template_fingerprints",
+    "move_files",
+    "move_file_hashes",
+    "wallets",
+    "non_developers",
+    "blob_analyses",
+    "repo_cursors",
+    "certificates",
+    "labels",
+    "annotations",
+    "ecosystem_rollups",
+    "opt_outs",
+];
+
+/// One row as column name -> value; the shape snapshots store tables in.
+pub type Row = serde_json::Map<String, serde_json::Value>;
+
+impl Storage {
+    /// Every row of `table` (one of [`TABLES`]) in insertion order.
+    pub fn export_table(&self, table: &str) -> Result<Vec<Row>, Box<dyn std::error::Error + Send + Sync>> {
+        if !TABLES.contains(&table) {
+            return Err(format!("unknown table {table}").into());
+        }
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {table} ORDER BY rowid"))?;
+        let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+        let rows = stmt.query_map([], |row| {
+            let mut values = Row::new();
+            for (i, column) in columns.iter().enumerate() {
+                let value = match row.get_ref(i)? {
+                    rusqlite::types::ValueRef::Null => serde_json::Value::Null,
+                    rusqlite::types::ValueRef::Integer(n) => n.into(),
+                    rusqlite::types::ValueRef::Real(x) => x.into(),
+                    rusqlite::types::ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned().into(),
+                    // No table declares a BLOB column; hex keeps stray ones readable.
+                    rusqlite::types::ValueRef::Blob(bytes) => hex::encode(bytes).into(),
+                };
+                values.insert(column.clone(), value);
+            }
+            Ok(values)
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Restores `tables` (name -> rows, as exported) in one transaction,
+    /// keeping row ids so references between tables survive. Every table
+    /// written to must be empty. Returns the number of rows inserted.
+    pub fn import_tables(&self, tables: &BTreeMap<String, Vec<Row>>) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(unknown) = tables.keys().find(|t| !TABLES.contains(&t.as_str())) {
+            return Err(format!("unknown table {unknown}").into());
+        }
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+        let mut inserted = 0;
+        for table in TABLES.iter().copied() {
+            let Some(rows) = tables.get(table).filter(|rows| !rows.is_empty()) else {
+                continue;
+            };
+            let existing: i64 = tx.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| row.get(0))?;
+            if existing > 0 {
+                return Err(format!("{table} already has {existing} rows; import into an empty database").into());
+            }
+            let known: Vec<String> = {
+                let mut stmt = tx.prepare(&format!("SELECT name FROM pragma_table_info('{table}')"))?;
+                stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?
+            };
+            for row in rows {
+                if let Some(unknown) = row.keys().find(|c| !known.contains(c)) {
+                    return Err(format!("{table} has no column {unknown}").into());
+                }
+                let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+                let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{i}")).collect();
+                let values = row.values().map(sql_value).collect::<Result<Vec<_>, _>>()?;
+                tx.execute(
+                    &format!("INSERT INTO {table} ({}) VALUES ({})", columns.join(", "), placeholders.join(", ")),
+                    rusqlite::params_from_iter(values),
+                )?;
+                inserted += 1;
+            }
+        }
+        tx.commit()?;
+        drop(conn);
+        self.reindex_search()?;
+        Ok(inserted)
+    }
+}
+
+/// Replaces the search index row of `result`'s user.
+fn index_for_search(conn: &Connection, result: &UserMoveFilesResponse) -> rusqlite::Result<()> {
+    let repositories: Vec<&str> = result.repositories.iter().map(|r| r.repo_name.as_str()).collect();
+    let packages: Vec<&str> = result.repositories.iter().flat_map(|r| &r.package_names).map(String::as_str).collect();
+    conn.execute("DELETE FROM developer_search WHERE lower(username) = lower(?1)", params![result.username])?;
+    conn.execute(
+        "INSERT INTO developer_search (username, repositories, packages) VALUES (?1, ?2, ?3)",
+        params![result.username, repositories.join(" "), packages.join(" ")],
+    )?;
+    Ok(())
+}
+
+fn sql_value(value: &serde_json::Value) -> Result<rusqlite::types::Value, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(match value {
+        serde_json::Value::Null => rusqlite::types::Value::Null,
+        serde_json::Value::Bool(b) => rusqlite::types::Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(n) => rusqlite::types::Value::Integer(n),
+            None => rusqlite::types::Value::Real(n.as_f64().ok_or("number out of range")?),
+        },
+        serde_json::Value::String(s) => rusqlite::types::Value::Text(s.clone()),
+        other => return Err(format!("cannot store {other} in a column").into()),
+    })
+}
+
+fn decode_certificate(row: &rusqlite::Row<'_>) -> rusqlite::Result<Certificate> {
+    Ok(Certificate {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        policy: row.get(2)?,
+        score: row.get(3)?,
+        issued_at: row.get::<_, i64>(4)?.max(0) as u64,
+        expires_at: row.get::<_, i64>(5)?.max(0) as u64,
+    })
+}
+
+fn decode_scan((username, scanned_at, result): (String, i64, String)) -> Result<StoredScan, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(StoredScan {
+        username,
+        scanned_at: scanned_at.max(0) as u64,
+        result: serde_json::from_str(&result)?,
+    })
+}
@@ -0,0 +1,85 @@
+use opentelemetry::{KeyValue, trace::TracerProvider as _};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    Resource,
+    trace::{Sampler, SdkTracerProvider},
+};
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+// ------------------- Tracing Setup -------------------
+
+/// Installs the global tracing subscriber: a stdout fmt layer, plus an OTLP
+/// export layer when `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+///
+/// Sampling is controlled by `OTEL_TRACES_SAMPLER_RATIO` (0.0 - 1.0, default 1.0)
+/// and the service name by `OTEL_SERVICE_NAME` (default `sui-contributors`).
+///
+/// The returned provider must be kept alive and shut down on exit so the
+/// batch exporter flushes pending spans.
+pub fn init() -> Option<SdkTracerProvider> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let fmt_layer = tracing_subscriber::fmt::layer();
+
+    let provider = match std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(endpoint) if !endpoint.is_empty() => match build_provider(&endpoint) {
+            Ok(provider) => Some(provider),
+            Err(e) => {
+                eprintln!("Failed to initialise OTLP exporter, continuing without it: {e}");
+                None
+            }
+        },
+        _ => None,
+    };
+
+    let otel_layer = provider
+        .as_ref()
+        .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer("sui-contributors")));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
+
+    provider
+}
+
+fn build_provider(endpoint: &str) -> Result<SdkTracerProvider, Box<dyn std::error::Error + Send + Sync>> {
+    let ratio = std::env::var("OTEL_TRACES_SAMPLER_RATIO")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(1.0)
+        .clamp(0.0, 1.0);
+
+    let service_name =
+        std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "sui-contributors".to_string());
+
+    // The HTTP exporter expects the full signal path, mirroring what the
+    // OTEL_EXPORTER_OTLP_ENDPOINT env var means in other SDKs.
+    let traces_endpoint = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint)
+        .build()?;
+
+    let resource = Resource::builder()
+        .with_service_name(service_name)
+        .with_attribute(KeyValue::new("service.version", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(ratio))))
+        .with_resource(resource)
+        .build())
+}
+
+/// Flushes and shuts down the OTLP exporter, if one was installed.
+pub fn shutdown(provider: Option<SdkTracerProvider>) {
+    if let Some(provider) = provider
+        && let Err(e) = provider.shutdown()
+    {
+        eprintln!("Failed to shut down tracer provider: {e}");
+    }
+}
@@ -0,0 +1,131 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    pacing,
+    scan::{self, ScanLimits, UserMoveFilesResponse},
+    state,
+    storage::Storage,
+};
+
+// ------------------- Structs -------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateMatch {
+    pub template: String,
+    pub matched_files: usize,
+    pub total_files: usize,
+}
+
+/// Fraction of a repository's Move files that must be template copies before
+/// it is flagged, unless overridden by `TEMPLATE_MATCH_THRESHOLD`.
+const DEFAULT_MATCH_THRESHOLD: f64 = 0.5;
+
+/// How often registered templates are re-fingerprinted, unless overridden by
+/// `TEMPLATE_REFRESH_SECS`.
+const DEFAULT_REFRESH_SECS: u64 = 6 * 60 * 60;
+
+// ------------------- Fingerprinting -------------------
+
+/// Records the blob SHA of every `.move` file in the template repository.
+/// Blob SHAs are content hashes, so verbatim copies share them.
+#[tracing::instrument(name = "templates.fingerprint", skip(client, token, storage))]
+pub async fn fingerprint_template(
+    client: &Client,
+    token: &str,
+    storage: &Storage,
+    id: i64,
+    repo: &str,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let max_entries = ScanLimits::ceiling().max_tree_entries;
+    let move_files: Vec<_> = scan::fetch_tree(client, token, repo, "HEAD", max_entries)
+        .await?
+        .into_iter()
+        .filter(|e| e.path.ends_with(".move"))
+        .collect();
+
+    storage.set_template_fingerprints(id, &move_files)?;
+    Ok(move_files.len())
+}
+
+async fn refresh_all(client: &Client, token: &str, storage: &Storage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let registered = storage.list_templates()?;
+    for template in registered {
+        match fingerprint_template(client, token, storage, template.id, &template.repo).await {
+            Ok(files) => tracing::info!("Fingerprinted template {} ({files} Move files)", template.repo),
+            Err(e) => tracing::warn!("Failed to fingerprint template {}: {e}", template.repo),
+        }
+        pacing::pause().await;
+    }
+    Ok(())
+}
+
+/// Spawns the background job that keeps template fingerprints current.
+pub fn spawn_refresh_job(client: Client, token: String, storage: Storage) {
+    let period = std::env::var("TEMPLATE_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_REFRESH_SECS);
+
+    state::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(period.max(60)));
+        loop {
+            interval.tick().await;
+            if let Err(e) = refresh_all(&client, &token, &storage).await {
+                tracing::warn!("Template refresh failed: {e}");
+            }
+        }
+    });
+}
+
+// ------------------- Matching -------------------
+
+/// Flags every repository in `result` whose Move files are mostly verbatim
+/// copies of a single registered template.
+pub fn flag_template_copies(storage: &Storage, result: &mut UserMoveFilesResponse) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let fingerprints = storage.template_fingerprints()?;
+    if fingerprints.is_empty() {
+        return Ok(());
+    }
+
+    let threshold = std::env::var("TEMPLATE_MATCH_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(DEFAULT_MATCH_THRESHOLD);
+
+    let mut index: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (sha, template) in &fingerprints {
+        index.entry(sha.as_str()).or_default().push(template.as_str());
+    }
+
+    for repo in &mut result.repositories {
+        if repo.move_files.is_empty() {
+            continue;
+        }
+
+        let mut per_template: HashMap<&str, usize> = HashMap::new();
+        for file in &repo.move_files {
+            for template in index.get(file.sha.as_str()).into_iter().flatten() {
+                // A template repository is not a copy of itself.
+                if !template.eq_ignore_ascii_case(&repo.repo_name) {
+                    *per_template.entry(template).or_default() += 1;
+                }
+            }
+        }
+
+        // Ties go to the first template by name, so the match is stable.
+        let best = per_template.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)));
+        if let Some((template, matched)) = best
+            && matched as f64 / repo.move_files.len() as f64 >= threshold
+        {
+            repo.template_match = Some(TemplateMatch {
+                template: template.to_string(),
+                matched_files: matched,
+                total_files: repo.move_files.len(),
+            });
+        }
+    }
+
+    Ok(())
+}
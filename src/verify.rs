@@ -0,0 +1,212 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{path::Path, process::Stdio, time::Duration};
+use tokio::process::Command;
+use tracing::Instrument;
+
+use crate::{
+    github, pacing, reporting,
+    scan::{RepositoryWithCommits, UserMoveFilesResponse, env_or},
+};
+
+// ------------------- Structs -------------------
+
+/// Outcome of building one Move package (a directory containing `Move.toml`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageBuild {
+    /// Package directory relative to the repository root (empty for the root).
+    pub path: String,
+    /// `None` when the build could not be attempted (repository too large,
+    /// clone failure, missing `sui` binary or timeout).
+    pub compiles: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Largest repository (GitHub's reported size, in KB) that will be cloned.
+const DEFAULT_MAX_REPO_KB: u64 = 50 * 1024;
+
+/// Upper bound on packages built per scan.
+const DEFAULT_MAX_PACKAGES: usize = 5;
+
+/// Wall-clock limit for each clone and each build.
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Compiler output kept in `error`, from the end of stderr.
+const MAX_ERROR_CHARS: usize = 500;
+
+/// Whether this deployment opted in to build verification with
+/// `VERIFY_BUILD_ENABLED`. Off by default.
+pub fn enabled() -> bool {
+    std::env::var("VERIFY_BUILD_ENABLED").is_ok_and(|v| matches!(v.trim(), "true" | "1" | "yes"))
+}
+
+fn sui_bin() -> String {
+    std::env::var("SUI_BIN").unwrap_or_else(|_| "sui".to_string())
+}
+
+// ------------------- Core Logic -------------------
+
+/// Shallow-clones each Move repository in the scan and runs `sui move build`
+/// on its packages, recording `compiles` per package. Builds run in a
+/// throwaway directory with a scrubbed environment and are bounded by
+/// `VERIFY_MAX_PACKAGES`, `VERIFY_MAX_REPO_KB` and `VERIFY_TIMEOUT_SECS`.
+/// `sui move build` runs build scripts from untrusted repositories with the
+/// service's privileges, so callers check [`enabled`] first.
+#[tracing::instrument(name = "verify_builds", skip_all, fields(username = %result.username))]
+pub async fn verify_builds(
+    client: &Client,
+    token: &str,
+    result: &mut UserMoveFilesResponse,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut budget = env_or("VERIFY_MAX_PACKAGES", DEFAULT_MAX_PACKAGES);
+
+    for repo in &mut result.repositories {
+        if budget == 0 {
+            break;
+        }
+        if repo.manifests.is_empty() {
+            continue;
+        }
+
+        let package_dirs: Vec<String> = repo
+            .manifests
+            .iter()
+            .take(budget)
+            .map(|m| m.path.strip_suffix("Move.toml").unwrap_or_default().trim_end_matches('/').to_string())
+            .collect();
+        budget -= package_dirs.len();
+
+        repo.packages = verify_repository(client, token, repo, &package_dirs).await?;
+        pacing::pause().await;
+    }
+
+    Ok(())
+}
+
+async fn verify_repository(
+    client: &Client,
+    token: &str,
+    repo: &RepositoryWithCommits,
+    package_dirs: &[String],
+) -> Result<Vec<PackageBuild>, Box<dyn std::error::Error + Send + Sync>> {
+    let unattempted = |reason: String| {
+        package_dirs
+            .iter()
+            .map(|path| PackageBuild { path: path.clone(), compiles: None, error: Some(reason.clone()) })
+            .collect()
+    };
+
+    let max_kb = env_or("VERIFY_MAX_REPO_KB", DEFAULT_MAX_REPO_KB);
+    let size_kb = fetch_repo_size_kb(client, token, &repo.repo_name).await?;
+    if size_kb > max_kb {
+        return Ok(unattempted(format!("repository is {size_kb} KB, above the {max_kb} KB limit")));
+    }
+
+    let timeout = Duration::from_secs(env_or("VERIFY_TIMEOUT_SECS", DEFAULT_TIMEOUT_SECS));
+    let workdir = tempfile::tempdir()?;
+    let checkout = workdir.path().join("repo");
+
+    let clone_url = format!("https://github.com/{}.git", repo.repo_name);
+    let mut clone = scrubbed(Command::new("git"), workdir.path());
+    clone.args(["clone", "--depth", "1", "--single-branch", "--quiet", &clone_url]).arg(&checkout);
+
+    if let Err(reason) = run(clone, timeout).instrument(tracing::info_span!("git.clone")).await {
+        return Ok(unattempted(format!("clone failed: {reason}")));
+    }
+
+    let mut builds = Vec::new();
+    for path in package_dirs {
+        let mut build = scrubbed(Command::new(sui_bin()), workdir.path());
+        build.args(["move", "build", "--path"]).arg(checkout.join(path));
+
+        let outcome = run(build, timeout).instrument(tracing::info_span!("sui.move_build", package = %path)).await;
+        builds.push(match outcome {
+            Ok(()) => PackageBuild { path: path.clone(), compiles: Some(true), error: None },
+            Err(RunError::Failed(stderr)) => PackageBuild { path: path.clone(), compiles: Some(false), error: Some(stderr) },
+            Err(reason) => PackageBuild { path: path.clone(), compiles: None, error: Some(reason.to_string()) },
+        });
+    }
+
+    Ok(builds)
+}
+
+async fn fetch_repo_size_kb(
+    client: &Client,
+    token: &str,
+    repo: &str,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("https://api.github.com/repos/{}", repo);
+    let resp = github::send(
+        github::EndpointClass::Repos,
+        github::get(client, token, &url),
+    )
+    .await?;
+
+    if !resp.status().is_success() {
+        reporting::github_response(&url, resp.status());
+        return Err(format!("Failed to fetch repository {repo}: {}", resp.status()).into());
+    }
+
+    let body: serde_json::Value = resp.json().await?;
+    Ok(body["size"].as_u64().unwrap_or(0))
+}
+
+// ------------------- Child Processes -------------------
+
+/// Runs `command` inside `dir` with only `PATH` inherited, `HOME` pointed at
+/// `dir` and no interactive git prompts. This keeps the service's secrets out
+/// of the child's environment; it is not a sandbox, and the child can still
+/// read and write whatever the service's user can.
+pub fn scrubbed(mut command: Command, dir: &Path) -> Command {
+    command
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    command
+}
+
+pub enum RunError {
+    Spawn(std::io::Error),
+    TimedOut(Duration),
+    Failed(String),
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Spawn(e) => write!(f, "could not start process: {e}"),
+            RunError::TimedOut(limit) => write!(f, "timed out after {}s", limit.as_secs()),
+            RunError::Failed(stderr) => f.write_str(stderr),
+        }
+    }
+}
+
+pub async fn run(command: Command, timeout: Duration) -> Result<(), RunError> {
+    run_output(command, timeout).await.map(drop)
+}
+
+/// Like [`run`], returning what the process wrote to stdout.
+pub async fn run_output(mut command: Command, timeout: Duration) -> Result<String, RunError> {
+    let child = command.stdout(Stdio::piped()).spawn().map_err(RunError::Spawn)?;
+
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output.map_err(RunError::Spawn)?,
+        Err(_) => return Err(RunError::TimedOut(timeout)),
+    };
+
+    if output.status.success() {
+        return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    let tail: String = stderr.chars().rev().take(MAX_ERROR_CHARS).collect::<Vec<_>>().into_iter().rev().collect();
+    Err(RunError::Failed(if tail.is_empty() { format!("exited with {}", output.status) } else { tail }))
+}
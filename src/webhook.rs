@@ -0,0 +1,282 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    authorship,
+    scan::{self, ScanLimits, ScanMode, ScanOptions},
+    state::AppState,
+    storage::Storage,
+};
+
+// ------------------- Payloads -------------------
+
+#[derive(Debug, Deserialize)]
+struct Repository {
+    full_name: String,
+    #[serde(default)]
+    default_branch: String,
+    owner: Owner,
+}
+
+#[derive(Debug, Deserialize)]
+struct Owner {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sender {
+    login: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushEvent {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    repository: Repository,
+    #[serde(default)]
+    commits: Vec<PushCommit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PushCommit {
+    id: String,
+    #[serde(default = "distinct_default")]
+    distinct: bool,
+    timestamp: Option<String>,
+    author: PushAuthor,
+    committer: PushAuthor,
+    #[serde(default)]
+    added: Vec<String>,
+    #[serde(default)]
+    modified: Vec<String>,
+}
+
+fn distinct_default() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct PushAuthor {
+    #[serde(default)]
+    name: String,
+    username: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateEvent {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    ref_type: String,
+    #[serde(default)]
+    master_branch: String,
+    repository: Repository,
+    sender: Sender,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct WebhookOutcome {
+    pub event: String,
+    /// Users whose stored scan was updated in place.
+    pub updated: Vec<String>,
+    /// Users scheduled for a background re-scan.
+    pub rescans: Vec<String>,
+    /// The delivery was already applied and is ignored.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub duplicate: bool,
+}
+
+/// How long applied delivery IDs are remembered; GitHub only redelivers
+/// events from the past few days.
+const DELIVERY_MEMORY_SECS: u64 = 7 * 24 * 60 * 60;
+
+// ------------------- Signature -------------------
+
+/// Checks `X-Hub-Signature-256` against an HMAC-SHA256 of the raw body.
+fn verify_signature(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let Some(signature) = headers
+        .get("X-Hub-Signature-256")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("sha256="))
+        .and_then(|v| hex::decode(v).ok())
+    else {
+        return false;
+    };
+
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    ring::hmac::verify(&key, body, &signature).is_ok()
+}
+
+/// Organizations whose repositories are followed even for untracked users
+/// (`WEBHOOK_ORGS`, comma-separated).
+fn watched_orgs() -> Vec<String> {
+    std::env::var("WEBHOOK_ORGS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|o| o.trim().to_lowercase())
+        .filter(|o| !o.is_empty())
+        .collect()
+}
+
+// ------------------- Handler -------------------
+
+/// Receives `push` and `create` events from a GitHub repository or
+/// organization webhook signed with `GITHUB_WEBHOOK_SECRET`. Pushes to the
+/// default branch of a repository already in a tracked user's stored scan
+/// update its commit counts in place; pushes that add Move code elsewhere,
+/// and new repositories of tracked users, schedule a re-scan. Each
+/// `X-GitHub-Delivery` is applied once.
+pub async fn receive(
+    headers: HeaderMap,
+    State(AppState { client, github_token: token, storage, .. }): State<AppState>,
+    body: Bytes,
+) -> Result<Json<WebhookOutcome>, (StatusCode, String)> {
+    let secret = std::env::var("GITHUB_WEBHOOK_SECRET").ok().filter(|s| !s.is_empty()).ok_or((
+        StatusCode::NOT_FOUND,
+        "webhook receiver is disabled".to_string(),
+    ))?;
+    if !verify_signature(&secret, &headers, &body) {
+        return Err((StatusCode::UNAUTHORIZED, "invalid webhook signature".to_string()));
+    }
+
+    let event = headers.get("X-GitHub-Event").and_then(|v| v.to_str().ok()).unwrap_or_default().to_string();
+    // Pushes add to stored counts, so a redelivered event must not apply twice.
+    let delivery = headers.get("X-GitHub-Delivery").and_then(|v| v.to_str().ok()).map(str::trim).filter(|d| !d.is_empty());
+    let Some(delivery) = delivery else {
+        return Err((StatusCode::BAD_REQUEST, "missing X-GitHub-Delivery header".to_string()));
+    };
+    let first_delivery = storage
+        .record_delivery(delivery, DELIVERY_MEMORY_SECS)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !first_delivery {
+        tracing::info!("Ignoring redelivered webhook {delivery}");
+        return Ok(Json(WebhookOutcome { event, duplicate: true, ..WebhookOutcome::default() }));
+    }
+    let invalid = |e: serde_json::Error| (StatusCode::BAD_REQUEST, format!("invalid {event} payload: {e}"));
+
+    let mut outcome = match event.as_str() {
+        "push" => handle_push(&storage, serde_json::from_slice(&body).map_err(invalid)?),
+        "create" => handle_create(&storage, serde_json::from_slice(&body).map_err(invalid)?),
+        _ => WebhookOutcome::default(),
+    };
+    outcome.event = event;
+
+    for username in &outcome.rescans {
+        let options = match storage.latest_scan(username) {
+            Ok(Some(stored)) => ScanOptions::new(stored.result.mode, stored.result.limits),
+            _ => ScanOptions::new(ScanMode::Full, ScanLimits::ceiling()),
+        };
+        crate::spawn_refresh(client.clone(), token.clone(), storage.clone(), vec![username.clone()], options);
+    }
+
+    Ok(Json(outcome))
+}
+
+fn handle_push(storage: &Storage, push: PushEvent) -> WebhookOutcome {
+    let mut outcome = WebhookOutcome::default();
+    let repo = &push.repository;
+    // Scans count commits on the default branch only.
+    if push.git_ref != format!("refs/heads/{}", repo.default_branch) {
+        return outcome;
+    }
+
+    let mut by_user: BTreeMap<String, Vec<&PushCommit>> = BTreeMap::new();
+    for commit in push.commits.iter().filter(|c| c.distinct && !is_bot(c)) {
+        if let Some(username) = &commit.author.username {
+            by_user.entry(username.to_lowercase()).or_default().push(commit);
+        }
+    }
+
+    let watched = watched_orgs().contains(&repo.owner.login.to_lowercase());
+    for (username, commits) in by_user {
+        let touches_move = commits
+            .iter()
+            .flat_map(|c| c.added.iter().chain(&c.modified))
+            .any(|p| p.ends_with(".move") || scan::is_manifest(p));
+
+        let stored = match storage.latest_scan(&username) {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!("Webhook could not load scan of {username}: {e}");
+                continue;
+            }
+        };
+        let Some(stored) = stored else {
+            if watched && touches_move {
+                outcome.rescans.push(username);
+            }
+            continue;
+        };
+
+        let mut result = stored.result;
+        let Some(entry) = result.repositories.iter_mut().find(|r| r.repo_name.eq_ignore_ascii_case(&repo.full_name))
+        else {
+            if touches_move {
+                outcome.rescans.push(username);
+            }
+            continue;
+        };
+
+        // Quick scans never counted commits, so there is nothing to add to.
+        if result.mode == ScanMode::Quick {
+            continue;
+        }
+        let added = commits.len() as u32;
+        entry.commit_count += added;
+        entry.last_commit_at = commits.iter().filter_map(|c| c.timestamp.clone()).chain(entry.last_commit_at.take()).max();
+        // Pushes list commits oldest first; later re-scans count from the head.
+        if let Some(head) = commits.last()
+            && let Some(timestamp) = &head.timestamp
+        {
+            entry.commit_cursor = Some(scan::CommitCursor { sha: head.id.clone(), committed_at: timestamp.clone() });
+        }
+        result.total_commits += added;
+        result.last_commit_at = result.repositories.iter().filter_map(|r| r.last_commit_at.clone()).max();
+        result.repositories.sort_by_key(|r| std::cmp::Reverse(r.commit_count));
+
+        match storage.save_scan(&result) {
+            Ok(()) => outcome.updated.push(result.username),
+            Err(e) => tracing::warn!("Webhook could not store scan of {username}: {e}"),
+        }
+    }
+
+    outcome
+}
+
+/// A new default branch means a new repository; tracked owners are re-scanned
+/// so it is picked up once it holds Move code.
+fn handle_create(storage: &Storage, create: CreateEvent) -> WebhookOutcome {
+    let mut outcome = WebhookOutcome::default();
+    if create.ref_type != "branch" || create.git_ref != create.master_branch {
+        return outcome;
+    }
+
+    let username = create.sender.login;
+    let known_repo = match storage.latest_scan(&username) {
+        Ok(Some(stored)) => {
+            stored.result.repositories.iter().any(|r| r.repo_name.eq_ignore_ascii_case(&create.repository.full_name))
+        }
+        _ => return outcome,
+    };
+    if !known_repo {
+        outcome.rescans.push(username);
+    }
+    outcome
+}
+
+/// Reuses the commit bot rules on the push payload's author and committer.
+fn is_bot(commit: &PushCommit) -> bool {
+    authorship::is_bot_commit(&serde_json::json!({
+        "author": { "login": commit.author.username },
+        "committer": { "login": commit.committer.username },
+        "commit": {
+            "author": { "name": commit.author.name },
+            "committer": { "name": commit.committer.name },
+        },
+    }))
+}
@@ -0,0 +1,199 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    authorship, github, i18n, pacing,
+    policy::days_since_epoch,
+    profile::iso_date,
+    reporting,
+    scan::{self, ScanLimits, UserMoveFilesResponse},
+};
+
+// ------------------- Windows -------------------
+
+/// Longest event window accepted, in days.
+const MAX_WINDOW_DAYS: u64 = 366;
+
+/// Commits per user and period whose diff is fetched to count lines added;
+/// commits past it count towards commits only.
+const MAX_COMMIT_DETAILS: usize = 100;
+
+/// An event such as a hackathon, as whole UTC days from `start` to `end`
+/// (`YYYY-MM-DD`, inclusive).
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventWindow {
+    pub start: String,
+    pub end: String,
+}
+
+/// A validated [`EventWindow`] with its baseline: the same number of days
+/// immediately before it.
+#[derive(Debug, Clone)]
+pub struct Window {
+    start_day: u64,
+    end_day: u64,
+}
+
+impl EventWindow {
+    pub fn parse(&self) -> Result<Window, i18n::Message> {
+        let day = |date: &str| {
+            let date = date.trim();
+            days_since_epoch(date)
+                .filter(|_| date.len() == 10)
+                .ok_or_else(|| i18n::Message::new("window_date_invalid").arg("date", date))
+        };
+        let (start_day, end_day) = (day(&self.start)?, day(&self.end)?);
+        if start_day > end_day {
+            return Err(i18n::Message::new("window_reversed").arg("start", self.start.trim()).arg("end", self.end.trim()));
+        }
+        if end_day - start_day + 1 > MAX_WINDOW_DAYS {
+            return Err(i18n::Message::new("window_too_long").arg("max", MAX_WINDOW_DAYS));
+        }
+        Ok(Window { start_day, end_day })
+    }
+}
+
+impl Window {
+    fn days(&self) -> u64 {
+        self.end_day - self.start_day + 1
+    }
+
+    /// `since`/`until` bounds of the event.
+    fn event_range(&self) -> (String, String) {
+        range(self.start_day, self.end_day)
+    }
+
+    /// `since`/`until` bounds of the baseline period before the event.
+    fn baseline_range(&self) -> (String, String) {
+        range(self.start_day.saturating_sub(self.days()), self.start_day.saturating_sub(1))
+    }
+}
+
+fn range(first_day: u64, last_day: u64) -> (String, String) {
+    (format!("{}T00:00:00Z", iso_date(first_day * 86_400)), format!("{}T23:59:59Z", iso_date(last_day * 86_400)))
+}
+
+// ------------------- Activity -------------------
+
+/// A user's commits and Move lines added during the event, against the same
+/// repositories' activity over the baseline period, so work that predates
+/// the event does not count towards it. Deltas are negative when the user
+/// was more active before the event.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WindowActivity {
+    pub start: String,
+    pub end: String,
+    pub baseline_start: String,
+    pub baseline_end: String,
+    pub window_commits: u32,
+    pub baseline_commits: u32,
+    pub delta_commits: i64,
+    /// Lines added to `.move` files.
+    pub window_loc: u32,
+    pub baseline_loc: u32,
+    /// Unset when the LOC figures are partial, since the two periods may
+    /// then cover different shares of their commits.
+    pub delta_loc: Option<i64>,
+    /// A period had more than `MAX_COMMIT_DETAILS` commits, so the LOC
+    /// figures cover only some of them.
+    pub loc_is_partial: bool,
+    /// Repositories with activity in either period.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub repositories: Vec<RepositoryWindowActivity>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryWindowActivity {
+    pub repo: String,
+    pub window_commits: u32,
+    pub baseline_commits: u32,
+    pub window_loc: u32,
+    pub baseline_loc: u32,
+}
+
+/// Lines added to `.move` files by the commit `sha`.
+async fn move_lines_added(
+    client: &Client,
+    token: &str,
+    repo: &str,
+    sha: &str,
+) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
+    let url = format!("https://api.github.com/repos/{repo}/commits/{sha}");
+    let resp = github::send(github::EndpointClass::Commits, github::get(client, token, &url)).await?;
+    if !resp.status().is_success() {
+        reporting::github_response(&url, resp.status());
+        return Ok(0);
+    }
+    let commit: serde_json::Value = resp.json().await?;
+    let added = commit["files"]
+        .as_array()
+        .map(|files| {
+            files
+                .iter()
+                .filter(|f| f["filename"].as_str().is_some_and(|name| name.ends_with(".move")))
+                .map(|f| f["additions"].as_u64().unwrap_or(0) as u32)
+                .sum()
+        })
+        .unwrap_or(0);
+    pacing::pause().await;
+    Ok(added)
+}
+
+/// Measures the event and baseline activity of `result`'s user and aliases
+/// in the Move repositories of the scan.
+pub async fn measure(
+    client: &Client,
+    token: &str,
+    result: &UserMoveFilesResponse,
+    window: &Window,
+) -> Result<WindowActivity, Box<dyn std::error::Error + Send + Sync>> {
+    let usernames: Vec<String> = std::iter::once(&result.username).chain(&result.aliases).cloned().collect();
+    let max_pages = ScanLimits::ceiling().max_commit_pages;
+    let (start, end) = window.event_range();
+    let (baseline_start, baseline_end) = window.baseline_range();
+    let mut activity = WindowActivity {
+        start: start.clone(),
+        end: end.clone(),
+        baseline_start: baseline_start.clone(),
+        baseline_end: baseline_end.clone(),
+        ..Default::default()
+    };
+
+    let mut details_left = [MAX_COMMIT_DETAILS; 2];
+    for repo in &result.repositories {
+        let mut counts = [(0u32, 0u32); 2];
+        for (period, (since, until)) in [(&start, &end), (&baseline_start, &baseline_end)].into_iter().enumerate() {
+            let mut commits = scan::fetch_commits_between(client, token, &repo.repo_name, &usernames, since, until, max_pages).await?;
+            authorship::exclude_bots(&mut commits);
+            counts[period].0 = commits.len() as u32;
+            for sha in commits.iter().filter_map(|c| c["sha"].as_str()) {
+                if details_left[period] == 0 {
+                    activity.loc_is_partial = true;
+                    break;
+                }
+                details_left[period] -= 1;
+                counts[period].1 += move_lines_added(client, token, &repo.repo_name, sha).await?;
+            }
+            pacing::pause().await;
+        }
+
+        let [(window_commits, window_loc), (baseline_commits, baseline_loc)] = counts;
+        activity.window_commits += window_commits;
+        activity.window_loc += window_loc;
+        activity.baseline_commits += baseline_commits;
+        activity.baseline_loc += baseline_loc;
+        if window_commits + baseline_commits > 0 {
+            activity.repositories.push(RepositoryWindowActivity {
+                repo: repo.repo_name.clone(),
+                window_commits,
+                baseline_commits,
+                window_loc,
+                baseline_loc,
+            });
+        }
+    }
+
+    activity.delta_commits = activity.window_commits as i64 - activity.baseline_commits as i64;
+    activity.delta_loc = (!activity.loc_is_partial).then(|| activity.window_loc as i64 - activity.baseline_loc as i64);
+    Ok(activity)
+}